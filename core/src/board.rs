@@ -0,0 +1,77 @@
+use rnglib::{Language, RNG};
+use serde::{Deserialize, Serialize};
+
+use crate::container::HyphaContainer;
+use crate::dep::HyphaDep;
+use crate::id::HyphaId;
+use crate::item::HyphaItem;
+use crate::list::HyphaList;
+use crate::preferences::HyphaPreferencesOverride;
+use crate::r#ref::HyphaFileIssueRef;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HyphaBoard {
+  #[serde(default)]
+  pub id: HyphaId,
+  pub title: String,
+  #[serde(default)]
+  pub lists: Vec<HyphaList>,
+  #[serde(default)]
+  pub deps: Vec<HyphaDep<HyphaFileIssueRef>>,
+  #[serde(default, skip_serializing_if = "HyphaPreferencesOverride::is_empty")]
+  pub preferences: HyphaPreferencesOverride,
+}
+
+impl HyphaItem for HyphaBoard {
+  fn id(&self) -> &HyphaId {
+    &self.id
+  }
+
+  fn title(&self) -> &str {
+    &self.title
+  }
+}
+
+impl HyphaBoard {
+  /// Board without any lists.
+  pub fn new(title: String) -> Self {
+    Self {
+      id: HyphaId::new(),
+      title,
+      lists: vec![],
+      deps: vec![],
+      preferences: HyphaPreferencesOverride::default(),
+    }
+  }
+}
+
+impl HyphaContainer for HyphaBoard {
+  type Item = HyphaList;
+  type Ref = HyphaFileIssueRef;
+
+  fn items(&self) -> &Vec<Self::Item> {
+    &self.lists
+  }
+
+  fn items_mut(&mut self) -> &mut Vec<Self::Item> {
+    &mut self.lists
+  }
+}
+
+impl Default for HyphaBoard {
+  fn default() -> Self {
+    let rng = RNG::from(&Language::Roman);
+    let title = rng.generate_name();
+    Self {
+      id: HyphaId::new(),
+      title,
+      lists: vec![
+        HyphaList::default(),
+        HyphaList::default(),
+        HyphaList::default(),
+      ],
+      deps: vec![],
+      preferences: HyphaPreferencesOverride::default(),
+    }
+  }
+}
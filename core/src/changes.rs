@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+
+use crate::{
+  board::HyphaBoard, dep::HyphaDep, id::HyphaId, list::HyphaList,
+  r#ref::HyphaFileIssueRef,
+};
+
+/// Boards, lists, issues and deps marked by the edits made to a file since it
+/// was loaded, so storages can write just those.
+///
+/// Each mark holds the revision of the edit and where the item now is, or
+/// `None` once it is removed. Edits that move an item mark it again at its new
+/// place and items whose position shifted are marked too.
+#[derive(Debug, Clone, Default)]
+pub struct HyphaChanges {
+  pub(crate) session: HyphaId,
+  pub(crate) revision: u64,
+  pub(crate) meta: u64,
+  pub(crate) boards: HashMap<HyphaId, HyphaChange<()>>,
+  pub(crate) lists: HashMap<HyphaId, HyphaChange<HyphaId>>,
+  pub(crate) issues: HashMap<HyphaId, HyphaChange<(HyphaId, HyphaId)>>,
+  pub(crate) deps: HashMap<(HyphaId, HyphaId), HyphaChange<()>>,
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct HyphaChange<Parent> {
+  pub(crate) revision: u64,
+  pub(crate) parent: Option<Parent>,
+}
+
+// Pending changes say nothing about what a file holds.
+impl PartialEq for HyphaChanges {
+  fn eq(&self, _: &Self) -> bool {
+    true
+  }
+}
+
+impl HyphaChanges {
+  /// Drops every mark and starts a new session, for when the whole file is
+  /// replaced rather than edited, so the next save compares everything.
+  pub fn reset(&mut self) {
+    *self = Self::default();
+  }
+
+  fn next(&mut self) -> u64 {
+    self.revision = self.revision.saturating_add(1);
+    self.revision
+  }
+
+  pub(crate) fn meta(&mut self) {
+    self.meta = self.next();
+  }
+
+  pub(crate) fn board(&mut self, board: &HyphaBoard) {
+    let revision = self.next();
+    self.boards.insert(
+      board.id.clone(),
+      HyphaChange {
+        revision,
+        parent: Some(()),
+      },
+    );
+  }
+
+  /// Marks a board along with its lists, issues and deps.
+  pub(crate) fn board_tree(&mut self, board: &HyphaBoard) {
+    self.board(board);
+    for list in board.lists.iter() {
+      self.list_tree(&board.id, list);
+    }
+    for dep in board.deps.iter() {
+      self.dep(dep, true);
+    }
+  }
+
+  pub(crate) fn removed_board(&mut self, board: &HyphaBoard) {
+    let revision = self.next();
+    self.boards.insert(
+      board.id.clone(),
+      HyphaChange {
+        revision,
+        parent: None,
+      },
+    );
+    for list in board.lists.iter() {
+      self.removed_list(list);
+    }
+    for dep in board.deps.iter() {
+      self.dep(dep, false);
+    }
+  }
+
+  pub(crate) fn replaced_board(&mut self, old: &HyphaBoard, new: &HyphaBoard) {
+    if old.lists == new.lists && old.deps == new.deps {
+      self.board(new);
+    } else {
+      self.removed_board(old);
+      self.board_tree(new);
+    }
+  }
+
+  pub(crate) fn list(&mut self, board: &HyphaId, list: &HyphaList) {
+    let revision = self.next();
+    self.lists.insert(
+      list.id.clone(),
+      HyphaChange {
+        revision,
+        parent: Some(board.clone()),
+      },
+    );
+  }
+
+  /// Marks a list along with its issues.
+  pub(crate) fn list_tree(&mut self, board: &HyphaId, list: &HyphaList) {
+    self.list(board, list);
+    for issue in list.issues.iter() {
+      self.issue(board, &list.id, &issue.id);
+    }
+  }
+
+  pub(crate) fn removed_list(&mut self, list: &HyphaList) {
+    let revision = self.next();
+    self.lists.insert(
+      list.id.clone(),
+      HyphaChange {
+        revision,
+        parent: None,
+      },
+    );
+    for issue in list.issues.iter() {
+      self.removed_issue(&issue.id);
+    }
+  }
+
+  pub(crate) fn replaced_list(
+    &mut self,
+    board: &HyphaId,
+    old: &HyphaList,
+    new: &HyphaList,
+  ) {
+    if old.issues == new.issues {
+      self.list(board, new);
+    } else {
+      self.removed_list(old);
+      self.list_tree(board, new);
+    }
+  }
+
+  pub(crate) fn issue(
+    &mut self,
+    board: &HyphaId,
+    list: &HyphaId,
+    id: &HyphaId,
+  ) {
+    let revision = self.next();
+    self.issues.insert(
+      id.clone(),
+      HyphaChange {
+        revision,
+        parent: Some((board.clone(), list.clone())),
+      },
+    );
+  }
+
+  pub(crate) fn removed_issue(&mut self, id: &HyphaId) {
+    let revision = self.next();
+    self.issues.insert(
+      id.clone(),
+      HyphaChange {
+        revision,
+        parent: None,
+      },
+    );
+  }
+
+  pub(crate) fn dep(
+    &mut self,
+    dep: &HyphaDep<HyphaFileIssueRef>,
+    present: bool,
+  ) {
+    let revision = self.next();
+    self.deps.insert(
+      (dep.left.issue.clone(), dep.right.issue.clone()),
+      HyphaChange {
+        revision,
+        parent: present.then_some(()),
+      },
+    );
+  }
+}
@@ -0,0 +1,67 @@
+use crate::{id::HyphaId, item::HyphaItem, r#ref::HyphaRef};
+
+pub trait HyphaContainer {
+  type Item: HyphaItem;
+  type Ref: HyphaRef;
+
+  fn items(&self) -> &Vec<Self::Item>;
+  fn items_mut(&mut self) -> &mut Vec<Self::Item>;
+}
+
+pub trait HyphaContainerOps: HyphaContainer {
+  #[allow(dead_code, reason = "Future use.")]
+  fn swap(&mut self, left: &HyphaId, right: &HyphaId) -> bool;
+  fn move_to(&mut self, id: &HyphaId, index: usize) -> bool;
+  fn insert_at(&mut self, index: usize, item: Self::Item);
+}
+
+impl<
+    Item: HyphaItem,
+    Ref: HyphaRef,
+    Container: HyphaContainer<Item = Item, Ref = Ref>,
+  > HyphaContainerOps for Container
+{
+  fn swap(&mut self, left: &HyphaId, right: &HyphaId) -> bool {
+    let left_idx = match self
+      .items()
+      .iter()
+      .enumerate()
+      .find(|(_, item)| item.id() == left)
+    {
+      Some((idx, _)) => idx,
+      None => {
+        return false;
+      }
+    };
+    let right_idx = match self
+      .items()
+      .iter()
+      .enumerate()
+      .find(|(_, item)| item.id() == right)
+    {
+      Some((idx, _)) => idx,
+      None => {
+        return false;
+      }
+    };
+    self.items_mut().swap(left_idx, right_idx);
+    true
+  }
+
+  fn move_to(&mut self, id: &HyphaId, index: usize) -> bool {
+    let idx = match self.items().iter().position(|item| item.id() == id) {
+      Some(idx) => idx,
+      None => {
+        return false;
+      }
+    };
+    let item = self.items_mut().remove(idx);
+    self.insert_at(index, item);
+    true
+  }
+
+  fn insert_at(&mut self, index: usize, item: Self::Item) {
+    let items = self.items_mut();
+    items.insert(index.min(items.len()), item);
+  }
+}
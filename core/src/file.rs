@@ -0,0 +1,714 @@
+use chrono::{DateTime, NaiveDateTime, TimeDelta, Utc};
+use directories::ProjectDirs;
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+  cmp::Reverse,
+  collections::HashSet,
+  env,
+  path::{Path, PathBuf},
+  time::Duration,
+};
+use thiserror::Error;
+use tokio::{fs, io, task};
+use uuid::Uuid;
+
+use crate::{
+  board::HyphaBoard,
+  changes::HyphaChanges,
+  container::HyphaContainer,
+  dep::HyphaDep,
+  format::HyphaFormat,
+  id::HyphaId,
+  preferences::HyphaPreferences,
+  r#ref::HyphaFileIssueRef,
+  storage::storage,
+  validate::{validate, HyphaReport},
+  view::HyphaView,
+};
+
+type Migration = fn(&mut toml::Table);
+
+pub const VERSION: u32 = 2;
+const MIGRATIONS: [(u32, Migration); 1] = [(1, migrate_v1)];
+const DEFAULT_AUTOSAVE_INTERVAL: Duration = Duration::from_secs(2);
+const BACKUP_COUNT: usize = 5;
+const BACKUP_INTERVAL: TimeDelta = TimeDelta::minutes(10);
+const BACKUP_TIMESTAMP_FORMAT: &str = "%Y%m%dT%H%M%S";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HyphaFile {
+  pub version: u32,
+  #[serde(default)]
+  pub boards: Vec<HyphaBoard>,
+  #[serde(default)]
+  pub deps: Vec<HyphaDep<HyphaFileIssueRef>>,
+  #[serde(default)]
+  pub preferences: HyphaPreferences,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub views: Vec<HyphaView>,
+  #[serde(skip)]
+  pub path: PathBuf,
+  #[serde(skip)]
+  pub changes: HyphaChanges,
+}
+
+impl HyphaContainer for HyphaFile {
+  type Item = HyphaBoard;
+  type Ref = HyphaFileIssueRef;
+
+  fn items(&self) -> &Vec<Self::Item> {
+    &self.boards
+  }
+
+  fn items_mut(&mut self) -> &mut Vec<Self::Item> {
+    &mut self.boards
+  }
+}
+
+impl Default for HyphaFile {
+  fn default() -> Self {
+    let boards = vec![HyphaBoard::default()];
+    if let Ok(path) = HyphaFile::path() {
+      HyphaFile {
+        version: VERSION,
+        path,
+        boards,
+        deps: vec![],
+        preferences: HyphaPreferences::default(),
+        views: vec![],
+        changes: HyphaChanges::default(),
+      }
+    } else {
+      HyphaFile {
+        version: VERSION,
+        boards,
+        path: PathBuf::new(),
+        deps: vec![],
+        preferences: HyphaPreferences::default(),
+        views: vec![],
+        changes: HyphaChanges::default(),
+      }
+    }
+  }
+}
+
+impl HyphaFile {
+  pub fn load(path: &Path) -> Result<HyphaFile, FileError> {
+    let (file, _) = Self::load_validated(path)?;
+    Ok(file)
+  }
+
+  pub fn load_validated(
+    path: &Path,
+  ) -> Result<(HyphaFile, HyphaReport), FileError> {
+    info!("Attempting to load config from: {}", path.display());
+    let loaded = storage(path).load(path)?;
+    info!("Hypha file loaded successfully.");
+    Ok(loaded)
+  }
+
+  pub async fn load_async(path: &Path) -> Result<HyphaFile, FileError> {
+    let (file, _) = Self::load_validated_async(path).await?;
+    Ok(file)
+  }
+
+  pub async fn load_validated_async(
+    path: &Path,
+  ) -> Result<(HyphaFile, HyphaReport), FileError> {
+    let path = path.to_path_buf();
+    task::spawn_blocking(move || Self::load_validated(&path)).await?
+  }
+
+  pub fn path() -> Result<PathBuf, FileError> {
+    if let Ok(path_str) = env::var("HYPHA_FILE") {
+      let expanded_path = expand(&path_str)?;
+      info!(
+        "Using hypha file path from HYPHA_FILE env var: {}",
+        expanded_path.display()
+      );
+      return Ok(expanded_path);
+    }
+
+    if let Some(proj_dirs) = ProjectDirs::from("com", "HyphaApp", "Hypha") {
+      let data_dir = proj_dirs.data_local_dir();
+      let default_path = data_dir.join("hypha.toml");
+      info!("Using default hypha file path: {}", default_path.display());
+      Ok(default_path)
+    } else {
+      error!(
+        "Could not determine project directory for default hypha file path."
+      );
+      Err(FileError::Directory)
+    }
+  }
+
+  pub fn autosave_interval() -> Duration {
+    let Ok(seconds) = env::var("HYPHA_AUTOSAVE") else {
+      return DEFAULT_AUTOSAVE_INTERVAL;
+    };
+    match seconds
+      .parse::<f64>()
+      .ok()
+      .and_then(|seconds| Duration::try_from_secs_f64(seconds).ok())
+    {
+      Some(interval) => {
+        info!(
+          "Using autosave interval from HYPHA_AUTOSAVE env var: {seconds}s"
+        );
+        interval
+      }
+      None => {
+        warn!("Invalid HYPHA_AUTOSAVE env var '{seconds}', using default.");
+        DEFAULT_AUTOSAVE_INTERVAL
+      }
+    }
+  }
+
+  pub fn reload(&mut self) -> Result<(), FileError> {
+    let loaded = Self::load(&self.path)?;
+    *self = loaded;
+    Ok(())
+  }
+
+  pub async fn reload_async(&mut self) -> Result<(), FileError> {
+    let loaded = Self::load_async(&self.path).await?;
+    *self = loaded;
+    Ok(())
+  }
+
+  pub fn save(&self) -> Result<(), FileError> {
+    let path = self.path.as_path();
+    info!("Attempting to save hypha file to: {}", path.display());
+    storage(path).save(self)?;
+    info!("Hypha file saved successfully to: {}", path.display());
+    Ok(())
+  }
+
+  pub async fn save_async(&self) -> Result<(), FileError> {
+    let file = self.clone();
+    task::spawn_blocking(move || file.save()).await?
+  }
+
+  /// Lists backups of the file at `path`, newest first.
+  pub fn backups(path: &Path) -> Result<Vec<HyphaBackup>, FileError> {
+    let Some(parent_dir) = path.parent() else {
+      return Ok(Vec::new());
+    };
+    let entries = match std::fs::read_dir(parent_dir) {
+      Ok(entries) => entries,
+      Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+      Err(e) => return Err(FileError::Io(e)),
+    };
+
+    let mut backups = Vec::new();
+    for entry in entries {
+      let entry_path = entry?.path();
+      if let Some(backup) = HyphaBackup::parse(path, &entry_path) {
+        backups.push(backup);
+      }
+    }
+    backups.sort_by_key(|backup| Reverse(backup.timestamp));
+    Ok(backups)
+  }
+
+  pub async fn backups_async(
+    path: &Path,
+  ) -> Result<Vec<HyphaBackup>, FileError> {
+    let Some(parent_dir) = path.parent() else {
+      return Ok(Vec::new());
+    };
+    let mut entries = match fs::read_dir(parent_dir).await {
+      Ok(entries) => entries,
+      Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+      Err(e) => return Err(FileError::Io(e)),
+    };
+
+    let mut backups = Vec::new();
+    while let Some(entry) = entries.next_entry().await? {
+      if let Some(backup) = HyphaBackup::parse(path, &entry.path()) {
+        backups.push(backup);
+      }
+    }
+    backups.sort_by_key(|backup| Reverse(backup.timestamp));
+    Ok(backups)
+  }
+
+  /// Replaces the file at `path` with a backup, backing up the current
+  /// contents first.
+  pub async fn restore_async(
+    path: &Path,
+    backup: &HyphaBackup,
+  ) -> Result<HyphaFile, FileError> {
+    info!(
+      "Restoring hypha file {} from {}",
+      path.display(),
+      backup.path.display()
+    );
+    let content = fs::read_to_string(&backup.path).await?;
+    let (file, _) = Self::parse(&content, path)?;
+
+    Self::backup_async(path, true).await?;
+    file.save_async().await?;
+    Ok(file)
+  }
+
+  pub(crate) fn backup(path: &Path, force: bool) -> Result<(), FileError> {
+    if !path.exists() {
+      return Ok(());
+    }
+
+    let backups = Self::backups(path)?;
+    let now = Utc::now();
+    let due = backups.first().is_none_or(|latest| {
+      now.signed_duration_since(latest.timestamp) >= BACKUP_INTERVAL
+    });
+    if force || due {
+      let backup_path = HyphaBackup::path(path, now);
+      std::fs::copy(path, &backup_path)
+        .map_err(|e| FileError::Backup(backup_path.clone(), e))?;
+      info!("Backed up hypha file to: {}", backup_path.display());
+    }
+
+    for backup in Self::backups(path)?.iter().skip(BACKUP_COUNT) {
+      if let Err(e) = std::fs::remove_file(&backup.path) {
+        warn!("Failed to remove old backup {}: {e}", backup.path.display());
+      }
+    }
+    Ok(())
+  }
+
+  async fn backup_async(path: &Path, force: bool) -> Result<(), FileError> {
+    if !fs::try_exists(path).await? {
+      return Ok(());
+    }
+
+    let backups = Self::backups_async(path).await?;
+    let now = Utc::now();
+    let due = backups.first().is_none_or(|latest| {
+      now.signed_duration_since(latest.timestamp) >= BACKUP_INTERVAL
+    });
+    if force || due {
+      let backup_path = HyphaBackup::path(path, now);
+      fs::copy(path, &backup_path)
+        .await
+        .map_err(|e| FileError::Backup(backup_path.clone(), e))?;
+      info!("Backed up hypha file to: {}", backup_path.display());
+    }
+
+    for backup in Self::backups_async(path).await?.iter().skip(BACKUP_COUNT) {
+      if let Err(e) = fs::remove_file(&backup.path).await {
+        warn!("Failed to remove old backup {}: {e}", backup.path.display());
+      }
+    }
+    Ok(())
+  }
+
+  pub(crate) fn parse(
+    content: &str,
+    path: &Path,
+  ) -> Result<(HyphaFile, HyphaReport), FileError> {
+    let mut table = HyphaFormat::from_path(path).parse(content)?;
+    Self::migrate(&mut table)?;
+    let mut file: HyphaFile = table.try_into()?;
+    file.path = path.to_path_buf();
+    Ok(file.check(content))
+  }
+
+  // Reports problems in a freshly loaded file before regenerating duplicate
+  // ids, locating them in `content` when it is TOML.
+  pub(crate) fn check(mut self, content: &str) -> (HyphaFile, HyphaReport) {
+    let report = validate(&self, content);
+    for problem in report.problems.iter() {
+      warn!("Problem in hypha file {}: {problem}", self.path.display());
+    }
+    self.uniq();
+    (self, report)
+  }
+
+  /// Upgrades a document written by an older version one step at a time.
+  fn migrate(table: &mut toml::Table) -> Result<(), FileError> {
+    let version = match table.get("version") {
+      Some(value) => value
+        .as_integer()
+        .and_then(|version| u32::try_from(version).ok())
+        .filter(|version| *version > 0)
+        .ok_or_else(|| FileError::InvalidVersion(value.to_string()))?,
+      None => 1,
+    };
+    if version > VERSION {
+      return Err(FileError::UnsupportedVersion(version, VERSION));
+    }
+
+    for (from, migration) in MIGRATIONS {
+      if version <= from {
+        info!("Migrating hypha file from version {from}");
+        migration(table);
+      }
+    }
+    table.insert("version".to_string(), i64::from(VERSION).into());
+    Ok(())
+  }
+
+  fn uniq(&mut self) {
+    let mut seen = HashSet::new();
+    for board in self.boards.iter_mut() {
+      if !seen.insert(board.id.clone()) {
+        board.id = HyphaId::new();
+      }
+      for list in board.lists.iter_mut() {
+        if !seen.insert(list.id.clone()) {
+          list.id = HyphaId::new();
+        }
+        for issue in list.issues.iter_mut() {
+          if !seen.insert(issue.id.clone()) {
+            issue.id = HyphaId::new();
+          }
+        }
+      }
+    }
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HyphaBackup {
+  pub path: PathBuf,
+  pub timestamp: DateTime<Utc>,
+}
+
+impl HyphaBackup {
+  // Backups live next to the file as `<file name>.<timestamp>.bak`.
+  fn path(path: &Path, timestamp: DateTime<Utc>) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(format!(
+      ".{}.bak",
+      timestamp.format(BACKUP_TIMESTAMP_FORMAT)
+    ));
+    path.with_file_name(name)
+  }
+
+  fn parse(path: &Path, candidate: &Path) -> Option<Self> {
+    let name = path.file_name()?.to_str()?;
+    let timestamp = candidate
+      .file_name()?
+      .to_str()?
+      .strip_prefix(name)?
+      .strip_prefix('.')?
+      .strip_suffix(".bak")?;
+    let timestamp =
+      NaiveDateTime::parse_from_str(timestamp, BACKUP_TIMESTAMP_FORMAT)
+        .ok()?
+        .and_utc();
+    Some(Self {
+      path: candidate.to_path_buf(),
+      timestamp,
+    })
+  }
+}
+
+/// Expands `~` and environment variables in a path typed by the user.
+pub fn expand(path: &str) -> Result<PathBuf, FileError> {
+  let path = path.trim();
+  if path.is_empty() {
+    return Err(FileError::EmptyPath);
+  }
+  let expanded = shellexpand::full(path)
+    .map_err(|e| FileError::Expansion(path.to_string(), e.to_string()))?;
+  Ok(PathBuf::from(expanded.to_string()))
+}
+
+// Temp files are named `<file name>.<pid>.<random>.tmp` so two processes or
+// two saves racing on the same file never write to the same temp file.
+pub(crate) fn temp_path(path: &Path) -> PathBuf {
+  let mut name = path.file_name().unwrap_or_default().to_os_string();
+  name.push(format!(
+    ".{}.{}.tmp",
+    std::process::id(),
+    Uuid::new_v4().simple()
+  ));
+  path.with_file_name(name)
+}
+
+// Version 1 files have no ids and reference items by title.
+fn migrate_v1(table: &mut toml::Table) {
+  let mut boards = Vec::new();
+  for board in tables_mut(table, "boards") {
+    let id = ensure_id(board);
+    let mut lists = Vec::new();
+    for list in tables_mut(board, "lists") {
+      let id = ensure_id(list);
+      let issues = tables_mut(list, "issues")
+        .map(|issue| MigrationNode {
+          id: ensure_id(issue),
+          title: title(issue),
+          children: Vec::new(),
+        })
+        .collect();
+      lists.push(MigrationNode {
+        id,
+        title: title(list),
+        children: issues,
+      });
+    }
+    boards.push(MigrationNode {
+      id,
+      title: title(board),
+      children: lists,
+    });
+  }
+
+  for dep in tables_mut(table, "deps") {
+    migrate_v1_dep(&boards, dep);
+  }
+  for board in tables_mut(table, "boards") {
+    for dep in tables_mut(board, "deps") {
+      migrate_v1_dep(&boards, dep);
+    }
+  }
+}
+
+fn migrate_v1_dep(boards: &[MigrationNode], dep: &mut toml::Table) {
+  for side in ["left", "right"] {
+    let Some(issue_ref) = dep.get_mut(side).and_then(toml::Value::as_table_mut)
+    else {
+      continue;
+    };
+    let field = |name: &str| {
+      issue_ref
+        .get(name)
+        .and_then(toml::Value::as_str)
+        .unwrap_or_default()
+        .to_string()
+    };
+    let Some((_, board)) = MigrationNode::find(boards, &field("board")) else {
+      continue;
+    };
+    let Some((stage, list)) =
+      MigrationNode::find(&board.children, &field("list"))
+    else {
+      continue;
+    };
+    let Some((_, issue)) = MigrationNode::find(&list.children, &field("issue"))
+    else {
+      continue;
+    };
+
+    issue_ref.insert("issue".to_string(), issue.id.clone().into());
+    issue_ref.insert("list".to_string(), list.id.clone().into());
+    issue_ref.insert(
+      "stage".to_string(),
+      i64::try_from(stage).unwrap_or_default().into(),
+    );
+    issue_ref.insert("board".to_string(), board.id.clone().into());
+  }
+}
+
+struct MigrationNode {
+  id: String,
+  title: String,
+  children: Vec<MigrationNode>,
+}
+
+impl MigrationNode {
+  fn find<'a>(
+    nodes: &'a [MigrationNode],
+    key: &str,
+  ) -> Option<(usize, &'a MigrationNode)> {
+    nodes
+      .iter()
+      .enumerate()
+      .find(|(_, node)| node.id == key)
+      .or_else(|| nodes.iter().enumerate().find(|(_, node)| node.title == key))
+  }
+}
+
+fn tables_mut<'a>(
+  table: &'a mut toml::Table,
+  key: &str,
+) -> impl Iterator<Item = &'a mut toml::Table> {
+  table
+    .get_mut(key)
+    .and_then(toml::Value::as_array_mut)
+    .into_iter()
+    .flatten()
+    .filter_map(toml::Value::as_table_mut)
+}
+
+fn ensure_id(table: &mut toml::Table) -> String {
+  if let Some(id) = table.get("id").and_then(toml::Value::as_str) {
+    return id.to_string();
+  }
+  let id = HyphaId::new().to_string();
+  table.insert("id".to_string(), id.clone().into());
+  id
+}
+
+fn title(table: &toml::Table) -> String {
+  table
+    .get("title")
+    .and_then(toml::Value::as_str)
+    .unwrap_or_default()
+    .to_string()
+}
+
+#[derive(Error, Debug)]
+pub enum FileError {
+  #[error("IO error: {0}")]
+  Io(#[from] io::Error),
+
+  #[error("TOML serialization error: {0}")]
+  TomlSer(#[from] toml::ser::Error),
+
+  #[error("TOML deserialization error: {0}")]
+  TomlDe(#[from] toml::de::Error),
+
+  #[error("JSON error: {0}")]
+  Json(#[from] serde_json::Error),
+
+  #[error("YAML error: {0}")]
+  Yaml(#[from] serde_yaml::Error),
+
+  #[error("SQLite error: {0}")]
+  Sqlite(#[from] rusqlite::Error),
+
+  #[error("Environment variable error: {0}")]
+  EnvVar(#[from] env::VarError),
+
+  #[error("Could not determine project directory for config path")]
+  Directory,
+
+  #[error("Failed to expand path '{0}': {1}")]
+  Expansion(String, String),
+
+  #[error("Path is empty")]
+  EmptyPath,
+
+  #[error("'{}' already exists", .0.display())]
+  Exists(PathBuf),
+
+  #[error("File version {0} is newer than the supported version {1}")]
+  UnsupportedVersion(u32, u32),
+
+  #[error("Invalid file version {0}")]
+  InvalidVersion(String),
+
+  #[error("Failed to write temporary file '{}': {}", .0.display(), .1)]
+  Temp(PathBuf, io::Error),
+
+  #[error("Failed to sync '{}' to disk: {}", .0.display(), .1)]
+  Sync(PathBuf, io::Error),
+
+  #[error("Failed to back up to '{}': {}", .0.display(), .1)]
+  Backup(PathBuf, io::Error),
+
+  #[error("Failed to replace '{}': {}", .0.display(), .1)]
+  Rename(PathBuf, io::Error),
+
+  #[error("Missing front matter in '{}'", .0.display())]
+  FrontMatter(PathBuf),
+
+  #[error("Background task failed: {0}")]
+  Task(#[from] tokio::task::JoinError),
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{graph::HyphaDepGraph, r#ref::HyphaRef};
+
+  const V1: &str = include_str!("../tests/fixtures/v1.toml");
+  const V2: &str = include_str!("../tests/fixtures/v2.toml");
+
+  fn parse(content: &str) -> Result<(HyphaFile, HyphaReport), FileError> {
+    HyphaFile::parse(content, Path::new("hypha.toml"))
+  }
+
+  // Titles of the boards, lists and issues and the deps between issue titles.
+  fn outline(file: &HyphaFile) -> (Vec<String>, Vec<(String, String)>) {
+    let mut items = Vec::new();
+    for board in file.boards.iter() {
+      for list in board.lists.iter() {
+        for issue in list.issues.iter() {
+          items.push(format!("{}/{}/{}", board.title, list.title, issue.title));
+        }
+      }
+    }
+    let title = |issue_ref: &HyphaFileIssueRef| {
+      issue_ref
+        .get_item_from_container(file)
+        .map(|issue| issue.title.clone())
+        .unwrap_or_default()
+    };
+    let deps = file
+      .deps
+      .iter()
+      .chain(file.boards.iter().flat_map(|board| board.deps.iter()))
+      .map(|dep| (title(&dep.left), title(&dep.right)))
+      .collect();
+    (items, deps)
+  }
+
+  #[test]
+  fn migrates_v1_to_current_version() -> Result<(), FileError> {
+    let (v1, report) = parse(V1)?;
+    assert_eq!(v1.version, VERSION);
+    assert_eq!(report.problems, []);
+
+    let (v2, _) = parse(V2)?;
+    assert_eq!(outline(&v1), outline(&v2));
+    let graph = HyphaDepGraph::new(&v1);
+    assert_eq!(graph.issues().count(), 4);
+    assert!(v1.boards.iter().all(|board| board.id.as_str() != ""));
+    Ok(())
+  }
+
+  #[test]
+  fn loads_current_version_as_is() -> Result<(), FileError> {
+    let (v2, report) = parse(V2)?;
+    assert_eq!(v2.version, VERSION);
+    assert_eq!(report.problems, []);
+    let ids = v2
+      .boards
+      .iter()
+      .map(|board| board.id.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(ids, ["board-work", "board-home"]);
+    assert_eq!(
+      outline(&v2).1,
+      [
+        ("Release".to_string(), "Announce".to_string()),
+        ("Write docs".to_string(), "Release".to_string()),
+      ]
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn treats_missing_version_as_v1() -> Result<(), FileError> {
+    let content = V1.replace("version = 1\n", "");
+    let (file, _) = parse(&content)?;
+    assert_eq!(file.version, VERSION);
+    assert_eq!(outline(&file), outline(&parse(V2)?.0));
+    Ok(())
+  }
+
+  #[test]
+  fn rejects_newer_versions() {
+    let content = V2.replace("version = 2", "version = 3");
+    assert!(matches!(
+      parse(&content),
+      Err(FileError::UnsupportedVersion(3, VERSION))
+    ));
+  }
+
+  #[test]
+  fn rejects_invalid_versions() {
+    for version in ["0", "-1", "\"two\"", "1.5"] {
+      let content = V2.replace("version = 2", &format!("version = {version}"));
+      assert!(
+        matches!(parse(&content), Err(FileError::InvalidVersion(_))),
+        "{version}"
+      );
+    }
+  }
+}
@@ -0,0 +1,175 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::path::Path;
+
+use crate::file::FileError;
+
+/// Serialization format of a hypha file picked by its extension.
+///
+/// Every format is parsed into a TOML table first so the same migrations
+/// upgrade older files regardless of how they are stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HyphaFormat {
+  #[default]
+  Toml,
+  Json,
+  Yaml,
+}
+
+impl HyphaFormat {
+  /// Picks the format from the extension of `path`, falling back to TOML.
+  pub fn from_path(path: &Path) -> Self {
+    let extension = path
+      .extension()
+      .and_then(|extension| extension.to_str())
+      .map(|extension| extension.to_ascii_lowercase());
+    match extension.as_deref() {
+      Some("json") => HyphaFormat::Json,
+      Some("yaml" | "yml") => HyphaFormat::Yaml,
+      _ => HyphaFormat::Toml,
+    }
+  }
+
+  pub fn parse(self, content: &str) -> Result<toml::Table, FileError> {
+    match self {
+      HyphaFormat::Toml => Ok(content.parse::<toml::Table>()?),
+      HyphaFormat::Json => table(serde_json::from_str(content)?),
+      HyphaFormat::Yaml => table(serde_yaml::from_str(content)?),
+    }
+  }
+
+  pub fn serialize<T: Serialize>(self, value: &T) -> Result<String, FileError> {
+    match self {
+      HyphaFormat::Toml => Ok(toml::to_string_pretty(value)?),
+      HyphaFormat::Json => Ok(serde_json::to_string_pretty(value)?),
+      HyphaFormat::Yaml => Ok(serde_yaml::to_string(value)?),
+    }
+  }
+}
+
+// TOML has no null so nulls are dropped and read as missing keys instead.
+fn table(mut value: Value) -> Result<toml::Table, FileError> {
+  strip_nulls(&mut value);
+  Ok(serde_json::from_value(value)?)
+}
+
+fn strip_nulls(value: &mut Value) {
+  match value {
+    Value::Array(items) => {
+      items.retain(|item| !item.is_null());
+      items.iter_mut().for_each(strip_nulls);
+    }
+    Value::Object(fields) => {
+      fields.retain(|_, field| !field.is_null());
+      fields.values_mut().for_each(strip_nulls);
+    }
+    _ => {}
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{file::HyphaFile, testing::sample};
+
+  fn round_trip(format: HyphaFormat, name: &str) -> Result<(), FileError> {
+    let mut file = sample();
+    file.path = Path::new(name).to_path_buf();
+    let content = format.serialize(&file)?;
+    let (parsed, report) = HyphaFile::parse(&content, &file.path)?;
+    assert_eq!(report.problems, []);
+    assert_eq!(parsed, file);
+    Ok(())
+  }
+
+  #[test]
+  fn picks_format_from_extension() {
+    for (name, format) in [
+      ("hypha.toml", HyphaFormat::Toml),
+      ("hypha.json", HyphaFormat::Json),
+      ("hypha.YAML", HyphaFormat::Yaml),
+      ("hypha.yml", HyphaFormat::Yaml),
+      ("hypha", HyphaFormat::Toml),
+    ] {
+      assert_eq!(HyphaFormat::from_path(Path::new(name)), format, "{name}");
+    }
+  }
+
+  #[test]
+  fn round_trips_toml() -> Result<(), FileError> {
+    round_trip(HyphaFormat::Toml, "hypha.toml")
+  }
+
+  #[test]
+  fn round_trips_json() -> Result<(), FileError> {
+    round_trip(HyphaFormat::Json, "hypha.json")
+  }
+
+  #[test]
+  fn round_trips_yaml() -> Result<(), FileError> {
+    round_trip(HyphaFormat::Yaml, "hypha.yaml")
+  }
+
+  #[test]
+  fn formats_agree() -> Result<(), FileError> {
+    let file = sample();
+    let parse = |format: HyphaFormat| -> Result<HyphaFile, FileError> {
+      let content = format.serialize(&file)?;
+      let (mut parsed, _) = HyphaFile::parse(
+        &content,
+        Path::new(match format {
+          HyphaFormat::Toml => "hypha.toml",
+          HyphaFormat::Json => "hypha.json",
+          HyphaFormat::Yaml => "hypha.yaml",
+        }),
+      )?;
+      parsed.path = file.path.clone();
+      Ok(parsed)
+    };
+    let toml = parse(HyphaFormat::Toml)?;
+    assert_eq!(parse(HyphaFormat::Json)?, toml);
+    assert_eq!(parse(HyphaFormat::Yaml)?, toml);
+    Ok(())
+  }
+
+  #[test]
+  fn drops_nulls() -> Result<(), FileError> {
+    let json = r#"{
+      "version": 2,
+      "boards": [{
+        "id": "board",
+        "title": "Work",
+        "lists": [{
+          "id": "list",
+          "title": "Todo",
+          "issues": [{
+            "id": "issue",
+            "title": "a",
+            "priority": null,
+            "estimate": null,
+            "created": null,
+            "tags": ["bug", null]
+          }, null]
+        }]
+      }],
+      "views": null
+    }"#;
+    let (file, _) = HyphaFile::parse(json, Path::new("hypha.json"))?;
+    let issues = file
+      .boards
+      .iter()
+      .flat_map(|board| board.lists.iter())
+      .flat_map(|list| list.issues.iter())
+      .collect::<Vec<_>>();
+    assert_eq!(issues.len(), 1);
+    let issue = issues.first();
+    assert_eq!(issue.and_then(|issue| issue.estimate), None);
+    assert_eq!(issue.and_then(|issue| issue.created), None);
+    assert_eq!(
+      issue.map(|issue| issue.tags.clone()),
+      Some(vec!["bug".to_string()])
+    );
+    assert!(file.views.is_empty());
+    Ok(())
+  }
+}
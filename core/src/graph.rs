@@ -0,0 +1,348 @@
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use thiserror::Error;
+
+use crate::{
+  dep::HyphaDep, file::HyphaFile, id::HyphaId, r#ref::HyphaFileIssueRef,
+};
+
+#[derive(Debug, Clone, Default)]
+pub struct HyphaDepGraph {
+  order: Vec<HyphaId>,
+  issues: HashMap<HyphaId, HyphaFileIssueRef>,
+  dependents: HashMap<HyphaId, Vec<HyphaId>>,
+  prerequisites: HashMap<HyphaId, Vec<HyphaId>>,
+}
+
+impl HyphaDepGraph {
+  pub fn new(file: &HyphaFile) -> Self {
+    let mut graph = Self::default();
+
+    for board in file.boards.iter() {
+      for (stage, list) in board.lists.iter().enumerate() {
+        for issue in list.issues.iter() {
+          graph.order.push(issue.id.clone());
+          graph.issues.insert(
+            issue.id.clone(),
+            HyphaFileIssueRef {
+              issue: issue.id.clone(),
+              list: list.id.clone(),
+              stage,
+              board: board.id.clone(),
+            },
+          );
+        }
+      }
+    }
+
+    let deps = file
+      .deps
+      .iter()
+      .chain(file.boards.iter().flat_map(|board| board.deps.iter()));
+    for dep in deps {
+      if !graph.issues.contains_key(&dep.left.issue)
+        || !graph.issues.contains_key(&dep.right.issue)
+      {
+        continue;
+      }
+      graph
+        .dependents
+        .entry(dep.left.issue.clone())
+        .or_default()
+        .push(dep.right.issue.clone());
+      graph
+        .prerequisites
+        .entry(dep.right.issue.clone())
+        .or_default()
+        .push(dep.left.issue.clone());
+    }
+
+    graph
+  }
+
+  pub fn issues(&self) -> impl Iterator<Item = &HyphaFileIssueRef> {
+    self.order.iter().filter_map(|id| self.issues.get(id))
+  }
+
+  pub fn issue(&self, id: &HyphaId) -> Option<&HyphaFileIssueRef> {
+    self.issues.get(id)
+  }
+
+  pub fn dependents(&self, id: &HyphaId) -> &[HyphaId] {
+    self
+      .dependents
+      .get(id)
+      .map(Vec::as_slice)
+      .unwrap_or_default()
+  }
+
+  pub fn prerequisites(&self, id: &HyphaId) -> &[HyphaId] {
+    self
+      .prerequisites
+      .get(id)
+      .map(Vec::as_slice)
+      .unwrap_or_default()
+  }
+
+  pub fn check(
+    &self,
+    dep: &HyphaDep<HyphaFileIssueRef>,
+  ) -> Result<(), DepError> {
+    let left = &dep.left.issue;
+    let right = &dep.right.issue;
+
+    for id in [left, right] {
+      if !self.issues.contains_key(id) {
+        return Err(DepError::Missing(id.clone()));
+      }
+    }
+    if left == right {
+      return Err(DepError::SelfDependency(left.clone()));
+    }
+    if self.dependents(left).contains(right) {
+      return Err(DepError::Duplicate(left.clone(), right.clone()));
+    }
+    if let Some(path) = self.path(right, left) {
+      return Err(DepError::Cycle(path));
+    }
+
+    Ok(())
+  }
+
+  pub fn cycles(&self) -> Vec<Vec<HyphaId>> {
+    let mut components = Components {
+      graph: self,
+      index: 0,
+      indices: HashMap::new(),
+      lowlinks: HashMap::new(),
+      stack: Vec::new(),
+      on_stack: HashSet::new(),
+      components: Vec::new(),
+    };
+    for id in self.order.iter() {
+      if !components.indices.contains_key(id) {
+        components.visit(id);
+      }
+    }
+
+    components
+      .components
+      .into_iter()
+      .filter(|component| match component.as_slice() {
+        [id] => self.dependents(id).contains(id),
+        _ => true,
+      })
+      .collect()
+  }
+
+  fn path(&self, from: &HyphaId, to: &HyphaId) -> Option<Vec<HyphaId>> {
+    let mut parents = HashMap::<HyphaId, HyphaId>::new();
+    let mut queue = VecDeque::from([from.clone()]);
+    while let Some(current) = queue.pop_front() {
+      if &current == to {
+        let mut path = vec![current.clone()];
+        let mut cursor = current;
+        while let Some(parent) = parents.get(&cursor) {
+          path.push(parent.clone());
+          cursor = parent.clone();
+        }
+        path.reverse();
+        return Some(path);
+      }
+      for dependent in self.dependents(&current) {
+        if dependent != from && !parents.contains_key(dependent) {
+          parents.insert(dependent.clone(), current.clone());
+          queue.push_back(dependent.clone());
+        }
+      }
+    }
+    None
+  }
+}
+
+struct Components<'a> {
+  graph: &'a HyphaDepGraph,
+  index: usize,
+  indices: HashMap<HyphaId, usize>,
+  lowlinks: HashMap<HyphaId, usize>,
+  stack: Vec<HyphaId>,
+  on_stack: HashSet<HyphaId>,
+  components: Vec<Vec<HyphaId>>,
+}
+
+impl Components<'_> {
+  // Tarjan's algorithm with an explicit stack of issues and the position of
+  // the next dependent to look at so long chains can not overflow the stack.
+  fn visit(&mut self, root: &HyphaId) {
+    let graph = self.graph;
+    self.open(root);
+    let mut frames = vec![(root.clone(), 0usize)];
+    while let Some((id, next)) = frames.last_mut() {
+      let id = id.clone();
+      if let Some(dependent) = graph.dependents(&id).get(*next) {
+        *next = next.saturating_add(1);
+        if !self.indices.contains_key(dependent) {
+          self.open(dependent);
+          frames.push((dependent.clone(), 0));
+        } else if self.on_stack.contains(dependent) {
+          let index = self.indices.get(dependent).copied();
+          self.lower(&id, index);
+        }
+        continue;
+      }
+
+      frames.pop();
+      if let Some((parent, _)) = frames.last() {
+        let lowlink = self.lowlinks.get(&id).copied();
+        self.lower(parent, lowlink);
+      }
+      if self.lowlinks.get(&id) == self.indices.get(&id) {
+        self.close(&id);
+      }
+    }
+  }
+
+  fn open(&mut self, id: &HyphaId) {
+    self.indices.insert(id.clone(), self.index);
+    self.lowlinks.insert(id.clone(), self.index);
+    self.index = self.index.saturating_add(1);
+    self.stack.push(id.clone());
+    self.on_stack.insert(id.clone());
+  }
+
+  fn lower(&mut self, id: &HyphaId, candidate: Option<usize>) {
+    if let (Some(lowlink), Some(candidate)) =
+      (self.lowlinks.get_mut(id), candidate)
+    {
+      *lowlink = (*lowlink).min(candidate);
+    }
+  }
+
+  fn close(&mut self, id: &HyphaId) {
+    let mut component = Vec::new();
+    while let Some(member) = self.stack.pop() {
+      self.on_stack.remove(&member);
+      let done = &member == id;
+      component.push(member);
+      if done {
+        break;
+      }
+    }
+    component.reverse();
+    self.components.push(component);
+  }
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum DepError {
+  #[error("Issue {0} does not exist")]
+  Missing(HyphaId),
+
+  #[error("Issue {0} can not depend on itself")]
+  SelfDependency(HyphaId),
+
+  #[error("Issue {1} already depends on {0}")]
+  Duplicate(HyphaId, HyphaId),
+
+  #[error("Dependency would create a cycle through {} issues", .0.len())]
+  Cycle(Vec<HyphaId>),
+
+  #[error("Issues {0} and {1} are in the same file")]
+  SameFile(HyphaId, HyphaId),
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::{board, dep, file, issue_ref, link, list, work};
+
+  #[test]
+  fn check_accepts_new_dep() {
+    let file = work();
+    let graph = HyphaDepGraph::new(&file);
+    assert_eq!(graph.check(&dep(&file, "a", "b")), Ok(()));
+  }
+
+  #[test]
+  fn check_rejects_invalid_deps() {
+    let mut file = work();
+    link(&mut file, &[("a", "b"), ("b", "c")]);
+    let graph = HyphaDepGraph::new(&file);
+
+    let missing = dep(&file, "a", "missing");
+    assert_eq!(
+      graph.check(&missing),
+      Err(DepError::Missing(missing.right.issue.clone()))
+    );
+    let a = issue_ref(&file, "a").issue;
+    let b = issue_ref(&file, "b").issue;
+    let c = issue_ref(&file, "c").issue;
+    assert_eq!(
+      graph.check(&dep(&file, "a", "a")),
+      Err(DepError::SelfDependency(a.clone()))
+    );
+    assert_eq!(
+      graph.check(&dep(&file, "a", "b")),
+      Err(DepError::Duplicate(a.clone(), b.clone()))
+    );
+    assert_eq!(
+      graph.check(&dep(&file, "c", "a")),
+      Err(DepError::Cycle(vec![a, b, c]))
+    );
+  }
+
+  #[test]
+  fn cycles_finds_each_cycle_once() {
+    let mut file = work();
+    link(&mut file, &[("a", "b"), ("b", "a"), ("b", "c"), ("c", "d")]);
+    let graph = HyphaDepGraph::new(&file);
+
+    let a = issue_ref(&file, "a").issue;
+    let b = issue_ref(&file, "b").issue;
+    assert_eq!(graph.cycles(), vec![vec![a, b]]);
+  }
+
+  #[test]
+  fn cycles_ignores_acyclic_graph() {
+    let mut file = work();
+    link(&mut file, &[("a", "b"), ("a", "c"), ("b", "c")]);
+    assert!(HyphaDepGraph::new(&file).cycles().is_empty());
+  }
+
+  #[test]
+  fn cycles_handles_long_chains() {
+    let titles = (0..100_000).map(|idx| idx.to_string()).collect::<Vec<_>>();
+    let titles = titles.iter().map(String::as_str).collect::<Vec<_>>();
+    let mut file = file(vec![board("Long", vec![list("Todo", &titles)])]);
+    let first = issue_ref(&file, "0");
+    let chain = file.boards[0].lists[0]
+      .issues
+      .iter()
+      .map(|issue| HyphaFileIssueRef {
+        issue: issue.id.clone(),
+        ..first.clone()
+      })
+      .collect::<Vec<_>>();
+    let mut deps = chain
+      .windows(2)
+      .filter_map(|pair| match pair {
+        [left, right] => Some(HyphaDep {
+          left: left.clone(),
+          right: right.clone(),
+        }),
+        _ => None,
+      })
+      .collect::<Vec<_>>();
+    if let Some(last) = chain.last() {
+      deps.push(HyphaDep {
+        left: last.clone(),
+        right: first,
+      });
+    }
+    file.boards[0].deps = deps;
+
+    let cycles = HyphaDepGraph::new(&file).cycles();
+    assert_eq!(cycles.len(), 1);
+    assert_eq!(cycles.first().map(Vec::len), Some(titles.len()));
+  }
+}
@@ -0,0 +1,226 @@
+use std::collections::VecDeque;
+
+use crate::{file::HyphaFile, id::HyphaId};
+
+const HISTORY_LIMIT: usize = 100;
+
+/// Bounded undo and redo stacks of file snapshots.
+///
+/// Consecutive edits of the same item are recorded once so typing into a
+/// title does not flood the history with one snapshot per keystroke.
+#[derive(Debug, Clone, Default)]
+pub struct HyphaHistory {
+  undo: VecDeque<HyphaFile>,
+  redo: Vec<HyphaFile>,
+  last: Option<HyphaId>,
+}
+
+impl HyphaHistory {
+  pub fn record(&mut self, snapshot: HyphaFile, item: Option<HyphaId>) {
+    self.redo.clear();
+    if item.is_some() && item == self.last {
+      return;
+    }
+    self.last = item;
+
+    self.undo.push_back(snapshot);
+    while self.undo.len() > HISTORY_LIMIT {
+      self.undo.pop_front();
+    }
+  }
+
+  pub fn undo(&mut self, current: HyphaFile) -> Option<HyphaFile> {
+    self.last = None;
+    while let Some(snapshot) = self.undo.pop_back() {
+      if snapshot != current {
+        self.redo.push(current);
+        return Some(snapshot);
+      }
+    }
+    None
+  }
+
+  pub fn redo(&mut self, current: HyphaFile) -> Option<HyphaFile> {
+    self.last = None;
+    let snapshot = self.redo.pop()?;
+    self.undo.push_back(current);
+    Some(snapshot)
+  }
+
+  pub fn can_undo(&self) -> bool {
+    !self.undo.is_empty()
+  }
+
+  pub fn can_redo(&self) -> bool {
+    !self.redo.is_empty()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    r#ref::{HyphaRef, WithHyphaRef},
+    testing::{board_ref, dep, issue_ref, link, list_ref, work},
+  };
+
+  // Records a snapshot, applies the op and checks undo and redo bring back
+  // the file from before and after it.
+  fn check(mut file: HyphaFile, op: impl FnOnce(&mut HyphaFile)) {
+    let mut history = HyphaHistory::default();
+    let before = file.clone();
+    history.record(before.clone(), None);
+    op(&mut file);
+    let after = file;
+    assert_ne!(before, after);
+
+    assert_eq!(history.undo(after.clone()).as_ref(), Some(&before));
+    assert!(history.can_redo());
+    assert_eq!(history.redo(before).as_ref(), Some(&after));
+    assert!(!history.can_redo());
+  }
+
+  #[test]
+  fn undoes_board_ops() {
+    check(work(), |file| {
+      file.add_board("Play".to_string());
+    });
+    check(work(), |file| {
+      let board_ref = board_ref(file, "Work");
+      if let Some(mut board) = board_ref.get_item_from_container(file).cloned()
+      {
+        board.title = "Play".to_string();
+        file.update_board(WithHyphaRef {
+          item: board,
+          r#ref: board_ref,
+        });
+      }
+    });
+    check(work(), |file| {
+      file.remove_board(board_ref(file, "Work"));
+    });
+  }
+
+  #[test]
+  fn undoes_list_ops() {
+    check(work(), |file| {
+      file.add_list(board_ref(file, "Work"), "Later".to_string());
+    });
+    check(work(), |file| {
+      let list_ref = list_ref(file, "Todo");
+      if let Some(mut list) = list_ref.get_item_from_container(file).cloned() {
+        list.title = "Later".to_string();
+        file.update_list(WithHyphaRef {
+          item: list,
+          r#ref: list_ref,
+        });
+      }
+    });
+    check(work(), |file| {
+      file.remove_list(list_ref(file, "Doing"));
+    });
+    check(work(), |file| {
+      file.move_list(list_ref(file, "Todo"), 1);
+    });
+  }
+
+  #[test]
+  fn undoes_issue_ops() {
+    check(work(), |file| {
+      file.add_issue(list_ref(file, "Todo"));
+    });
+    check(work(), |file| {
+      let issue_ref = issue_ref(file, "a");
+      if let Some(mut issue) = issue_ref.get_item_from_container(file).cloned()
+      {
+        issue.title = "e".to_string();
+        file.update_issue(WithHyphaRef {
+          item: issue,
+          r#ref: issue_ref,
+        });
+      }
+    });
+    check(work(), |file| {
+      file.remove_issue(issue_ref(file, "a"));
+    });
+    check(work(), |file| {
+      file.move_issue(issue_ref(file, "a"), list_ref(file, "Todo"), 2);
+    });
+    check(work(), |file| {
+      file.move_issue(issue_ref(file, "a"), list_ref(file, "Done"), 0);
+    });
+  }
+
+  #[test]
+  fn undoes_dep_ops() {
+    check(work(), |file| {
+      let dep = dep(file, "a", "c");
+      assert_eq!(file.add_dep(dep), Ok(()));
+    });
+    let mut linked = work();
+    link(&mut linked, &[("a", "c")]);
+    check(linked, |file| {
+      file.remove_dep(dep(file, "a", "c"));
+    });
+  }
+
+  #[test]
+  fn skips_snapshots_equal_to_current() {
+    let mut history = HyphaHistory::default();
+    let file = work();
+    history.record(file.clone(), None);
+    assert_eq!(history.undo(file), None);
+  }
+
+  #[test]
+  fn coalesces_edits_of_same_item() {
+    let mut history = HyphaHistory::default();
+    let mut file = work();
+    let item = Some(issue_ref(&file, "a").issue);
+    let first = file.clone();
+    for title in ["e", "ed", "edi", "edit"] {
+      history.record(file.clone(), item.clone());
+      file.boards[0].lists[0].issues[0].title = title.to_string();
+    }
+
+    assert_eq!(history.undo(file), Some(first.clone()));
+    assert_eq!(history.undo(first), None);
+  }
+
+  #[test]
+  fn keeps_separate_edits_of_other_items() {
+    let mut history = HyphaHistory::default();
+    let mut file = work();
+    let first = file.clone();
+    history.record(file.clone(), Some(issue_ref(&file, "a").issue));
+    file.boards[0].lists[0].issues[0].title = "e".to_string();
+    let second = file.clone();
+    history.record(file.clone(), Some(issue_ref(&file, "b").issue));
+    file.boards[0].lists[0].issues[1].title = "f".to_string();
+
+    assert_eq!(history.undo(file), Some(second.clone()));
+    assert_eq!(history.undo(second), Some(first));
+  }
+
+  #[test]
+  fn caps_history_at_limit() {
+    let mut history = HyphaHistory::default();
+    let mut file = work();
+    let mut snapshots = Vec::new();
+    for idx in 0..HISTORY_LIMIT.saturating_add(10) {
+      snapshots.push(file.clone());
+      history.record(file.clone(), None);
+      file.boards[0].title = idx.to_string();
+    }
+
+    let mut undone = Vec::new();
+    let mut current = file;
+    while let Some(snapshot) = history.undo(current.clone()) {
+      undone.push(snapshot.clone());
+      current = snapshot;
+    }
+    undone.reverse();
+    assert_eq!(undone.len(), HISTORY_LIMIT);
+    assert_eq!(undone.as_slice(), &snapshots[10..]);
+  }
+}
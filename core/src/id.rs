@@ -0,0 +1,37 @@
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use uuid::Uuid;
+
+#[derive(
+  Debug, Clone, Serialize, Deserialize, PartialEq, Eq, Hash, PartialOrd, Ord,
+)]
+#[serde(transparent)]
+pub struct HyphaId(String);
+
+impl HyphaId {
+  pub fn new() -> Self {
+    Self(Uuid::new_v4().to_string())
+  }
+
+  pub fn as_str(&self) -> &str {
+    &self.0
+  }
+}
+
+impl Default for HyphaId {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl fmt::Display for HyphaId {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.0)
+  }
+}
+
+impl From<String> for HyphaId {
+  fn from(id: String) -> Self {
+    Self(id)
+  }
+}
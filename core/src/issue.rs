@@ -0,0 +1,156 @@
+use chrono::{DateTime, NaiveDate, Utc};
+use rnglib::{Language, RNG};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+  file::HyphaFile,
+  id::HyphaId,
+  item::HyphaItem,
+  r#ref::{HyphaFileBoardRef, HyphaFileIssueRef, HyphaFileListRef, HyphaRef},
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HyphaIssue {
+  #[serde(default)]
+  pub id: HyphaId,
+  pub title: String,
+  #[serde(default)]
+  pub description: String,
+  #[serde(default)]
+  pub priority: Option<HyphaPriority>,
+  #[serde(default)]
+  pub estimate: Option<f64>,
+  #[serde(default)]
+  pub due: Option<NaiveDate>,
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub tags: Vec<String>,
+  #[serde(default)]
+  pub assignee: Option<String>,
+  #[serde(default)]
+  pub created: Option<DateTime<Utc>>,
+  #[serde(default)]
+  pub updated: Option<DateTime<Utc>>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum HyphaPriority {
+  Low,
+  Medium,
+  High,
+  Urgent,
+}
+
+impl HyphaPriority {
+  pub const ALL: [HyphaPriority; 4] = [
+    HyphaPriority::Low,
+    HyphaPriority::Medium,
+    HyphaPriority::High,
+    HyphaPriority::Urgent,
+  ];
+
+  pub fn as_str(self) -> &'static str {
+    match self {
+      HyphaPriority::Low => "low",
+      HyphaPriority::Medium => "medium",
+      HyphaPriority::High => "high",
+      HyphaPriority::Urgent => "urgent",
+    }
+  }
+
+  pub fn parse(value: &str) -> Option<Self> {
+    Self::ALL
+      .into_iter()
+      .find(|priority| priority.as_str() == value)
+  }
+
+  pub fn weight(self) -> f64 {
+    match self {
+      HyphaPriority::Low => 1.0,
+      HyphaPriority::Medium => 2.0,
+      HyphaPriority::High => 3.0,
+      HyphaPriority::Urgent => 4.0,
+    }
+  }
+}
+
+impl HyphaItem for HyphaIssue {
+  fn id(&self) -> &HyphaId {
+    &self.id
+  }
+
+  fn title(&self) -> &str {
+    &self.title
+  }
+}
+
+impl Default for HyphaIssue {
+  fn default() -> Self {
+    let rng = RNG::from(&Language::Roman);
+    let title = rng.generate_name();
+    let now = Utc::now();
+    HyphaIssue {
+      id: HyphaId::new(),
+      title,
+      description: String::new(),
+      priority: None,
+      estimate: None,
+      due: None,
+      tags: vec![],
+      assignee: None,
+      created: Some(now),
+      updated: Some(now),
+    }
+  }
+}
+
+/// Parses an estimate, which has to be a finite number of zero or more.
+pub fn parse_estimate(value: &str) -> Option<f64> {
+  value
+    .trim()
+    .parse::<f64>()
+    .ok()
+    .filter(|estimate| is_valid_estimate(*estimate))
+}
+
+pub fn is_valid_estimate(estimate: f64) -> bool {
+  estimate.is_finite() && estimate >= 0.0
+}
+
+pub fn label(
+  file: &HyphaFile,
+  issue_ref: &HyphaFileIssueRef,
+) -> Option<String> {
+  let board_ref = HyphaFileBoardRef {
+    board: issue_ref.board.clone(),
+  };
+  let list_ref = HyphaFileListRef {
+    list: issue_ref.list.clone(),
+    stage: issue_ref.stage,
+    board: issue_ref.board.clone(),
+  };
+  Some(format!(
+    "{} ({} / {})",
+    issue_ref.get_item_from_container(file)?.title,
+    board_ref.get_item_from_container(file)?.title,
+    list_ref.get_item_from_container(file)?.title,
+  ))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn parses_valid_estimates() {
+    assert_eq!(parse_estimate("0"), Some(0.0));
+    assert_eq!(parse_estimate(" 2.5 "), Some(2.5));
+  }
+
+  #[test]
+  fn rejects_invalid_estimates() {
+    for value in ["", "-1", "NaN", "inf", "two"] {
+      assert_eq!(parse_estimate(value), None, "{value}");
+    }
+  }
+}
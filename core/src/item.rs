@@ -1,14 +1,25 @@
+use crate::id::HyphaId;
+
 pub trait HyphaItem {
+  fn id(&self) -> &HyphaId;
   fn title(&self) -> &str;
 }
 
 impl<Item: HyphaItem> HyphaItem for &Item {
+  fn id(&self) -> &HyphaId {
+    HyphaItem::id(*self)
+  }
+
   fn title(&self) -> &str {
     HyphaItem::title(*self)
   }
 }
 
 impl<Item: HyphaItem> HyphaItem for &mut Item {
+  fn id(&self) -> &HyphaId {
+    HyphaItem::id(*self)
+  }
+
   fn title(&self) -> &str {
     HyphaItem::title(*self)
   }
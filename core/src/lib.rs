@@ -0,0 +1,42 @@
+//! The hypha domain model and file handling without any UI.
+//!
+//! Every edit is a plain method on `&mut HyphaFile` so the app, the command
+//! line interface and anything else can share them.
+
+#![deny(unsafe_code)]
+#![deny(clippy::unwrap_used, clippy::expect_used, clippy::panic)]
+#![deny(clippy::arithmetic_side_effects)]
+#![deny(clippy::dbg_macro, clippy::print_stdout, clippy::print_stderr)]
+#![deny(clippy::todo)]
+#![deny(clippy::unreachable)]
+#![deny(clippy::allow_attributes_without_reason)]
+
+pub mod board;
+pub mod changes;
+pub mod container;
+pub mod dep;
+pub mod file;
+pub mod format;
+pub mod graph;
+pub mod history;
+pub mod id;
+pub mod issue;
+pub mod item;
+pub mod list;
+pub mod markdown;
+pub mod merge;
+mod ops;
+pub mod preferences;
+pub mod query;
+pub mod recent;
+pub mod r#ref;
+pub mod search;
+pub mod sqlite;
+pub mod storage;
+pub mod suggest;
+pub mod template;
+#[cfg(test)]
+mod testing;
+pub mod validate;
+pub mod view;
+pub mod workspace;
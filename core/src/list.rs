@@ -0,0 +1,48 @@
+use rnglib::{Language, RNG};
+use serde::{Deserialize, Serialize};
+
+use crate::id::HyphaId;
+use crate::issue::HyphaIssue;
+use crate::item::HyphaItem;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HyphaList {
+  #[serde(default)]
+  pub id: HyphaId,
+  pub title: String,
+  #[serde(default)]
+  pub issues: Vec<HyphaIssue>,
+}
+
+impl HyphaItem for HyphaList {
+  fn id(&self) -> &HyphaId {
+    &self.id
+  }
+
+  fn title(&self) -> &str {
+    &self.title
+  }
+}
+
+impl HyphaList {
+  /// List without any issues.
+  pub fn new(title: String) -> Self {
+    Self {
+      id: HyphaId::new(),
+      title,
+      issues: vec![],
+    }
+  }
+}
+
+impl Default for HyphaList {
+  fn default() -> Self {
+    let rng = RNG::from(&Language::Roman);
+    let title = rng.generate_name();
+    HyphaList {
+      id: HyphaId::new(),
+      title,
+      issues: vec![HyphaIssue::default()],
+    }
+  }
+}
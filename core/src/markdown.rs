@@ -0,0 +1,519 @@
+use log::{info, warn};
+use serde::Serialize;
+use std::{
+  collections::{HashMap, HashSet},
+  fs, io,
+  path::{Path, PathBuf},
+  time::SystemTime,
+};
+use tokio::task;
+
+use crate::{
+  board::HyphaBoard,
+  changes::HyphaChanges,
+  file::{FileError, HyphaFile, VERSION},
+  id::HyphaId,
+  issue::HyphaIssue,
+  list::HyphaList,
+  storage::{link_deps, replace, write_temp, HyphaStorage},
+  validate::HyphaReport,
+};
+
+const ROOT_FILE: &str = "hypha.toml";
+const BOARD_FILE: &str = "board.toml";
+const LIST_FILE: &str = "list.toml";
+const ISSUE_EXTENSION: &str = "md";
+const FENCE: &str = "+++";
+const UNTITLED: &str = "untitled";
+
+// A hypha directory looks like this, with names derived from titles:
+//
+// hypha.toml              version, preferences, views and board order
+// <board>/board.toml      board fields and list order
+// <board>/<list>/list.toml
+//                         list fields and issue order
+// <board>/<list>/<issue>.md
+//                         issue fields and the ids of the issues blocking it
+//                         as TOML front matter followed by the description
+//
+// Entries missing from an order are appended by name so files added by hand
+// are picked up too.
+
+/// Whether `path` is stored as a directory of Markdown issues.
+///
+/// Paths without an extension that do not exist yet are treated as
+/// directories so new ones can be created from the path picker.
+pub fn is_directory(path: &Path) -> bool {
+  path.is_dir() || (!path.exists() && path.extension().is_none())
+}
+
+/// The file stored as a directory of Markdown issues.
+pub struct HyphaMarkdownStorage;
+
+impl HyphaStorage for HyphaMarkdownStorage {
+  fn load(&self, path: &Path) -> Result<(HyphaFile, HyphaReport), FileError> {
+    Ok(load(path)?.check(""))
+  }
+
+  fn save(&self, file: &HyphaFile) -> Result<(), FileError> {
+    save(file)
+  }
+}
+
+fn load(path: &Path) -> Result<HyphaFile, FileError> {
+  let content = match fs::read_to_string(path.join(ROOT_FILE)) {
+    Ok(content) => content,
+    Err(e) if e.kind() == io::ErrorKind::NotFound => {
+      warn!(
+        "Hypha directory not found at {}, creating default.",
+        path.display()
+      );
+      return Ok(HyphaFile {
+        path: path.to_path_buf(),
+        ..HyphaFile::default()
+      });
+    }
+    Err(e) => return Err(FileError::Io(e)),
+  };
+
+  let mut root = content.parse::<toml::Table>()?;
+  if let Some(value) = root.get("version") {
+    let version = value
+      .as_integer()
+      .and_then(|version| u32::try_from(version).ok())
+      .ok_or_else(|| FileError::InvalidVersion(value.to_string()))?;
+    if version > VERSION {
+      return Err(FileError::UnsupportedVersion(version, VERSION));
+    }
+  }
+  let board_names = take_names(&mut root, "boards")?;
+  let preferences = root
+    .remove("preferences")
+    .map(|preferences| preferences.try_into())
+    .transpose()?
+    .unwrap_or_default();
+  let views = root
+    .remove("views")
+    .map(|views| views.try_into())
+    .transpose()?
+    .unwrap_or_default();
+
+  let mut blockers = Vec::new();
+  let mut boards = Vec::new();
+  for board_dir in
+    entries(path, &board_names, |entry| entry.join(BOARD_FILE).is_file())?
+  {
+    boards.push(load_board(&board_dir, &mut blockers)?);
+  }
+
+  let mut file = HyphaFile {
+    version: VERSION,
+    boards,
+    deps: Vec::new(),
+    preferences,
+    views,
+    path: path.to_path_buf(),
+    changes: HyphaChanges::default(),
+  };
+
+  link_deps(
+    &mut file,
+    blockers.into_iter().flat_map(|(issue, blocked_by)| {
+      blocked_by
+        .into_iter()
+        .map(move |blocker| (blocker, issue.clone()))
+    }),
+  );
+  Ok(file)
+}
+
+fn load_board(
+  dir: &Path,
+  blockers: &mut Vec<(HyphaId, Vec<HyphaId>)>,
+) -> Result<HyphaBoard, FileError> {
+  let mut table =
+    fs::read_to_string(dir.join(BOARD_FILE))?.parse::<toml::Table>()?;
+  let list_names = take_names(&mut table, "lists")?;
+  let mut board: HyphaBoard = table.try_into()?;
+  for list_dir in
+    entries(dir, &list_names, |entry| entry.join(LIST_FILE).is_file())?
+  {
+    board.lists.push(load_list(&list_dir, blockers)?);
+  }
+  Ok(board)
+}
+
+fn load_list(
+  dir: &Path,
+  blockers: &mut Vec<(HyphaId, Vec<HyphaId>)>,
+) -> Result<HyphaList, FileError> {
+  let mut table =
+    fs::read_to_string(dir.join(LIST_FILE))?.parse::<toml::Table>()?;
+  let issue_names = take_names(&mut table, "issues")?;
+  let mut list: HyphaList = table.try_into()?;
+  for issue_path in entries(dir, &issue_names, |entry| {
+    entry.is_file()
+      && entry
+        .extension()
+        .is_some_and(|extension| extension == ISSUE_EXTENSION)
+  })? {
+    let content = fs::read_to_string(&issue_path)?;
+    let (issue, blocked_by) = parse_issue(&content, &issue_path)?;
+    if !blocked_by.is_empty() {
+      blockers.push((issue.id.clone(), blocked_by));
+    }
+    list.issues.push(issue);
+  }
+  Ok(list)
+}
+
+fn parse_issue(
+  content: &str,
+  path: &Path,
+) -> Result<(HyphaIssue, Vec<HyphaId>), FileError> {
+  let (front_matter, body) = content
+    .strip_prefix(FENCE)
+    .and_then(|rest| rest.split_once(&format!("\n{FENCE}")))
+    .ok_or_else(|| FileError::FrontMatter(path.to_path_buf()))?;
+  let body = body.strip_prefix('\n').unwrap_or(body);
+  let description = body.strip_prefix('\n').unwrap_or(body);
+  let description = description.strip_suffix('\n').unwrap_or(description);
+
+  let mut table = front_matter.parse::<toml::Table>()?;
+  let blocked_by = table
+    .remove("blocked_by")
+    .map(|blocked_by| blocked_by.try_into())
+    .transpose()?
+    .unwrap_or_default();
+  table.insert("description".to_string(), description.into());
+  Ok((table.try_into()?, blocked_by))
+}
+
+/// Writes the file as a directory, removing boards, lists and issues that are
+/// no longer part of it.
+///
+/// Only files whose content changed are rewritten, each atomically.
+fn save(file: &HyphaFile) -> Result<(), FileError> {
+  let path = file.path.as_path();
+  fs::create_dir_all(path)?;
+
+  let mut blockers = HashMap::<HyphaId, Vec<HyphaId>>::new();
+  let deps = file
+    .deps
+    .iter()
+    .chain(file.boards.iter().flat_map(|board| board.deps.iter()));
+  for dep in deps {
+    blockers
+      .entry(dep.right.issue.clone())
+      .or_default()
+      .push(dep.left.issue.clone());
+  }
+
+  let board_names =
+    names(file.boards.iter().map(|board| board.title.as_str()), "");
+  let mut root = toml::Table::new();
+  root.insert("version".to_string(), i64::from(file.version).into());
+  root.insert("preferences".to_string(), table(&file.preferences)?.into());
+  if !file.views.is_empty() {
+    root.insert("views".to_string(), toml::Value::try_from(&file.views)?);
+  }
+  root.insert("boards".to_string(), board_names.clone().into());
+  write(&path.join(ROOT_FILE), &toml::to_string_pretty(&root)?)?;
+
+  for (board, name) in file.boards.iter().zip(board_names.iter()) {
+    save_board(&path.join(name), board, &blockers)?;
+  }
+  remove_stale(path, &board_names, |entry| entry.join(BOARD_FILE).is_file())
+}
+
+fn save_board(
+  dir: &Path,
+  board: &HyphaBoard,
+  blockers: &HashMap<HyphaId, Vec<HyphaId>>,
+) -> Result<(), FileError> {
+  fs::create_dir_all(dir)?;
+
+  let list_names =
+    names(board.lists.iter().map(|list| list.title.as_str()), "");
+  let mut board_table = table(board)?;
+  board_table.remove("deps");
+  board_table.insert("lists".to_string(), list_names.clone().into());
+  write(
+    &dir.join(BOARD_FILE),
+    &toml::to_string_pretty(&board_table)?,
+  )?;
+
+  for (list, name) in board.lists.iter().zip(list_names.iter()) {
+    let list_dir = dir.join(name);
+    fs::create_dir_all(&list_dir)?;
+
+    let issue_names = names(
+      list.issues.iter().map(|issue| issue.title.as_str()),
+      &format!(".{ISSUE_EXTENSION}"),
+    );
+    let mut list_table = table(list)?;
+    list_table.insert("issues".to_string(), issue_names.clone().into());
+    write(
+      &list_dir.join(LIST_FILE),
+      &toml::to_string_pretty(&list_table)?,
+    )?;
+
+    for (issue, name) in list.issues.iter().zip(issue_names.iter()) {
+      let blocked_by = blockers
+        .get(&issue.id)
+        .map(Vec::as_slice)
+        .unwrap_or_default();
+      write(&list_dir.join(name), &issue_content(issue, blocked_by)?)?;
+    }
+    remove_stale(&list_dir, &issue_names, |entry| {
+      entry.is_file()
+        && entry
+          .extension()
+          .is_some_and(|extension| extension == ISSUE_EXTENSION)
+    })?;
+  }
+  remove_stale(dir, &list_names, |entry| entry.join(LIST_FILE).is_file())
+}
+
+fn issue_content(
+  issue: &HyphaIssue,
+  blocked_by: &[HyphaId],
+) -> Result<String, FileError> {
+  let mut front_matter = table(issue)?;
+  front_matter.remove("description");
+  if !blocked_by.is_empty() {
+    let blocked_by = blocked_by
+      .iter()
+      .map(|id| id.to_string())
+      .collect::<Vec<_>>();
+    front_matter.insert("blocked_by".to_string(), blocked_by.into());
+  }
+
+  let mut content = format!(
+    "{FENCE}\n{}{FENCE}\n",
+    toml::to_string_pretty(&front_matter)?
+  );
+  if !issue.description.is_empty() {
+    content.push('\n');
+    content.push_str(&issue.description);
+    content.push('\n');
+  }
+  Ok(content)
+}
+
+/// Latest modification time of anything inside the directory at `path`.
+pub fn modified(path: &Path) -> Option<SystemTime> {
+  let mut latest = fs::metadata(path).and_then(|meta| meta.modified()).ok()?;
+  for entry in fs::read_dir(path).ok()?.flatten() {
+    let entry = entry.path();
+    let entry_modified = if entry.is_dir() {
+      modified(&entry)
+    } else {
+      fs::metadata(&entry).and_then(|meta| meta.modified()).ok()
+    };
+    if let Some(entry_modified) = entry_modified {
+      latest = latest.max(entry_modified);
+    }
+  }
+  Some(latest)
+}
+
+pub async fn modified_async(path: &Path) -> Option<SystemTime> {
+  let path = path.to_path_buf();
+  task::spawn_blocking(move || modified(&path))
+    .await
+    .ok()
+    .flatten()
+}
+
+fn table<T: Serialize>(value: &T) -> Result<toml::Table, FileError> {
+  match toml::Value::try_from(value)? {
+    toml::Value::Table(table) => Ok(table),
+    _ => Ok(toml::Table::new()),
+  }
+}
+
+fn take_names(
+  table: &mut toml::Table,
+  key: &str,
+) -> Result<Vec<String>, FileError> {
+  Ok(
+    table
+      .remove(key)
+      .map(|names| names.try_into())
+      .transpose()?
+      .unwrap_or_default(),
+  )
+}
+
+// File names derived from titles, numbered when titles collide.
+fn names<'a>(
+  titles: impl Iterator<Item = &'a str>,
+  extension: &str,
+) -> Vec<String> {
+  let mut seen = HashSet::new();
+  titles
+    .map(|title| {
+      let slug = title
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .map(str::to_lowercase)
+        .collect::<Vec<_>>()
+        .join("-");
+      let slug = if slug.is_empty() {
+        UNTITLED.to_string()
+      } else {
+        slug
+      };
+
+      let mut name = format!("{slug}{extension}");
+      let mut count = 1_usize;
+      while !seen.insert(name.clone()) {
+        count = count.saturating_add(1);
+        name = format!("{slug}-{count}{extension}");
+      }
+      name
+    })
+    .collect()
+}
+
+fn entries(
+  dir: &Path,
+  names: &[String],
+  matches: impl Fn(&Path) -> bool,
+) -> Result<Vec<PathBuf>, FileError> {
+  let mut unlisted = Vec::new();
+  for entry in fs::read_dir(dir)? {
+    let entry = entry?.path();
+    if matches(&entry) && !names.contains(&file_name(&entry)) {
+      unlisted.push(entry);
+    }
+  }
+  unlisted.sort();
+
+  Ok(
+    names
+      .iter()
+      .map(|name| dir.join(name))
+      .filter(|entry| matches(entry))
+      .chain(unlisted)
+      .collect(),
+  )
+}
+
+fn remove_stale(
+  dir: &Path,
+  keep: &[String],
+  matches: impl Fn(&Path) -> bool,
+) -> Result<(), FileError> {
+  for entry in fs::read_dir(dir)? {
+    let entry = entry?.path();
+    if !matches(&entry) || keep.contains(&file_name(&entry)) {
+      continue;
+    }
+    info!("Removing stale {}", entry.display());
+    if entry.is_dir() {
+      fs::remove_dir_all(&entry)?;
+    } else {
+      fs::remove_file(&entry)?;
+    }
+  }
+  Ok(())
+}
+
+fn write(path: &Path, content: &str) -> Result<(), FileError> {
+  if fs::read_to_string(path).is_ok_and(|existing| existing == content) {
+    return Ok(());
+  }
+
+  let temp_path = write_temp(path, content.as_bytes())?;
+  replace(&temp_path, path)
+}
+
+fn file_name(path: &Path) -> String {
+  path
+    .file_name()
+    .and_then(|name| name.to_str())
+    .unwrap_or_default()
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::{issue_ref, sample, temp_dir};
+
+  fn sample_in(dir: &Path) -> HyphaFile {
+    HyphaFile {
+      path: dir.join("hypha"),
+      ..sample()
+    }
+  }
+
+  fn markdown_files(dir: &Path) -> io::Result<usize> {
+    let mut count = 0usize;
+    for entry in fs::read_dir(dir)? {
+      let path = entry?.path();
+      if path.is_dir() {
+        count = count.saturating_add(markdown_files(&path)?);
+      } else if path.extension().is_some_and(|ext| ext == ISSUE_EXTENSION) {
+        count = count.saturating_add(1);
+      }
+    }
+    Ok(count)
+  }
+
+  #[test]
+  fn round_trips_directory() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = temp_dir()?;
+    let file = sample_in(&dir);
+    save(&file)?;
+    assert_eq!(load(&file.path)?, file);
+    assert_eq!(markdown_files(&file.path)?, 4);
+    fs::remove_dir_all(dir)?;
+    Ok(())
+  }
+
+  #[test]
+  fn removes_stale_entries() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = temp_dir()?;
+    let mut file = sample_in(&dir);
+    save(&file)?;
+    file.remove_issue(issue_ref(&file, "b"));
+    if let Some(list) = file.boards[0].lists.get_mut(1) {
+      list.title = "In progress".to_string();
+    }
+    save(&file)?;
+
+    assert_eq!(load(&file.path)?, file);
+    assert_eq!(markdown_files(&file.path)?, 3);
+    fs::remove_dir_all(dir)?;
+    Ok(())
+  }
+
+  #[test]
+  fn picks_up_issues_added_by_hand() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = temp_dir()?;
+    let file = sample_in(&dir);
+    save(&file)?;
+    fs::write(
+      file.path.join("work").join("todo").join("extra.md"),
+      "+++\ntitle = \"Extra\"\n+++\nBy hand\n",
+    )?;
+
+    let loaded = load(&file.path)?;
+    let todo = loaded.boards.first().and_then(|board| board.lists.first());
+    let titles = todo
+      .map(|list| {
+        list
+          .issues
+          .iter()
+          .map(|issue| issue.title.as_str())
+          .collect::<Vec<_>>()
+      })
+      .unwrap_or_default();
+    assert_eq!(titles, ["a", "b", "Extra"]);
+    fs::remove_dir_all(dir)?;
+    Ok(())
+  }
+}
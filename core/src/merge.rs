@@ -0,0 +1,624 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::{
+  board::HyphaBoard, changes::HyphaChanges, dep::HyphaDep, file::HyphaFile,
+  graph::HyphaDepGraph, id::HyphaId, issue::HyphaIssue, list::HyphaList,
+  r#ref::HyphaFileIssueRef, view::HyphaView,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HyphaSide {
+  Ours,
+  Theirs,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum HyphaConflictKey {
+  Preferences,
+  Item(HyphaId),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HyphaConflict {
+  pub key: HyphaConflictKey,
+  pub label: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HyphaMerge {
+  pub file: HyphaFile,
+  pub conflicts: Vec<HyphaConflict>,
+}
+
+/// Merges in-app edits (`ours`) and external edits (`theirs`) made on top of
+/// the last saved file (`base`).
+///
+/// Boards, lists, issues and views are matched by id. A change made on one side
+/// only is taken as is while items changed differently on both sides are
+/// reported as conflicts and resolved by `resolve`. Removing a board or list on
+/// one side while the other side added or changed items in it is a conflict
+/// too. Keeping the board or list brings back the items removed along with it
+/// and removing it drops the items in it. Deps are merged as a set and pointed
+/// at wherever their issues ended up.
+pub fn merge(
+  base: &HyphaFile,
+  ours: &HyphaFile,
+  theirs: &HyphaFile,
+  resolve: impl Fn(&HyphaConflictKey) -> HyphaSide,
+) -> HyphaMerge {
+  let indices = [Index::new(base), Index::new(ours), Index::new(theirs)];
+  let [base_index, ours_index, theirs_index] = &indices;
+  let mut resolver = Resolver {
+    resolve,
+    conflicts: Vec::new(),
+  };
+
+  let preferences = resolver
+    .pick(
+      HyphaConflictKey::Preferences,
+      "Preferences".to_string(),
+      merge_value(
+        Some(&base.preferences),
+        Some(&ours.preferences),
+        Some(&theirs.preferences),
+      ),
+    )
+    .unwrap_or_else(|| ours.preferences.clone());
+
+  let mut views = Vec::new();
+  for id in union(indices.iter().map(|index| index.view_order.as_slice())) {
+    let values = merge_value(
+      base_index.views.get(&id),
+      ours_index.views.get(&id),
+      theirs_index.views.get(&id),
+    );
+    let label = format!("View '{}'", title(&indices, &id));
+    if let Some(view) =
+      resolver.pick(HyphaConflictKey::Item(id.clone()), label, values)
+    {
+      views.push(view);
+    }
+  }
+  let views = order(
+    &indices,
+    |index| index.view_order.clone(),
+    views,
+    |view| &view.id,
+  );
+
+  let [changed_ours, changed_theirs] =
+    [ours_index, theirs_index].map(|index| index.changed(base_index));
+
+  let mut boards = HashMap::new();
+  let mut restored_boards = HashMap::new();
+  for id in union(indices.iter().map(|index| index.board_order.as_slice())) {
+    let ours = ours_index.boards.get(&id);
+    let theirs = theirs_index.boards.get(&id);
+    let values = keep_parent(
+      merge_value(base_index.boards.get(&id), ours, theirs),
+      ours.filter(|_| changed_ours.boards.contains(&id)),
+      theirs.filter(|_| changed_theirs.boards.contains(&id)),
+    );
+    let label = format!("Board '{}'", title(&indices, &id));
+    if let Some(board) =
+      resolver.pick(HyphaConflictKey::Item(id.clone()), label, values)
+    {
+      if let Some(side) = removed_by(ours, theirs) {
+        restored_boards.insert(id.clone(), side);
+      }
+      boards.insert(id, board);
+    }
+  }
+
+  let mut lists = HashMap::<HyphaId, Vec<HyphaList>>::new();
+  let mut restored_lists = HashMap::new();
+  for id in union(indices.iter().map(|index| index.list_order.as_slice())) {
+    let base = base_index.lists.get(&id);
+    let raw_ours = ours_index.lists.get(&id);
+    let raw_theirs = theirs_index.lists.get(&id);
+    let ours = restore(raw_ours, base, HyphaSide::Ours, &restored_boards);
+    let theirs = restore(raw_theirs, base, HyphaSide::Theirs, &restored_boards);
+    let values = keep_parent(
+      merge_value(base, ours, theirs),
+      ours.filter(|_| changed_ours.lists.contains(&id)),
+      theirs.filter(|_| changed_theirs.lists.contains(&id)),
+    );
+    let label = format!("List '{}'", title(&indices, &id));
+    if let Some((board, list)) =
+      resolver.pick(HyphaConflictKey::Item(id.clone()), label, values)
+    {
+      if let Some(side) = removed_by(raw_ours, raw_theirs) {
+        restored_lists.insert(id.clone(), side);
+      }
+      lists.entry(board).or_default().push(list);
+    }
+  }
+
+  let mut issues = HashMap::<HyphaId, Vec<HyphaIssue>>::new();
+  for id in union(indices.iter().map(|index| index.issue_order.as_slice())) {
+    let base = base_index.issues.get(&id);
+    let ours = restore(
+      ours_index.issues.get(&id),
+      base,
+      HyphaSide::Ours,
+      &restored_lists,
+    );
+    let theirs = restore(
+      theirs_index.issues.get(&id),
+      base,
+      HyphaSide::Theirs,
+      &restored_lists,
+    );
+    let values = merge_value(base, ours, theirs);
+    let label = format!("Issue '{}'", title(&indices, &id));
+    if let Some((list, issue)) =
+      resolver.pick(HyphaConflictKey::Item(id.clone()), label, values)
+    {
+      issues.entry(list).or_default().push(issue);
+    }
+  }
+
+  let mut boards = order(
+    &indices,
+    |index| index.board_order.clone(),
+    boards.into_values().collect(),
+    |board| &board.id,
+  );
+  for board in boards.iter_mut() {
+    let board_id = board.id.clone();
+    board.lists = order(
+      &indices,
+      |index| index.lists_of(&board_id),
+      lists.remove(&board.id).unwrap_or_default(),
+      |list| &list.id,
+    );
+    for list in board.lists.iter_mut() {
+      let list_id = list.id.clone();
+      list.issues = order(
+        &indices,
+        |index| index.issues_of(&list_id),
+        issues.remove(&list.id).unwrap_or_default(),
+        |issue| &issue.id,
+      );
+    }
+  }
+
+  let mut file = HyphaFile {
+    version: ours.version,
+    boards,
+    deps: Vec::new(),
+    preferences,
+    views,
+    path: ours.path.clone(),
+    changes: HyphaChanges::default(),
+  };
+
+  let graph = HyphaDepGraph::new(&file);
+  for key in union(indices.iter().map(|index| index.dep_order.as_slice())) {
+    let [base, ours, theirs] =
+      indices.each_ref().map(|index| index.deps.contains(&key));
+    let present = if ours == base { theirs } else { ours };
+    let (left, right) = key;
+    let (true, Some(left), Some(right)) =
+      (present, graph.issue(&left), graph.issue(&right))
+    else {
+      continue;
+    };
+
+    let dep = HyphaDep {
+      left: left.clone(),
+      right: right.clone(),
+    };
+    let board = file
+      .boards
+      .iter_mut()
+      .filter(|_| dep.left.board == dep.right.board)
+      .find(|board| board.id == dep.left.board);
+    match board {
+      Some(board) => board.deps.push(dep),
+      None => file.deps.push(dep),
+    }
+  }
+
+  HyphaMerge {
+    file,
+    conflicts: resolver.conflicts,
+  }
+}
+
+struct Resolver<F> {
+  resolve: F,
+  conflicts: Vec<HyphaConflict>,
+}
+
+impl<F: Fn(&HyphaConflictKey) -> HyphaSide> Resolver<F> {
+  fn pick<T>(
+    &mut self,
+    key: HyphaConflictKey,
+    label: String,
+    merged: Merged<T>,
+  ) -> Option<T> {
+    match merged {
+      Merged::Clean(value) => value,
+      Merged::Conflict(ours, theirs) => {
+        let side = (self.resolve)(&key);
+        self.conflicts.push(HyphaConflict { key, label });
+        match side {
+          HyphaSide::Ours => ours,
+          HyphaSide::Theirs => theirs,
+        }
+      }
+    }
+  }
+}
+
+enum Merged<T> {
+  Clean(Option<T>),
+  Conflict(Option<T>, Option<T>),
+}
+
+fn merge_value<T: Clone + PartialEq>(
+  base: Option<&T>,
+  ours: Option<&T>,
+  theirs: Option<&T>,
+) -> Merged<T> {
+  if ours == theirs || theirs == base {
+    Merged::Clean(ours.cloned())
+  } else if ours == base {
+    Merged::Clean(theirs.cloned())
+  } else {
+    Merged::Conflict(ours.cloned(), theirs.cloned())
+  }
+}
+
+// Removing a board or list on one side while the other side added or changed
+// items in it would silently drop those items, so it is a conflict instead.
+// `ours` and `theirs` are only given for the sides with changed items.
+fn keep_parent<T: Clone>(
+  merged: Merged<T>,
+  ours: Option<&T>,
+  theirs: Option<&T>,
+) -> Merged<T> {
+  match merged {
+    Merged::Clean(None) if ours.is_some() || theirs.is_some() => {
+      Merged::Conflict(ours.cloned(), theirs.cloned())
+    }
+    merged => merged,
+  }
+}
+
+fn removed_by<T>(ours: Option<&T>, theirs: Option<&T>) -> Option<HyphaSide> {
+  match (ours, theirs) {
+    (None, Some(_)) => Some(HyphaSide::Ours),
+    (Some(_), None) => Some(HyphaSide::Theirs),
+    _ => None,
+  }
+}
+
+// A side that removed a board or list which is kept anyway removed the items
+// in it along with it, so they count as unchanged on that side.
+fn restore<'a, T>(
+  value: Option<&'a (HyphaId, T)>,
+  base: Option<&'a (HyphaId, T)>,
+  side: HyphaSide,
+  restored: &HashMap<HyphaId, HyphaSide>,
+) -> Option<&'a (HyphaId, T)> {
+  value
+    .or_else(|| base.filter(|(parent, _)| restored.get(parent) == Some(&side)))
+}
+
+fn union<'a, T: Clone + Eq + std::hash::Hash + 'a>(
+  orders: impl Iterator<Item = &'a [T]>,
+) -> Vec<T> {
+  let mut seen = HashSet::new();
+  orders
+    .flatten()
+    .filter(|item| seen.insert((*item).clone()))
+    .cloned()
+    .collect()
+}
+
+// Keeps the order of the side that reordered the container, preferring ours
+// when both did. Items missing from that side, like ones added or kept by the
+// other side, go right after the item they follow there.
+fn order<T>(
+  indices: &[Index; 3],
+  ids: impl Fn(&Index) -> Vec<HyphaId>,
+  mut items: Vec<T>,
+  id: impl Fn(&T) -> &HyphaId,
+) -> Vec<T> {
+  let [base, ours, theirs] = indices.each_ref().map(ids);
+  let (mut sequence, other) = if ours == base {
+    (theirs, ours)
+  } else {
+    (ours, theirs)
+  };
+  for side in [&other, &base] {
+    for (idx, candidate) in side.iter().enumerate() {
+      if sequence.contains(candidate) {
+        continue;
+      }
+      let at = side
+        .iter()
+        .take(idx)
+        .rev()
+        .find_map(|previous| {
+          sequence.iter().position(|existing| existing == previous)
+        })
+        .map_or(0, |position| position.saturating_add(1));
+      sequence.insert(at, candidate.clone());
+    }
+  }
+  items.sort_by_key(|item| {
+    sequence
+      .iter()
+      .position(|candidate| candidate == id(item))
+      .unwrap_or(sequence.len())
+  });
+  items
+}
+
+fn title(indices: &[Index; 3], id: &HyphaId) -> String {
+  indices
+    .iter()
+    .rev()
+    .find_map(|index| index.titles.get(id))
+    .cloned()
+    .unwrap_or_else(|| id.to_string())
+}
+
+struct Changed {
+  boards: HashSet<HyphaId>,
+  lists: HashSet<HyphaId>,
+}
+
+#[derive(Default)]
+struct Index {
+  board_order: Vec<HyphaId>,
+  boards: HashMap<HyphaId, HyphaBoard>,
+  list_order: Vec<HyphaId>,
+  lists: HashMap<HyphaId, (HyphaId, HyphaList)>,
+  issue_order: Vec<HyphaId>,
+  issues: HashMap<HyphaId, (HyphaId, HyphaIssue)>,
+  dep_order: Vec<(HyphaId, HyphaId)>,
+  deps: HashSet<(HyphaId, HyphaId)>,
+  view_order: Vec<HyphaId>,
+  views: HashMap<HyphaId, HyphaView>,
+  titles: HashMap<HyphaId, String>,
+}
+
+impl Index {
+  fn new(file: &HyphaFile) -> Self {
+    let mut index = Self::default();
+    for board in file.boards.iter() {
+      index.board_order.push(board.id.clone());
+      index.titles.insert(board.id.clone(), board.title.clone());
+      index.boards.insert(
+        board.id.clone(),
+        HyphaBoard {
+          lists: Vec::new(),
+          deps: Vec::new(),
+          ..board.clone()
+        },
+      );
+
+      for list in board.lists.iter() {
+        index.list_order.push(list.id.clone());
+        index.titles.insert(list.id.clone(), list.title.clone());
+        index.lists.insert(
+          list.id.clone(),
+          (
+            board.id.clone(),
+            HyphaList {
+              issues: Vec::new(),
+              ..list.clone()
+            },
+          ),
+        );
+
+        for issue in list.issues.iter() {
+          index.issue_order.push(issue.id.clone());
+          index.titles.insert(issue.id.clone(), issue.title.clone());
+          index
+            .issues
+            .insert(issue.id.clone(), (list.id.clone(), issue.clone()));
+        }
+      }
+    }
+
+    let deps = file
+      .deps
+      .iter()
+      .chain(file.boards.iter().flat_map(|board| board.deps.iter()));
+    for dep in deps {
+      let key = dep_key(dep);
+      if index.deps.insert(key.clone()) {
+        index.dep_order.push(key);
+      }
+    }
+
+    for view in file.views.iter() {
+      index.view_order.push(view.id.clone());
+      index.titles.insert(view.id.clone(), view.title.clone());
+      index.views.insert(view.id.clone(), view.clone());
+    }
+
+    index
+  }
+
+  // Boards and lists holding items added, changed or moved in compared to
+  // `base`, directly or further down.
+  fn changed(&self, base: &Index) -> Changed {
+    let lists = self
+      .issues
+      .iter()
+      .filter(|(id, issue)| base.issues.get(*id) != Some(*issue))
+      .map(|(_, (list, _))| list.clone())
+      .collect::<HashSet<_>>();
+    let boards = self
+      .lists
+      .iter()
+      .filter(|(id, list)| {
+        lists.contains(*id) || base.lists.get(*id) != Some(*list)
+      })
+      .map(|(_, (board, _))| board.clone())
+      .collect();
+    Changed { boards, lists }
+  }
+
+  fn lists_of(&self, board: &HyphaId) -> Vec<HyphaId> {
+    self
+      .list_order
+      .iter()
+      .filter(|id| self.lists.get(*id).is_some_and(|(owner, _)| owner == board))
+      .cloned()
+      .collect()
+  }
+
+  fn issues_of(&self, list: &HyphaId) -> Vec<HyphaId> {
+    self
+      .issue_order
+      .iter()
+      .filter(|id| self.issues.get(*id).is_some_and(|(owner, _)| owner == list))
+      .cloned()
+      .collect()
+  }
+}
+
+fn dep_key(dep: &HyphaDep<HyphaFileIssueRef>) -> (HyphaId, HyphaId) {
+  (dep.left.issue.clone(), dep.right.issue.clone())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    r#ref::HyphaRef,
+    testing::{board_ref, edit, issue_ref, list_ref, titles, work},
+  };
+
+  fn always(side: HyphaSide) -> impl Fn(&HyphaConflictKey) -> HyphaSide {
+    move |_| side
+  }
+
+  fn list_titles(file: &HyphaFile) -> Vec<String> {
+    file
+      .boards
+      .iter()
+      .flat_map(|board| board.lists.iter())
+      .map(|list| list.title.clone())
+      .collect()
+  }
+
+  #[test]
+  fn takes_changes_from_both_sides() {
+    let base = work();
+    let mut ours = base.clone();
+    edit(&mut ours, "a", |issue| {
+      issue.description = "ours".to_string()
+    });
+    let mut theirs = base.clone();
+    edit(&mut theirs, "b", |issue| {
+      issue.description = "theirs".to_string()
+    });
+
+    let merged = merge(&base, &ours, &theirs, always(HyphaSide::Ours));
+    assert!(merged.conflicts.is_empty());
+    let a = issue_ref(&merged.file, "a");
+    let b = issue_ref(&merged.file, "b");
+    assert_eq!(
+      a.get_item_from_container(&merged.file)
+        .map(|issue| issue.description.as_str()),
+      Some("ours")
+    );
+    assert_eq!(
+      b.get_item_from_container(&merged.file)
+        .map(|issue| issue.description.as_str()),
+      Some("theirs")
+    );
+  }
+
+  #[test]
+  fn reports_items_changed_on_both_sides() {
+    let base = work();
+    let mut ours = base.clone();
+    edit(&mut ours, "a", |issue| issue.title = "ours".to_string());
+    let mut theirs = base.clone();
+    edit(&mut theirs, "a", |issue| issue.title = "theirs".to_string());
+
+    let merged = merge(&base, &ours, &theirs, always(HyphaSide::Theirs));
+    assert_eq!(merged.conflicts.len(), 1);
+    let all = issue_refs(&merged.file);
+    assert_eq!(titles(&merged.file, &all), ["theirs", "b", "c", "d"]);
+  }
+
+  #[test]
+  fn keeps_list_removed_while_issue_edited() {
+    let base = work();
+    let mut ours = base.clone();
+    ours.remove_list(list_ref(&ours, "Doing"));
+    let mut theirs = base.clone();
+    edit(&mut theirs, "c", |issue| issue.title = "edited".to_string());
+
+    let merged = merge(&base, &ours, &theirs, always(HyphaSide::Theirs));
+    let list = HyphaConflictKey::Item(list_ref(&base, "Doing").list);
+    assert!(merged.conflicts.iter().any(|conflict| conflict.key == list));
+    assert_eq!(list_titles(&merged.file), ["Todo", "Doing", "Done"]);
+    let all = issue_refs(&merged.file);
+    assert_eq!(titles(&merged.file, &all), ["a", "b", "edited", "d"]);
+  }
+
+  #[test]
+  fn keeps_list_removed_while_issue_added() {
+    let base = work();
+    let mut ours = base.clone();
+    ours.remove_list(list_ref(&ours, "Doing"));
+    let mut theirs = base.clone();
+    theirs.add_issue(list_ref(&theirs, "Doing"));
+
+    let merged = merge(&base, &ours, &theirs, |key| match key {
+      HyphaConflictKey::Item(_) => HyphaSide::Theirs,
+      HyphaConflictKey::Preferences => HyphaSide::Ours,
+    });
+    assert_eq!(merged.conflicts.len(), 1);
+    assert_eq!(list_titles(&merged.file), ["Todo", "Doing", "Done"]);
+    assert_eq!(issue_refs(&merged.file).len(), 5);
+  }
+
+  #[test]
+  fn removes_list_with_its_issues_when_chosen() {
+    let base = work();
+    let mut ours = base.clone();
+    ours.remove_list(list_ref(&ours, "Doing"));
+    let mut theirs = base.clone();
+    theirs.add_issue(list_ref(&theirs, "Doing"));
+
+    let merged = merge(&base, &ours, &theirs, always(HyphaSide::Ours));
+    assert_eq!(merged.conflicts.len(), 1);
+    assert_eq!(list_titles(&merged.file), ["Todo", "Done"]);
+    let all = issue_refs(&merged.file);
+    assert_eq!(titles(&merged.file, &all), ["a", "b", "d"]);
+  }
+
+  #[test]
+  fn keeps_board_removed_while_issue_edited() {
+    let base = work();
+    let mut ours = base.clone();
+    ours.remove_board(board_ref(&ours, "Work"));
+    let mut theirs = base.clone();
+    edit(&mut theirs, "a", |issue| issue.title = "edited".to_string());
+
+    let merged = merge(&base, &ours, &theirs, always(HyphaSide::Theirs));
+    let board = HyphaConflictKey::Item(board_ref(&base, "Work").board);
+    assert!(merged
+      .conflicts
+      .iter()
+      .any(|conflict| conflict.key == board));
+    let all = issue_refs(&merged.file);
+    assert_eq!(titles(&merged.file, &all), ["edited", "b", "c", "d"]);
+  }
+
+  fn issue_refs(file: &HyphaFile) -> Vec<HyphaFileIssueRef> {
+    HyphaDepGraph::new(file).issues().cloned().collect()
+  }
+}
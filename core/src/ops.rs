@@ -0,0 +1,722 @@
+use chrono::Utc;
+use std::{collections::HashMap, mem};
+
+use crate::{
+  board::HyphaBoard,
+  container::HyphaContainerOps,
+  dep::HyphaDep,
+  file::HyphaFile,
+  graph::{DepError, HyphaDepGraph},
+  id::HyphaId,
+  issue::HyphaIssue,
+  list::HyphaList,
+  preferences::HyphaPreferences,
+  r#ref::{
+    HyphaFileBoardRef, HyphaFileIssueRef, HyphaFileListRef, HyphaRef,
+    WithHyphaRef,
+  },
+  view::HyphaView,
+};
+
+impl HyphaFile {
+  pub fn update_preferences(&mut self, preferences: HyphaPreferences) {
+    self.preferences = preferences;
+    self.changes.meta();
+  }
+
+  pub fn add_view(&mut self, view: HyphaView) -> HyphaId {
+    let id = view.id.clone();
+    self.views.push(view);
+    self.changes.meta();
+    id
+  }
+
+  pub fn update_view(&mut self, view: HyphaView) -> bool {
+    match self
+      .views
+      .iter_mut()
+      .find(|candidate| candidate.id == view.id)
+    {
+      Some(container_view) => {
+        *container_view = view;
+        self.changes.meta();
+        true
+      }
+      None => false,
+    }
+  }
+
+  pub fn remove_view(&mut self, id: &HyphaId) -> bool {
+    let count = self.views.len();
+    self.views.retain(|view| view.id != *id);
+    let removed = self.views.len() != count;
+    if removed {
+      self.changes.meta();
+    }
+    removed
+  }
+
+  pub fn add_board(&mut self, title: String) -> HyphaFileBoardRef {
+    let board = HyphaBoard::new(title);
+    let board_ref = HyphaFileBoardRef {
+      board: board.id.clone(),
+    };
+    self.changes.board_tree(&board);
+    self.boards.push(board);
+    board_ref
+  }
+
+  pub fn update_board(
+    &mut self,
+    board: WithHyphaRef<HyphaBoard, HyphaFileBoardRef>,
+  ) -> bool {
+    match self
+      .boards
+      .iter_mut()
+      .find(|container_board| container_board.id == board.r#ref.board)
+    {
+      Some(container_board) => {
+        let old = mem::replace(container_board, board.item);
+        self.changes.replaced_board(&old, container_board);
+        true
+      }
+      None => false,
+    }
+  }
+
+  pub fn remove_board(&mut self, board_ref: HyphaFileBoardRef) -> bool {
+    let Some(index) = self
+      .boards
+      .iter()
+      .position(|board| board.id == board_ref.board)
+    else {
+      return false;
+    };
+    let board = self.boards.remove(index);
+    self.changes.removed_board(&board);
+    for board in self.boards.iter().skip(index) {
+      self.changes.board(board);
+    }
+    let changes = &mut self.changes;
+    self.deps.retain(|dep| {
+      let kept =
+        dep.left.board != board_ref.board && dep.right.board != board_ref.board;
+      if !kept {
+        changes.dep(dep, false);
+      }
+      kept
+    });
+    true
+  }
+
+  pub fn add_list(
+    &mut self,
+    board_ref: HyphaFileBoardRef,
+    title: String,
+  ) -> Option<HyphaFileListRef> {
+    let board = self
+      .boards
+      .iter_mut()
+      .find(|board| board.id == board_ref.board)?;
+    let list = HyphaList::new(title);
+    let list_ref = HyphaFileListRef {
+      list: list.id.clone(),
+      stage: board.lists.len(),
+      board: board.id.clone(),
+    };
+    self.changes.list_tree(&board.id, &list);
+    board.lists.push(list);
+    Some(list_ref)
+  }
+
+  pub fn update_list(
+    &mut self,
+    list: WithHyphaRef<HyphaList, HyphaFileListRef>,
+  ) -> bool {
+    let container_list = self
+      .boards
+      .iter_mut()
+      .find(|board| board.id == list.r#ref.board)
+      .and_then(|board| {
+        board
+          .lists
+          .iter_mut()
+          .find(|container_list| container_list.id == list.r#ref.list)
+      });
+    match container_list {
+      Some(container_list) => {
+        let old = mem::replace(container_list, list.item);
+        self
+          .changes
+          .replaced_list(&list.r#ref.board, &old, container_list);
+        true
+      }
+      None => false,
+    }
+  }
+
+  pub fn remove_list(&mut self, list_ref: HyphaFileListRef) -> bool {
+    let Some(board) = self
+      .boards
+      .iter_mut()
+      .find(|board| board.id == list_ref.board)
+    else {
+      return false;
+    };
+    let Some(index) =
+      board.lists.iter().position(|list| list.id == list_ref.list)
+    else {
+      return false;
+    };
+    let list = board.lists.remove(index);
+    let changes = &mut self.changes;
+    changes.removed_list(&list);
+    for list in board.lists.iter().skip(index) {
+      changes.list(&board.id, list);
+    }
+
+    let mut kept = |dep: &HyphaDep<HyphaFileIssueRef>| {
+      let kept =
+        dep.left.list != list_ref.list && dep.right.list != list_ref.list;
+      if !kept {
+        changes.dep(dep, false);
+      }
+      kept
+    };
+    board.deps.retain(&mut kept);
+    self.deps.retain(kept);
+    true
+  }
+
+  pub fn move_list(
+    &mut self,
+    list_ref: HyphaFileListRef,
+    index: usize,
+  ) -> Option<HyphaFileListRef> {
+    let board = self
+      .boards
+      .iter_mut()
+      .find(|board| board.id == list_ref.board)?;
+    if !board.move_to(&list_ref.list, index) {
+      return None;
+    }
+    for list in board.lists.iter() {
+      self.changes.list(&board.id, list);
+    }
+
+    let stages = board
+      .lists
+      .iter()
+      .enumerate()
+      .map(|(stage, list)| (list.id.clone(), stage))
+      .collect::<HashMap<_, _>>();
+    let deps = self.deps.iter_mut().chain(
+      self
+        .boards
+        .iter_mut()
+        .flat_map(|board| board.deps.iter_mut()),
+    );
+    for dep in deps {
+      for issue_ref in [&mut dep.left, &mut dep.right] {
+        if let Some(stage) = stages.get(&issue_ref.list) {
+          issue_ref.stage = *stage;
+        }
+      }
+    }
+
+    Some(HyphaFileListRef {
+      stage: *stages.get(&list_ref.list)?,
+      ..list_ref
+    })
+  }
+
+  pub fn add_issue(
+    &mut self,
+    list_ref: HyphaFileListRef,
+  ) -> Option<HyphaFileIssueRef> {
+    let list = self
+      .boards
+      .iter_mut()
+      .find(|board| board.id == list_ref.board)?
+      .lists
+      .iter_mut()
+      .find(|list| list.id == list_ref.list)?;
+    let issue = HyphaIssue::default();
+    let issue_ref = HyphaFileIssueRef {
+      issue: issue.id.clone(),
+      list: list_ref.list.clone(),
+      stage: list_ref.stage,
+      board: list_ref.board.clone(),
+    };
+    self
+      .changes
+      .issue(&list_ref.board, &list_ref.list, &issue.id);
+    list.issues.push(issue);
+    Some(issue_ref)
+  }
+
+  pub fn update_issue(
+    &mut self,
+    mut issue: WithHyphaRef<HyphaIssue, HyphaFileIssueRef>,
+  ) -> bool {
+    issue.item.updated = Some(Utc::now());
+    match issue.r#ref.get_item_from_container_mut(self) {
+      Some(container_issue) => {
+        *container_issue = issue.item;
+        let issue_ref = issue.r#ref;
+        self
+          .changes
+          .issue(&issue_ref.board, &issue_ref.list, &issue_ref.issue);
+        true
+      }
+      None => false,
+    }
+  }
+
+  pub fn remove_issue(&mut self, issue_ref: HyphaFileIssueRef) -> bool {
+    let Some(board) = self
+      .boards
+      .iter_mut()
+      .find(|board| board.id == issue_ref.board)
+    else {
+      return false;
+    };
+    let Some(list) = board
+      .lists
+      .iter_mut()
+      .find(|list| list.id == issue_ref.list)
+    else {
+      return false;
+    };
+    let Some(index) = list
+      .issues
+      .iter()
+      .position(|issue| issue.id == issue_ref.issue)
+    else {
+      return false;
+    };
+    list.issues.remove(index);
+    let changes = &mut self.changes;
+    changes.removed_issue(&issue_ref.issue);
+    for issue in list.issues.iter().skip(index) {
+      changes.issue(&board.id, &list.id, &issue.id);
+    }
+
+    let mut kept = |dep: &HyphaDep<HyphaFileIssueRef>| {
+      let kept =
+        dep.left.issue != issue_ref.issue && dep.right.issue != issue_ref.issue;
+      if !kept {
+        changes.dep(dep, false);
+      }
+      kept
+    };
+    board.deps.retain(&mut kept);
+    self.deps.retain(kept);
+    true
+  }
+
+  pub fn move_issue(
+    &mut self,
+    from: HyphaFileIssueRef,
+    to: HyphaFileListRef,
+    index: usize,
+  ) -> Option<HyphaFileIssueRef> {
+    let stage = self
+      .boards
+      .iter()
+      .find(|board| board.id == to.board)?
+      .lists
+      .iter()
+      .position(|list| list.id == to.list)?;
+    let from_index = self
+      .boards
+      .iter()
+      .find(|board| board.id == from.board)?
+      .lists
+      .iter()
+      .find(|list| list.id == from.list)?
+      .issues
+      .iter()
+      .position(|issue| issue.id == from.issue)?;
+    let mut issue = from.get_item_from_container(self)?.clone();
+    issue.updated = Some(Utc::now());
+
+    from.remove_item_from_container(self);
+    let index = if from.list == to.list && from_index < index {
+      index.saturating_sub(1)
+    } else {
+      index
+    };
+    if let Some(list) = to.get_item_from_container_mut(self) {
+      list.issues.insert(index.min(list.issues.len()), issue);
+    }
+
+    for (board_id, list_id) in
+      [(&from.board, &from.list), (&to.board, &to.list)]
+    {
+      let list = self
+        .boards
+        .iter()
+        .find(|board| board.id == *board_id)
+        .and_then(|board| board.lists.iter().find(|list| list.id == *list_id));
+      for issue in list.iter().flat_map(|list| list.issues.iter()) {
+        self.changes.issue(board_id, list_id, &issue.id);
+      }
+    }
+
+    let moved = HyphaFileIssueRef {
+      issue: from.issue.clone(),
+      list: to.list.clone(),
+      stage,
+      board: to.board.clone(),
+    };
+    if from.board != to.board {
+      let board_ref = HyphaFileBoardRef {
+        board: from.board.clone(),
+      };
+      if let Some(board) = board_ref.get_item_from_container_mut(self) {
+        let (crossing, staying) =
+          board.deps.drain(..).partition::<Vec<_>, _>(|dep| {
+            dep.left.issue == from.issue || dep.right.issue == from.issue
+          });
+        board.deps = staying;
+        self.deps.extend(crossing);
+      }
+    }
+    let deps = self.deps.iter_mut().chain(
+      self
+        .boards
+        .iter_mut()
+        .flat_map(|board| board.deps.iter_mut()),
+    );
+    for dep in deps {
+      if dep.left.issue == moved.issue {
+        dep.left = moved.clone();
+      }
+      if dep.right.issue == moved.issue {
+        dep.right = moved.clone();
+      }
+    }
+
+    Some(moved)
+  }
+
+  pub fn add_dep(
+    &mut self,
+    dep: HyphaDep<HyphaFileIssueRef>,
+  ) -> Result<(), DepError> {
+    let graph = HyphaDepGraph::new(self);
+    graph.check(&dep)?;
+    let dep = HyphaDep {
+      left: graph.issue(&dep.left.issue).cloned().unwrap_or(dep.left),
+      right: graph.issue(&dep.right.issue).cloned().unwrap_or(dep.right),
+    };
+
+    if dep.left.board == dep.right.board {
+      let board_ref = HyphaFileBoardRef {
+        board: dep.left.board.clone(),
+      };
+      if let Some(board) = board_ref.get_item_from_container_mut(self) {
+        board.deps.push(dep.clone());
+        self.changes.dep(&dep, true);
+        return Ok(());
+      }
+    }
+    self.changes.dep(&dep, true);
+    self.deps.push(dep);
+    Ok(())
+  }
+
+  pub fn remove_dep(&mut self, dep: HyphaDep<HyphaFileIssueRef>) -> bool {
+    let matches = |other: &HyphaDep<HyphaFileIssueRef>| {
+      other.left.issue == dep.left.issue && other.right.issue == dep.right.issue
+    };
+
+    let count = self.dep_count();
+    self.deps.retain(|other| !matches(other));
+    for board in self.boards.iter_mut() {
+      board.deps.retain(|other| !matches(other));
+    }
+    let removed = self.dep_count() != count;
+    if removed {
+      self.changes.dep(&dep, false);
+    }
+    removed
+  }
+
+  fn dep_count(&self) -> usize {
+    self
+      .boards
+      .iter()
+      .map(|board| board.deps.len())
+      .fold(self.deps.len(), usize::saturating_add)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::{
+    board, board_ref, dep, file, issue_ref, link, list, list_ref, titles, work,
+  };
+
+  fn all_deps(file: &HyphaFile) -> Vec<(String, String)> {
+    let title = |issue_ref: &HyphaFileIssueRef| {
+      issue_ref
+        .get_item_from_container(file)
+        .map(|issue| issue.title.clone())
+        .unwrap_or_default()
+    };
+    file
+      .deps
+      .iter()
+      .chain(file.boards.iter().flat_map(|board| board.deps.iter()))
+      .map(|dep| (title(&dep.left), title(&dep.right)))
+      .collect()
+  }
+
+  fn two_boards() -> HyphaFile {
+    file(vec![
+      board("Work", vec![list("Todo", &["a", "b"]), list("Done", &[])]),
+      board("Home", vec![list("Todo", &["c"]), list("Done", &[])]),
+    ])
+  }
+
+  #[test]
+  fn adds_empty_board() {
+    let mut file = work();
+    let board_ref = file.add_board("Home".to_string());
+    let board = board_ref.get_item_from_container(&file);
+    assert_eq!(board.map(|board| board.title.as_str()), Some("Home"));
+    assert_eq!(board.map(|board| board.lists.len()), Some(0));
+    assert_eq!(file.boards.len(), 2);
+  }
+
+  #[test]
+  fn adds_empty_list() {
+    let mut file = work();
+    let list_ref = file.add_list(board_ref(&file, "Work"), "Later".to_string());
+    let list = list_ref
+      .as_ref()
+      .and_then(|list_ref| list_ref.get_item_from_container(&file));
+    assert_eq!(list.map(|list| list.title.as_str()), Some("Later"));
+    assert_eq!(list.map(|list| list.issues.len()), Some(0));
+    assert_eq!(list_ref.map(|list_ref| list_ref.stage), Some(3));
+  }
+
+  #[test]
+  fn adds_no_list_to_missing_board() {
+    let mut file = work();
+    let missing = HyphaFileBoardRef {
+      board: HyphaId::new(),
+    };
+    assert_eq!(file.add_list(missing, "Later".to_string()), None);
+  }
+
+  #[test]
+  fn updates_preferences() {
+    let mut file = work();
+    let preferences = HyphaPreferences {
+      capacity: 8.0,
+      ..Default::default()
+    };
+    file.update_preferences(preferences.clone());
+    assert_eq!(file.preferences, preferences);
+  }
+
+  #[test]
+  fn adds_updates_and_removes_views() {
+    let mut file = work();
+    let id =
+      file.add_view(HyphaView::new("Bugs".to_string(), "tag:bug".to_string()));
+    let mut view = file.views.first().cloned();
+    if let Some(view) = view.as_mut() {
+      view.title = "All bugs".to_string();
+      assert!(file.update_view(view.clone()));
+    }
+    assert_eq!(file.views.first(), view.as_ref());
+    assert!(file.remove_view(&id));
+    assert!(!file.remove_view(&id));
+    assert!(file.views.is_empty());
+  }
+
+  #[test]
+  fn updates_board() {
+    let mut file = work();
+    let board_ref = board_ref(&file, "Work");
+    let mut board = board_ref.get_item_from_container(&file).cloned();
+    if let Some(board) = board.as_mut() {
+      board.title = "Job".to_string();
+      assert!(file.update_board(WithHyphaRef {
+        item: board.clone(),
+        r#ref: board_ref.clone(),
+      }));
+    }
+    assert_eq!(board_ref.get_item_from_container(&file), board.as_ref());
+  }
+
+  #[test]
+  fn removes_board_with_its_deps() {
+    let mut file = two_boards();
+    link(&mut file, &[("a", "b"), ("a", "c")]);
+    assert!(file.remove_board(board_ref(&file, "Home")));
+    assert_eq!(file.boards.len(), 1);
+    assert_eq!(all_deps(&file), [("a".to_string(), "b".to_string())]);
+    assert!(!file.remove_board(board_ref(&file, "Home")));
+  }
+
+  #[test]
+  fn updates_list() {
+    let mut file = work();
+    let list_ref = list_ref(&file, "Todo");
+    let mut list = list_ref.get_item_from_container(&file).cloned();
+    if let Some(list) = list.as_mut() {
+      list.title = "Backlog".to_string();
+      assert!(file.update_list(WithHyphaRef {
+        item: list.clone(),
+        r#ref: list_ref.clone(),
+      }));
+    }
+    assert_eq!(list_ref.get_item_from_container(&file), list.as_ref());
+  }
+
+  #[test]
+  fn removes_list_with_its_deps() {
+    let mut file = work();
+    link(&mut file, &[("a", "b"), ("a", "c")]);
+    assert!(file.remove_list(list_ref(&file, "Doing")));
+    assert_eq!(all_deps(&file), [("a".to_string(), "b".to_string())]);
+  }
+
+  #[test]
+  fn moves_list_and_restages_deps() {
+    let mut file = work();
+    link(&mut file, &[("a", "c")]);
+    let moved = file.move_list(list_ref(&file, "Doing"), 0);
+    assert_eq!(moved.map(|list_ref| list_ref.stage), Some(0));
+    let lists = file.boards[0]
+      .lists
+      .iter()
+      .map(|list| list.title.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(lists, ["Doing", "Todo", "Done"]);
+    let dep = file.boards[0].deps.first();
+    assert_eq!(
+      dep.map(|dep| (dep.left.stage, dep.right.stage)),
+      Some((1, 0))
+    );
+  }
+
+  #[test]
+  fn adds_issue_at_end_of_list() {
+    let mut file = work();
+    let issue_ref = file.add_issue(list_ref(&file, "Todo"));
+    assert_eq!(issue_ref.as_ref().map(|issue_ref| issue_ref.stage), Some(0));
+    assert_eq!(file.boards[0].lists[0].issues.len(), 3);
+    assert_eq!(
+      file.boards[0].lists[0].issues.last().map(|issue| &issue.id),
+      issue_ref.as_ref().map(|issue_ref| &issue_ref.issue)
+    );
+  }
+
+  #[test]
+  fn updates_issue_and_touches_it() {
+    let mut file = work();
+    let issue_ref = issue_ref(&file, "a");
+    let mut issue = issue_ref.get_item_from_container(&file).cloned();
+    if let Some(issue) = issue.as_mut() {
+      issue.title = "e".to_string();
+      assert!(file.update_issue(WithHyphaRef {
+        item: issue.clone(),
+        r#ref: issue_ref.clone(),
+      }));
+    }
+    let updated = issue_ref.get_item_from_container(&file);
+    assert_eq!(updated.map(|issue| issue.title.as_str()), Some("e"));
+    assert!(updated.is_some_and(|issue| issue.updated.is_some()));
+  }
+
+  #[test]
+  fn removes_issue_with_its_deps() {
+    let mut file = work();
+    link(&mut file, &[("a", "b"), ("b", "c")]);
+    assert!(file.remove_issue(issue_ref(&file, "a")));
+    assert_eq!(all_deps(&file), [("b".to_string(), "c".to_string())]);
+    assert!(!file.remove_issue(issue_ref(&file, "a")));
+  }
+
+  #[test]
+  fn moves_issue_within_list() {
+    let mut file = work();
+    let moved =
+      file.move_issue(issue_ref(&file, "a"), list_ref(&file, "Todo"), 2);
+    assert_eq!(moved, Some(issue_ref(&file, "a")));
+    let issues = file.boards[0].lists[0].issues.clone();
+    assert_eq!(
+      issues
+        .iter()
+        .map(|issue| issue.title.as_str())
+        .collect::<Vec<_>>(),
+      ["b", "a"]
+    );
+  }
+
+  #[test]
+  fn moves_issue_and_repoints_deps() {
+    let mut file = work();
+    link(&mut file, &[("a", "b")]);
+    let moved =
+      file.move_issue(issue_ref(&file, "a"), list_ref(&file, "Doing"), 0);
+    assert_eq!(moved.as_ref().map(|moved| moved.stage), Some(1));
+    assert_eq!(
+      titles(&file, &[issue_ref(&file, "a"), issue_ref(&file, "c")]),
+      ["a", "c"]
+    );
+    assert_eq!(file.boards[0].lists[1].issues[0].title, "a");
+    assert_eq!(
+      file.boards[0].deps.first().map(|dep| &dep.left),
+      moved.as_ref()
+    );
+    assert!(file.boards[0].lists[1].issues[0].updated.is_some());
+  }
+
+  #[test]
+  fn moves_issue_across_boards_with_its_deps() {
+    let mut file = two_boards();
+    link(&mut file, &[("a", "b")]);
+    let home = HyphaFileListRef {
+      list: file.boards[1].lists[0].id.clone(),
+      stage: 0,
+      board: file.boards[1].id.clone(),
+    };
+    let moved = file.move_issue(issue_ref(&file, "a"), home, 0);
+    assert_eq!(
+      moved.as_ref().map(|moved| &moved.board),
+      Some(&file.boards[1].id)
+    );
+    assert!(file.boards[0].deps.is_empty());
+    assert_eq!(file.deps.first().map(|dep| &dep.left), moved.as_ref());
+  }
+
+  #[test]
+  fn adds_deps_to_board_or_file() {
+    let mut file = two_boards();
+    assert_eq!(file.add_dep(dep(&file, "a", "b")), Ok(()));
+    assert_eq!(file.add_dep(dep(&file, "b", "c")), Ok(()));
+    assert_eq!(file.boards[0].deps.len(), 1);
+    assert_eq!(file.deps.len(), 1);
+    assert!(matches!(
+      file.add_dep(dep(&file, "c", "a")),
+      Err(DepError::Cycle(_))
+    ));
+  }
+
+  #[test]
+  fn removes_deps() {
+    let mut file = two_boards();
+    link(&mut file, &[("a", "b"), ("b", "c")]);
+    assert!(file.remove_dep(dep(&file, "b", "c")));
+    assert_eq!(all_deps(&file), [("a".to_string(), "b".to_string())]);
+    assert!(!file.remove_dep(dep(&file, "b", "c")));
+  }
+}
@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct HyphaPreferences {
+  pub priority: f64,
+  pub age: f64,
+  pub due: f64,
+  pub unblocking: f64,
+  pub capacity: f64,
+}
+
+impl Default for HyphaPreferences {
+  fn default() -> Self {
+    Self {
+      priority: 1.0,
+      age: 0.1,
+      due: 1.0,
+      unblocking: 1.0,
+      capacity: 5.0,
+    }
+  }
+}
+
+impl HyphaPreferences {
+  pub fn with(&self, overrides: &HyphaPreferencesOverride) -> Self {
+    Self {
+      priority: overrides.priority.unwrap_or(self.priority),
+      age: overrides.age.unwrap_or(self.age),
+      due: overrides.due.unwrap_or(self.due),
+      unblocking: overrides.unblocking.unwrap_or(self.unblocking),
+      capacity: overrides.capacity.unwrap_or(self.capacity),
+    }
+  }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(default)]
+pub struct HyphaPreferencesOverride {
+  pub priority: Option<f64>,
+  pub age: Option<f64>,
+  pub due: Option<f64>,
+  pub unblocking: Option<f64>,
+  pub capacity: Option<f64>,
+}
+
+impl HyphaPreferencesOverride {
+  pub fn is_empty(&self) -> bool {
+    *self == Self::default()
+  }
+}
+
+impl From<&HyphaPreferences> for HyphaPreferencesOverride {
+  fn from(preferences: &HyphaPreferences) -> Self {
+    Self {
+      priority: Some(preferences.priority),
+      age: Some(preferences.age),
+      due: Some(preferences.due),
+      unblocking: Some(preferences.unblocking),
+      capacity: Some(preferences.capacity),
+    }
+  }
+}
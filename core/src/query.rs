@@ -0,0 +1,688 @@
+use chrono::{Days, NaiveDate};
+use std::{collections::HashMap, fmt, mem, str::FromStr};
+use thiserror::Error;
+
+use crate::{
+  file::HyphaFile,
+  graph::HyphaDepGraph,
+  id::HyphaId,
+  issue::{HyphaIssue, HyphaPriority},
+  r#ref::{HyphaFileIssueRef, HyphaRef},
+};
+
+/// Filter over issues like `board:infra tag:bug blocked:false due<7d`.
+///
+/// A query is a list of terms separated by whitespace that all have to match.
+/// A term is either a word searched for in the title, description and tags or
+/// a `field:value` filter, where numbers and dates can also be compared with
+/// `<`, `<=`, `>` and `>=`. Terms are negated with a leading `-` and values
+/// with spaces are quoted like `list:"in progress"`. Terms with a field not
+/// listed below, like URLs, are searched for as words.
+///
+/// The fields are:
+/// - `board`, `list`, `title` and `assignee` containing the value
+/// - `tag` equal to the value
+/// - `priority` from `low` to `urgent`
+/// - `estimate` as a number
+/// - `due`, `created` and `updated` as a date like `2025-01-31`, `today` or a
+///   number of days or weeks like `7d` or `2w`, counted until `due` and since
+///   `created` and `updated`
+/// - `blocked`, `blocking` and `done` as `true` or `false`
+/// - `has` with `priority`, `estimate`, `due`, `tags`, `assignee` or
+///   `description`
+#[derive(Debug, Clone, PartialEq)]
+pub struct HyphaQuery {
+  source: String,
+  terms: Vec<HyphaQueryTerm>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct HyphaQueryTerm {
+  negated: bool,
+  filter: HyphaQueryFilter,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum HyphaQueryFilter {
+  Text(String),
+  Board(String),
+  List(String),
+  Title(String),
+  Assignee(String),
+  Tag(String),
+  Priority(HyphaQueryOperator, HyphaPriority),
+  Estimate(HyphaQueryOperator, f64),
+  Due(HyphaQueryOperator, HyphaQueryDate),
+  Created(HyphaQueryOperator, HyphaQueryDate),
+  Updated(HyphaQueryOperator, HyphaQueryDate),
+  Blocked(bool),
+  Blocking(bool),
+  Done(bool),
+  Has(HyphaQueryField),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HyphaQueryOperator {
+  Equal,
+  Less,
+  LessOrEqual,
+  Greater,
+  GreaterOrEqual,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HyphaQueryDate {
+  Date(NaiveDate),
+  Days(u64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum HyphaQueryField {
+  Priority,
+  Estimate,
+  Due,
+  Tags,
+  Assignee,
+  Description,
+}
+
+/// What a query needs to know about the file beyond the issue itself.
+#[derive(Debug, Clone)]
+pub struct HyphaQueryScope<'a> {
+  file: &'a HyphaFile,
+  graph: HyphaDepGraph,
+  done_stages: HashMap<HyphaId, usize>,
+  today: NaiveDate,
+}
+
+impl<'a> HyphaQueryScope<'a> {
+  pub fn new(file: &'a HyphaFile, today: NaiveDate) -> Self {
+    let done_stages = file
+      .boards
+      .iter()
+      .filter_map(|board| {
+        board
+          .lists
+          .len()
+          .checked_sub(1)
+          .map(|stage| (board.id.clone(), stage))
+      })
+      .collect();
+    Self {
+      file,
+      graph: HyphaDepGraph::new(file),
+      done_stages,
+      today,
+    }
+  }
+
+  pub fn file(&self) -> &'a HyphaFile {
+    self.file
+  }
+
+  pub fn graph(&self) -> &HyphaDepGraph {
+    &self.graph
+  }
+
+  fn is_done(&self, issue_ref: &HyphaFileIssueRef) -> bool {
+    self.done_stages.get(&issue_ref.board) == Some(&issue_ref.stage)
+  }
+
+  // Like the daily suggestions a prerequisite stops blocking once it is done
+  // or moved past the issue on the same board.
+  fn blocks(
+    &self,
+    prerequisite: &HyphaFileIssueRef,
+    dependent: &HyphaFileIssueRef,
+  ) -> bool {
+    let passed = prerequisite.board == dependent.board
+      && prerequisite.stage > dependent.stage;
+    !self.is_done(prerequisite) && !passed
+  }
+
+  fn is_blocked(&self, issue_ref: &HyphaFileIssueRef) -> bool {
+    self
+      .graph
+      .prerequisites(&issue_ref.issue)
+      .iter()
+      .filter_map(|id| self.graph.issue(id))
+      .any(|prerequisite| self.blocks(prerequisite, issue_ref))
+  }
+
+  fn is_blocking(&self, issue_ref: &HyphaFileIssueRef) -> bool {
+    self
+      .graph
+      .dependents(&issue_ref.issue)
+      .iter()
+      .filter_map(|id| self.graph.issue(id))
+      .any(|dependent| self.blocks(issue_ref, dependent))
+  }
+}
+
+impl HyphaQuery {
+  pub fn parse(source: &str) -> Result<Self, QueryError> {
+    let terms = split(source)?
+      .into_iter()
+      .map(|(token, quoted)| parse_term(&token, quoted))
+      .collect::<Result<Vec<_>, _>>()?;
+    Ok(Self {
+      source: source.trim().to_string(),
+      terms,
+    })
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.terms.is_empty()
+  }
+
+  pub fn matches(
+    &self,
+    scope: &HyphaQueryScope,
+    issue: &HyphaIssue,
+    issue_ref: &HyphaFileIssueRef,
+  ) -> bool {
+    self
+      .terms
+      .iter()
+      .all(|term| term.filter.matches(scope, issue, issue_ref) != term.negated)
+  }
+
+  /// Refs of the issues matching the query in file order.
+  pub fn filter(&self, scope: &HyphaQueryScope) -> Vec<HyphaFileIssueRef> {
+    scope
+      .graph
+      .issues()
+      .filter(|issue_ref| {
+        issue_ref
+          .get_item_from_container(scope.file)
+          .is_some_and(|issue| self.matches(scope, issue, issue_ref))
+      })
+      .cloned()
+      .collect()
+  }
+}
+
+impl FromStr for HyphaQuery {
+  type Err = QueryError;
+
+  fn from_str(source: &str) -> Result<Self, Self::Err> {
+    Self::parse(source)
+  }
+}
+
+impl fmt::Display for HyphaQuery {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    f.write_str(&self.source)
+  }
+}
+
+impl HyphaQueryFilter {
+  fn matches(
+    &self,
+    scope: &HyphaQueryScope,
+    issue: &HyphaIssue,
+    issue_ref: &HyphaFileIssueRef,
+  ) -> bool {
+    match self {
+      HyphaQueryFilter::Text(text) => {
+        contains(&issue.title, text)
+          || contains(&issue.description, text)
+          || issue.tags.iter().any(|tag| contains(tag, text))
+      }
+      HyphaQueryFilter::Board(board) => scope
+        .file
+        .boards
+        .iter()
+        .find(|candidate| candidate.id == issue_ref.board)
+        .is_some_and(|candidate| contains(&candidate.title, board)),
+      HyphaQueryFilter::List(list) => scope
+        .file
+        .boards
+        .iter()
+        .find(|board| board.id == issue_ref.board)
+        .and_then(|board| board.lists.get(issue_ref.stage))
+        .is_some_and(|candidate| contains(&candidate.title, list)),
+      HyphaQueryFilter::Title(title) => contains(&issue.title, title),
+      HyphaQueryFilter::Assignee(assignee) => issue
+        .assignee
+        .as_ref()
+        .is_some_and(|candidate| contains(candidate, assignee)),
+      HyphaQueryFilter::Tag(tag) => issue
+        .tags
+        .iter()
+        .any(|candidate| candidate.to_lowercase() == *tag),
+      HyphaQueryFilter::Priority(operator, priority) => {
+        issue.priority.is_some_and(|candidate| {
+          operator.compare(rank(candidate), rank(*priority))
+        })
+      }
+      HyphaQueryFilter::Estimate(operator, estimate) => issue
+        .estimate
+        .is_some_and(|candidate| operator.compare(candidate, *estimate)),
+      HyphaQueryFilter::Due(operator, date) => {
+        match (issue.due, date.until(scope.today)) {
+          (Some(due), Some(date)) => operator.compare(due, date),
+          _ => false,
+        }
+      }
+      HyphaQueryFilter::Created(operator, date) => since(
+        issue.created.map(|created| created.date_naive()),
+        *operator,
+        date,
+        scope.today,
+      ),
+      HyphaQueryFilter::Updated(operator, date) => since(
+        issue.updated.map(|updated| updated.date_naive()),
+        *operator,
+        date,
+        scope.today,
+      ),
+      HyphaQueryFilter::Blocked(blocked) => {
+        scope.is_blocked(issue_ref) == *blocked
+      }
+      HyphaQueryFilter::Blocking(blocking) => {
+        scope.is_blocking(issue_ref) == *blocking
+      }
+      HyphaQueryFilter::Done(done) => scope.is_done(issue_ref) == *done,
+      HyphaQueryFilter::Has(field) => match field {
+        HyphaQueryField::Priority => issue.priority.is_some(),
+        HyphaQueryField::Estimate => issue.estimate.is_some(),
+        HyphaQueryField::Due => issue.due.is_some(),
+        HyphaQueryField::Tags => !issue.tags.is_empty(),
+        HyphaQueryField::Assignee => issue.assignee.is_some(),
+        HyphaQueryField::Description => !issue.description.trim().is_empty(),
+      },
+    }
+  }
+}
+
+impl HyphaQueryOperator {
+  fn compare<T: PartialOrd>(self, left: T, right: T) -> bool {
+    match self {
+      HyphaQueryOperator::Equal => left == right,
+      HyphaQueryOperator::Less => left < right,
+      HyphaQueryOperator::LessOrEqual => left <= right,
+      HyphaQueryOperator::Greater => left > right,
+      HyphaQueryOperator::GreaterOrEqual => left >= right,
+    }
+  }
+
+  // The same comparison with its sides swapped.
+  fn flip(self) -> Self {
+    match self {
+      HyphaQueryOperator::Equal => HyphaQueryOperator::Equal,
+      HyphaQueryOperator::Less => HyphaQueryOperator::Greater,
+      HyphaQueryOperator::LessOrEqual => HyphaQueryOperator::GreaterOrEqual,
+      HyphaQueryOperator::Greater => HyphaQueryOperator::Less,
+      HyphaQueryOperator::GreaterOrEqual => HyphaQueryOperator::LessOrEqual,
+    }
+  }
+}
+
+impl HyphaQueryDate {
+  fn until(self, today: NaiveDate) -> Option<NaiveDate> {
+    match self {
+      HyphaQueryDate::Date(date) => Some(date),
+      HyphaQueryDate::Days(days) => today.checked_add_days(Days::new(days)),
+    }
+  }
+
+  fn since(self, today: NaiveDate) -> Option<NaiveDate> {
+    match self {
+      HyphaQueryDate::Date(date) => Some(date),
+      HyphaQueryDate::Days(days) => today.checked_sub_days(Days::new(days)),
+    }
+  }
+}
+
+// Days since a date count backwards so `created<7d` means created less than
+// seven days ago while `created<2025-01-31` means created before that day.
+fn since(
+  date: Option<NaiveDate>,
+  operator: HyphaQueryOperator,
+  value: &HyphaQueryDate,
+  today: NaiveDate,
+) -> bool {
+  let (Some(date), Some(value_date)) = (date, value.since(today)) else {
+    return false;
+  };
+  match value {
+    HyphaQueryDate::Date(_) => operator.compare(date, value_date),
+    HyphaQueryDate::Days(_) => operator.flip().compare(date, value_date),
+  }
+}
+
+fn contains(text: &str, value: &str) -> bool {
+  text.to_lowercase().contains(value)
+}
+
+fn rank(priority: HyphaPriority) -> usize {
+  HyphaPriority::ALL
+    .iter()
+    .position(|candidate| *candidate == priority)
+    .unwrap_or_default()
+}
+
+// Splits the source on whitespace outside of quotes, keeping whether each
+// token started with a quote so quoted words are never read as filters.
+fn split(source: &str) -> Result<Vec<(String, bool)>, QueryError> {
+  let mut tokens = Vec::new();
+  let mut token = String::new();
+  let mut quoted = false;
+  let mut starts_quoted = false;
+  for c in source.chars() {
+    match c {
+      '"' => {
+        if token.is_empty() && !quoted {
+          starts_quoted = true;
+        }
+        quoted = !quoted;
+      }
+      c if c.is_whitespace() && !quoted => {
+        if !token.is_empty() || starts_quoted {
+          tokens.push((mem::take(&mut token), starts_quoted));
+        }
+        starts_quoted = false;
+      }
+      c => token.push(c),
+    }
+  }
+  if quoted {
+    return Err(QueryError::UnterminatedQuote);
+  }
+  if !token.is_empty() || starts_quoted {
+    tokens.push((token, starts_quoted));
+  }
+  Ok(tokens)
+}
+
+const FIELDS: [&str; 14] = [
+  "board", "list", "title", "assignee", "tag", "priority", "estimate", "due",
+  "created", "updated", "blocked", "blocking", "done", "has",
+];
+
+fn parse_term(token: &str, quoted: bool) -> Result<HyphaQueryTerm, QueryError> {
+  if quoted {
+    return Ok(HyphaQueryTerm {
+      negated: false,
+      filter: HyphaQueryFilter::Text(token.to_lowercase()),
+    });
+  }
+
+  let (negated, token) = match token.strip_prefix('-') {
+    Some(rest) if !rest.is_empty() => (true, rest),
+    _ => (false, token),
+  };
+  let Some(start) = token.find([':', '<', '>', '=']) else {
+    return Ok(HyphaQueryTerm {
+      negated,
+      filter: HyphaQueryFilter::Text(token.to_lowercase()),
+    });
+  };
+  let (field, rest) = token.split_at(start);
+  let field = field.to_lowercase();
+  if !FIELDS.contains(&field.as_str()) {
+    return Ok(HyphaQueryTerm {
+      negated,
+      filter: HyphaQueryFilter::Text(token.to_lowercase()),
+    });
+  }
+  let (operator, value) = if let Some(value) = rest.strip_prefix("<=") {
+    (HyphaQueryOperator::LessOrEqual, value)
+  } else if let Some(value) = rest.strip_prefix(">=") {
+    (HyphaQueryOperator::GreaterOrEqual, value)
+  } else if let Some(value) = rest.strip_prefix('<') {
+    (HyphaQueryOperator::Less, value)
+  } else if let Some(value) = rest.strip_prefix('>') {
+    (HyphaQueryOperator::Greater, value)
+  } else {
+    (HyphaQueryOperator::Equal, rest.get(1..).unwrap_or_default())
+  };
+  let value = value.trim();
+  if value.is_empty() {
+    return Err(QueryError::MissingValue(field));
+  }
+
+  let filter = parse_filter(&field, operator, value)?;
+  Ok(HyphaQueryTerm { negated, filter })
+}
+
+fn parse_filter(
+  field: &str,
+  operator: HyphaQueryOperator,
+  value: &str,
+) -> Result<HyphaQueryFilter, QueryError> {
+  let invalid =
+    || QueryError::InvalidValue(field.to_string(), value.to_string());
+  let text = || {
+    if operator == HyphaQueryOperator::Equal {
+      Ok(value.to_lowercase())
+    } else {
+      Err(QueryError::UnsupportedComparison(field.to_string()))
+    }
+  };
+  let flag = || match (operator, value.to_lowercase().as_str()) {
+    (HyphaQueryOperator::Equal, "true" | "yes") => Ok(true),
+    (HyphaQueryOperator::Equal, "false" | "no") => Ok(false),
+    (HyphaQueryOperator::Equal, _) => Err(invalid()),
+    _ => Err(QueryError::UnsupportedComparison(field.to_string())),
+  };
+
+  Ok(match field {
+    "board" => HyphaQueryFilter::Board(text()?),
+    "list" => HyphaQueryFilter::List(text()?),
+    "title" => HyphaQueryFilter::Title(text()?),
+    "assignee" => HyphaQueryFilter::Assignee(text()?),
+    "tag" => HyphaQueryFilter::Tag(text()?),
+    "priority" => HyphaQueryFilter::Priority(
+      operator,
+      HyphaPriority::parse(&value.to_lowercase()).ok_or_else(invalid)?,
+    ),
+    "estimate" => HyphaQueryFilter::Estimate(
+      operator,
+      value.parse::<f64>().map_err(|_| invalid())?,
+    ),
+    "due" => {
+      HyphaQueryFilter::Due(operator, parse_date(value).ok_or_else(invalid)?)
+    }
+    "created" => HyphaQueryFilter::Created(
+      operator,
+      parse_date(value).ok_or_else(invalid)?,
+    ),
+    "updated" => HyphaQueryFilter::Updated(
+      operator,
+      parse_date(value).ok_or_else(invalid)?,
+    ),
+    "blocked" => HyphaQueryFilter::Blocked(flag()?),
+    "blocking" => HyphaQueryFilter::Blocking(flag()?),
+    "done" => HyphaQueryFilter::Done(flag()?),
+    "has" => {
+      text()?;
+      HyphaQueryFilter::Has(match value.to_lowercase().as_str() {
+        "priority" => HyphaQueryField::Priority,
+        "estimate" => HyphaQueryField::Estimate,
+        "due" => HyphaQueryField::Due,
+        "tags" | "tag" => HyphaQueryField::Tags,
+        "assignee" => HyphaQueryField::Assignee,
+        "description" => HyphaQueryField::Description,
+        _ => return Err(invalid()),
+      })
+    }
+    _ => return Err(invalid()),
+  })
+}
+
+fn parse_date(value: &str) -> Option<HyphaQueryDate> {
+  if value.eq_ignore_ascii_case("today") {
+    return Some(HyphaQueryDate::Days(0));
+  }
+  if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+    return Some(HyphaQueryDate::Date(date));
+  }
+  if let Some(days) = value.strip_suffix('d') {
+    return days.parse::<u64>().ok().map(HyphaQueryDate::Days);
+  }
+  let weeks = value.strip_suffix('w')?.parse::<u64>().ok()?;
+  weeks.checked_mul(7).map(HyphaQueryDate::Days)
+}
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum QueryError {
+  #[error("Missing value for '{0}'")]
+  MissingValue(String),
+
+  #[error("Invalid value '{1}' for '{0}'")]
+  InvalidValue(String, String),
+
+  #[error("'{0}' can not be compared with <, <=, > or >=")]
+  UnsupportedComparison(String),
+
+  #[error("Unterminated quote")]
+  UnterminatedQuote,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::{edit, sample, titles};
+
+  fn today() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 2, 20).unwrap_or_default()
+  }
+
+  fn filter(query: &str) -> Result<Vec<String>, QueryError> {
+    let file = sample();
+    let scope = HyphaQueryScope::new(&file, today());
+    Ok(titles(&file, &HyphaQuery::parse(query)?.filter(&scope)))
+  }
+
+  fn term(negated: bool, filter: HyphaQueryFilter) -> HyphaQueryTerm {
+    HyphaQueryTerm { negated, filter }
+  }
+
+  #[test]
+  fn parses_terms() -> Result<(), QueryError> {
+    let query = HyphaQuery::parse(
+      r#"  Fix -tag:BUG list:"in progress" due<7d "tag:ui" blocked:no "#,
+    )?;
+    assert_eq!(
+      query.terms,
+      [
+        term(false, HyphaQueryFilter::Text("fix".to_string())),
+        term(true, HyphaQueryFilter::Tag("bug".to_string())),
+        term(false, HyphaQueryFilter::List("in progress".to_string())),
+        term(
+          false,
+          HyphaQueryFilter::Due(
+            HyphaQueryOperator::Less,
+            HyphaQueryDate::Days(7)
+          )
+        ),
+        term(false, HyphaQueryFilter::Text("tag:ui".to_string())),
+        term(false, HyphaQueryFilter::Blocked(false)),
+      ]
+    );
+    assert_eq!(
+      query.to_string(),
+      r#"Fix -tag:BUG list:"in progress" due<7d "tag:ui" blocked:no"#
+    );
+    assert!(HyphaQuery::parse(" ")?.is_empty());
+    Ok(())
+  }
+
+  #[test]
+  fn parses_dates() {
+    let date = NaiveDate::from_ymd_opt(2025, 1, 31);
+    assert_eq!(parse_date("today"), Some(HyphaQueryDate::Days(0)));
+    assert_eq!(parse_date("2w"), Some(HyphaQueryDate::Days(14)));
+    assert_eq!(parse_date("2025-01-31"), date.map(HyphaQueryDate::Date));
+    assert_eq!(parse_date("soon"), None);
+  }
+
+  #[test]
+  fn falls_back_to_text_for_unknown_fields() -> Result<(), QueryError> {
+    let query = HyphaQuery::parse("https://example.com -Foo:Bar x<1")?;
+    assert_eq!(
+      query.terms,
+      [
+        term(
+          false,
+          HyphaQueryFilter::Text("https://example.com".to_string())
+        ),
+        term(true, HyphaQueryFilter::Text("foo:bar".to_string())),
+        term(false, HyphaQueryFilter::Text("x<1".to_string())),
+      ]
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn rejects_invalid_terms() {
+    let error = |query: &str| HyphaQuery::parse(query).err();
+    let invalid = |field: &str, value: &str| {
+      Some(QueryError::InvalidValue(
+        field.to_string(),
+        value.to_string(),
+      ))
+    };
+    assert_eq!(error(r#"list:"todo"#), Some(QueryError::UnterminatedQuote));
+    assert_eq!(
+      error("due:"),
+      Some(QueryError::MissingValue("due".to_string()))
+    );
+    assert_eq!(error("priority:huge"), invalid("priority", "huge"));
+    assert_eq!(error("due<soon"), invalid("due", "soon"));
+    assert_eq!(error("blocked:maybe"), invalid("blocked", "maybe"));
+    assert_eq!(error("has:colour"), invalid("has", "colour"));
+    assert_eq!(
+      error("tag>bug"),
+      Some(QueryError::UnsupportedComparison("tag".to_string()))
+    );
+  }
+
+  #[test]
+  fn filters_issues() -> Result<(), QueryError> {
+    assert_eq!(filter("")?, ["a", "b", "c", "d"]);
+    assert_eq!(filter("due<7d")?, Vec::<String>::new());
+    assert_eq!(filter("due<=8d")?, ["a"]);
+    assert_eq!(filter("due>2025-02-01 priority>=high")?, ["a"]);
+    assert_eq!(filter("blocked:false")?, ["a", "c", "d"]);
+    assert_eq!(filter("blocked:true")?, ["b"]);
+    assert_eq!(filter("blocking:yes")?, ["a"]);
+    assert_eq!(filter("done:true")?, ["d"]);
+    assert_eq!(filter("board:work list:DOING")?, ["c"]);
+    assert_eq!(filter("has:estimate estimate<3")?, ["a"]);
+    assert_eq!(filter("assignee:sam created<2025-01-03")?, ["a"]);
+    Ok(())
+  }
+
+  #[test]
+  fn negates_terms() -> Result<(), QueryError> {
+    assert_eq!(filter("-tag:bug")?, ["b", "c", "d"]);
+    assert_eq!(filter("-done:true -has:tags")?, ["b", "c"]);
+    assert_eq!(filter("-ui")?, ["b", "c", "d"]);
+    Ok(())
+  }
+
+  #[test]
+  fn matches_quoted_phrases() -> Result<(), QueryError> {
+    assert_eq!(filter("one line")?, ["a"]);
+    assert_eq!(filter(r#""line one""#)?, ["a"]);
+    assert_eq!(filter(r#""one line""#)?, Vec::<String>::new());
+    assert_eq!(filter(r#""tag:bug""#)?, Vec::<String>::new());
+    Ok(())
+  }
+
+  #[test]
+  fn matches_unknown_fields_as_text() -> Result<(), QueryError> {
+    let mut file = sample();
+    edit(&mut file, "c", |issue| {
+      issue.description = "See https://example.com/c".to_string();
+    });
+    let scope = HyphaQueryScope::new(&file, today());
+    let query = HyphaQuery::parse("https://example.com")?;
+    assert_eq!(titles(&file, &query.filter(&scope)), ["c"]);
+    assert_eq!(filter("foo:bar")?, Vec::<String>::new());
+    Ok(())
+  }
+}
@@ -0,0 +1,164 @@
+use chrono::{DateTime, Utc};
+use directories::ProjectDirs;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use std::{
+  fs, io,
+  path::{Path, PathBuf},
+};
+use tokio::task;
+
+use crate::{
+  file::FileError,
+  storage::{replace, write_temp},
+};
+
+const RECENT_COUNT: usize = 10;
+
+/// Hypha files opened lately, most recent first.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct HyphaRecent {
+  #[serde(default)]
+  pub files: Vec<HyphaRecentFile>,
+  #[serde(skip)]
+  pub path: PathBuf,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HyphaRecentFile {
+  pub path: PathBuf,
+  pub opened: DateTime<Utc>,
+}
+
+impl HyphaRecent {
+  pub fn path() -> Result<PathBuf, FileError> {
+    if let Some(proj_dirs) = ProjectDirs::from("com", "HyphaApp", "Hypha") {
+      Ok(proj_dirs.config_dir().join("recent.toml"))
+    } else {
+      error!("Could not determine project directory for recent files.");
+      Err(FileError::Directory)
+    }
+  }
+
+  pub fn load(path: &Path) -> Result<HyphaRecent, FileError> {
+    let mut recent = match fs::read_to_string(path) {
+      Ok(content) => toml::from_str::<HyphaRecent>(&content)?,
+      Err(e) if e.kind() == io::ErrorKind::NotFound => HyphaRecent::default(),
+      Err(e) => return Err(FileError::Io(e)),
+    };
+    recent.path = path.to_path_buf();
+    Ok(recent)
+  }
+
+  pub async fn load_async(path: &Path) -> Result<HyphaRecent, FileError> {
+    let path = path.to_path_buf();
+    task::spawn_blocking(move || Self::load(&path)).await?
+  }
+
+  pub fn save(&self) -> Result<(), FileError> {
+    let path = self.path.as_path();
+    if let Some(parent_dir) = path.parent() {
+      fs::create_dir_all(parent_dir)?;
+    }
+    let content = toml::to_string_pretty(self)?;
+    let temp_path = write_temp(path, content.as_bytes())?;
+    replace(&temp_path, path)?;
+    info!("Recent files saved successfully to: {}", path.display());
+    Ok(())
+  }
+
+  pub async fn save_async(&self) -> Result<(), FileError> {
+    let recent = self.clone();
+    task::spawn_blocking(move || recent.save()).await?
+  }
+
+  /// Moves `path` to the front, dropping the oldest files past the limit.
+  pub fn open(&mut self, path: &Path) {
+    self.files.retain(|file| file.path != path);
+    self.files.insert(
+      0,
+      HyphaRecentFile {
+        path: path.to_path_buf(),
+        opened: Utc::now(),
+      },
+    );
+    self.files.truncate(RECENT_COUNT);
+  }
+
+  pub fn remove(&mut self, path: &Path) -> bool {
+    let count = self.files.len();
+    self.files.retain(|file| file.path != path);
+    self.files.len() != count
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::temp_dir;
+
+  fn paths(recent: &HyphaRecent) -> Vec<&Path> {
+    recent
+      .files
+      .iter()
+      .map(|file| file.path.as_path())
+      .collect()
+  }
+
+  #[test]
+  fn keeps_most_recent_first() {
+    let mut recent = HyphaRecent::default();
+    recent.open(Path::new("a"));
+    recent.open(Path::new("b"));
+    recent.open(Path::new("c"));
+    assert_eq!(
+      paths(&recent),
+      [Path::new("c"), Path::new("b"), Path::new("a")]
+    );
+  }
+
+  #[test]
+  fn moves_reopened_files_to_front() {
+    let mut recent = HyphaRecent::default();
+    recent.open(Path::new("a"));
+    recent.open(Path::new("b"));
+    recent.open(Path::new("a"));
+    assert_eq!(paths(&recent), [Path::new("a"), Path::new("b")]);
+  }
+
+  #[test]
+  fn drops_oldest_past_limit() {
+    let mut recent = HyphaRecent::default();
+    for index in 0..RECENT_COUNT.saturating_add(2) {
+      recent.open(&PathBuf::from(index.to_string()));
+    }
+    assert_eq!(recent.files.len(), RECENT_COUNT);
+    assert_eq!(paths(&recent).first(), Some(&Path::new("11")));
+    assert_eq!(paths(&recent).last(), Some(&Path::new("2")));
+  }
+
+  #[test]
+  fn removes_files() {
+    let mut recent = HyphaRecent::default();
+    recent.open(Path::new("a"));
+    recent.open(Path::new("b"));
+    assert!(recent.remove(Path::new("a")));
+    assert!(!recent.remove(Path::new("a")));
+    assert_eq!(paths(&recent), [Path::new("b")]);
+  }
+
+  #[test]
+  fn round_trips_recent_files() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = temp_dir()?;
+    let path = dir.join("recent.toml");
+    assert_eq!(HyphaRecent::load(&path)?.files, []);
+
+    let mut recent = HyphaRecent::load(&path)?;
+    recent.open(Path::new("a"));
+    recent.open(Path::new("b"));
+    recent.save()?;
+    assert_eq!(HyphaRecent::load(&path)?, recent);
+    fs::remove_dir_all(dir)?;
+    Ok(())
+  }
+}
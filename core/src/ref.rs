@@ -1,7 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 use crate::{
-  board::HyphaBoard, container::HyphaContainer, file::HyphaFile,
+  board::HyphaBoard, container::HyphaContainer, file::HyphaFile, id::HyphaId,
   issue::HyphaIssue, item::HyphaItem, list::HyphaList,
 };
 
@@ -29,8 +29,8 @@ pub struct WithHyphaRef<Item: HyphaItem, Ref: HyphaRef> {
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct HyphaBoardIssueRef {
-  pub issue: String,
-  pub list: String,
+  pub issue: HyphaId,
+  pub list: HyphaId,
   pub stage: usize,
 }
 
@@ -45,10 +45,8 @@ impl HyphaRef for HyphaBoardIssueRef {
     container
       .lists
       .iter()
-      .find(|list| list.title == self.list)
-      .and_then(|list| {
-        list.issues.iter().find(|issue| issue.title == self.issue)
-      })
+      .find(|list| list.id == self.list)
+      .and_then(|list| list.issues.iter().find(|issue| issue.id == self.issue))
   }
 
   fn get_item_from_container_mut<'a>(
@@ -58,12 +56,9 @@ impl HyphaRef for HyphaBoardIssueRef {
     container
       .lists
       .iter_mut()
-      .find(|list| list.title == self.list)
+      .find(|list| list.id == self.list)
       .and_then(|list| {
-        list
-          .issues
-          .iter_mut()
-          .find(|issue| issue.title == self.issue)
+        list.issues.iter_mut().find(|issue| issue.id == self.issue)
       })
   }
 
@@ -71,20 +66,17 @@ impl HyphaRef for HyphaBoardIssueRef {
     &self,
     container: &mut Self::Container,
   ) -> bool {
-    let list = match container
-      .lists
-      .iter_mut()
-      .find(|list| list.title == self.list)
-    {
-      Some(list) => list,
-      None => return false,
-    };
+    let list =
+      match container.lists.iter_mut().find(|list| list.id == self.list) {
+        Some(list) => list,
+        None => return false,
+      };
 
     if let Some((idx, _)) = list
       .issues
       .iter()
       .enumerate()
-      .find(|(_, issue)| issue.title == self.issue)
+      .find(|(_, issue)| issue.id == self.issue)
     {
       list.issues.remove(idx);
       return true;
@@ -96,10 +88,10 @@ impl HyphaRef for HyphaBoardIssueRef {
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct HyphaFileIssueRef {
-  pub issue: String,
-  pub list: String,
+  pub issue: HyphaId,
+  pub list: HyphaId,
   pub stage: usize,
-  pub board: String,
+  pub board: HyphaId,
 }
 
 impl HyphaRef for HyphaFileIssueRef {
@@ -113,15 +105,13 @@ impl HyphaRef for HyphaFileIssueRef {
     container
       .boards
       .iter()
-      .find(|board| board.title == self.board)
+      .find(|board| board.id == self.board)
       .and_then(|board| {
         board
           .lists
           .iter()
-          .find(|list| list.title == self.list)
-          .map(|list| {
-            list.issues.iter().find(|issue| issue.title == self.issue)
-          })
+          .find(|list| list.id == self.list)
+          .map(|list| list.issues.iter().find(|issue| issue.id == self.issue))
       })
       .flatten()
   }
@@ -133,22 +123,14 @@ impl HyphaRef for HyphaFileIssueRef {
     container
       .boards
       .iter_mut()
-      .find(|board| board.title == self.board)
+      .find(|board| board.id == self.board)
       .and_then(|board| {
         board
           .lists
           .iter_mut()
-          .find(|list| list.title == self.list)
+          .find(|list| list.id == self.list)
           .and_then(|list| {
-            if list.issues.iter().any(|issue| issue.title == self.issue) {
-              list
-                .issues
-                .iter_mut()
-                .find(|issue| issue.title == self.issue)
-            } else {
-              list.issues.push(HyphaIssue::default());
-              list.issues.last_mut()
-            }
+            list.issues.iter_mut().find(|issue| issue.id == self.issue)
           })
       })
   }
@@ -160,14 +142,13 @@ impl HyphaRef for HyphaFileIssueRef {
     let board = match container
       .boards
       .iter_mut()
-      .find(|board| board.title == self.board)
+      .find(|board| board.id == self.board)
     {
       Some(board) => board,
       None => return false,
     };
 
-    let list = match board.lists.iter_mut().find(|list| list.title == self.list)
-    {
+    let list = match board.lists.iter_mut().find(|list| list.id == self.list) {
       Some(list) => list,
       None => return false,
     };
@@ -176,7 +157,7 @@ impl HyphaRef for HyphaFileIssueRef {
       .issues
       .iter()
       .enumerate()
-      .find(|(_, issue)| issue.title == self.issue)
+      .find(|(_, issue)| issue.id == self.issue)
     {
       list.issues.remove(idx);
       return true;
@@ -188,9 +169,9 @@ impl HyphaRef for HyphaFileIssueRef {
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct HyphaFileListRef {
-  pub list: String,
+  pub list: HyphaId,
   pub stage: usize,
-  pub board: String,
+  pub board: HyphaId,
 }
 
 impl HyphaRef for HyphaFileListRef {
@@ -204,8 +185,8 @@ impl HyphaRef for HyphaFileListRef {
     container
       .boards
       .iter()
-      .find(|board| board.title == self.board)
-      .and_then(|board| board.lists.get(self.stage))
+      .find(|board| board.id == self.board)
+      .and_then(|board| board.lists.iter().find(|list| list.id == self.list))
   }
 
   fn get_item_from_container_mut<'a>(
@@ -215,8 +196,10 @@ impl HyphaRef for HyphaFileListRef {
     container
       .boards
       .iter_mut()
-      .find(|board| board.title == self.board)
-      .and_then(|board| board.lists.get_mut(self.stage))
+      .find(|board| board.id == self.board)
+      .and_then(|board| {
+        board.lists.iter_mut().find(|list| list.id == self.list)
+      })
   }
 
   fn remove_item_from_container(
@@ -226,7 +209,7 @@ impl HyphaRef for HyphaFileListRef {
     let board = match container
       .boards
       .iter_mut()
-      .find(|board| board.title == self.board)
+      .find(|board| board.id == self.board)
     {
       Some(board) => board,
       None => return false,
@@ -236,7 +219,7 @@ impl HyphaRef for HyphaFileListRef {
       .lists
       .iter()
       .enumerate()
-      .find(|(_, list)| list.title == self.list)
+      .find(|(_, list)| list.id == self.list)
     {
       board.lists.remove(idx);
       return true;
@@ -248,7 +231,7 @@ impl HyphaRef for HyphaFileListRef {
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 pub struct HyphaFileBoardRef {
-  pub board: String,
+  pub board: HyphaId,
 }
 
 impl HyphaRef for HyphaFileBoardRef {
@@ -259,10 +242,7 @@ impl HyphaRef for HyphaFileBoardRef {
     &self,
     container: &'a Self::Container,
   ) -> Option<&'a Self::Item> {
-    container
-      .boards
-      .iter()
-      .find(|board| board.title == self.board)
+    container.boards.iter().find(|board| board.id == self.board)
   }
 
   fn get_item_from_container_mut<'a>(
@@ -272,7 +252,7 @@ impl HyphaRef for HyphaFileBoardRef {
     container
       .boards
       .iter_mut()
-      .find(|board| board.title == self.board)
+      .find(|board| board.id == self.board)
   }
 
   fn remove_item_from_container(
@@ -283,7 +263,7 @@ impl HyphaRef for HyphaFileBoardRef {
       .boards
       .iter()
       .enumerate()
-      .find(|(_, board)| board.title == self.board)
+      .find(|(_, board)| board.id == self.board)
     {
       container.boards.remove(idx);
       return true;
@@ -0,0 +1,380 @@
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+use crate::{
+  file::HyphaFile,
+  id::HyphaId,
+  issue::HyphaIssue,
+  r#ref::{HyphaFileBoardRef, HyphaFileIssueRef, HyphaFileListRef, HyphaRef},
+};
+
+/// Full-text index over the boards, lists and issues of a file.
+///
+/// Boards and lists are indexed by title and issues by title, description and
+/// tags. Every query word has to be the start of an indexed word so results
+/// show up while typing.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct HyphaSearchIndex {
+  entries: HashMap<HyphaId, HyphaSearchEntry>,
+  terms: BTreeMap<String, BTreeSet<HyphaId>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HyphaSearchTarget {
+  Board(HyphaFileBoardRef),
+  List(HyphaFileListRef),
+  Issue(HyphaFileIssueRef),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HyphaSearchHit {
+  pub target: HyphaSearchTarget,
+  pub title: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct HyphaSearchEntry {
+  target: HyphaSearchTarget,
+  title: String,
+  text: String,
+}
+
+impl HyphaSearchTarget {
+  pub fn board(&self) -> HyphaFileBoardRef {
+    let board = match self {
+      HyphaSearchTarget::Board(board_ref) => &board_ref.board,
+      HyphaSearchTarget::List(list_ref) => &list_ref.board,
+      HyphaSearchTarget::Issue(issue_ref) => &issue_ref.board,
+    };
+    HyphaFileBoardRef {
+      board: board.clone(),
+    }
+  }
+
+  pub fn issue(&self) -> Option<HyphaFileIssueRef> {
+    match self {
+      HyphaSearchTarget::Issue(issue_ref) => Some(issue_ref.clone()),
+      _ => None,
+    }
+  }
+
+  fn id(&self) -> &HyphaId {
+    match self {
+      HyphaSearchTarget::Board(board_ref) => &board_ref.board,
+      HyphaSearchTarget::List(list_ref) => &list_ref.list,
+      HyphaSearchTarget::Issue(issue_ref) => &issue_ref.issue,
+    }
+  }
+
+  // Title and searchable text of the target or none when it is gone.
+  fn document(&self, file: &HyphaFile) -> Option<(String, String)> {
+    match self {
+      HyphaSearchTarget::Board(board_ref) => {
+        let board = board_ref.get_item_from_container(file)?;
+        Some((board.title.clone(), board.title.clone()))
+      }
+      HyphaSearchTarget::List(list_ref) => {
+        let list = list_ref.get_item_from_container(file)?;
+        Some((list.title.clone(), list.title.clone()))
+      }
+      HyphaSearchTarget::Issue(issue_ref) => {
+        let issue = issue_ref.get_item_from_container(file)?;
+        Some((issue.title.clone(), issue_text(issue)))
+      }
+    }
+  }
+}
+
+impl HyphaSearchIndex {
+  pub fn new(file: &HyphaFile) -> Self {
+    let mut index = Self::default();
+    index.sync(file);
+    index
+  }
+
+  /// Reindexes the item with `id` after it was edited in place, falling back
+  /// to [`HyphaSearchIndex::sync`] when it moved or is not indexed yet.
+  pub fn update(&mut self, file: &HyphaFile, id: &HyphaId) {
+    let target = match self.entries.get(id) {
+      Some(entry) => entry.target.clone(),
+      None => {
+        self.sync(file);
+        return;
+      }
+    };
+    match target.document(file) {
+      Some((title, text)) => self.insert(target, title, text),
+      None => self.sync(file),
+    }
+  }
+
+  /// Brings the index in line with `file`, only reindexing the text of items
+  /// that were added or changed and dropping the removed ones.
+  pub fn sync(&mut self, file: &HyphaFile) {
+    let mut seen = BTreeSet::new();
+    for (target, title, text) in documents(file) {
+      seen.insert(target.id().clone());
+      match self.entries.get_mut(target.id()) {
+        Some(entry) if entry.text == text => {
+          entry.target = target;
+          entry.title = title;
+        }
+        _ => self.insert(target, title, text),
+      }
+    }
+
+    let removed = self
+      .entries
+      .keys()
+      .filter(|id| !seen.contains(*id))
+      .cloned()
+      .collect::<Vec<_>>();
+    for id in removed {
+      self.remove(&id);
+    }
+  }
+
+  /// Items matching every word of `query`, best matches first.
+  ///
+  /// Items where more query words match whole words rank higher, then boards
+  /// before lists before issues and then by title.
+  pub fn search(&self, query: &str) -> Vec<HyphaSearchHit> {
+    let words = tokenize(query);
+    if words.is_empty() {
+      return Vec::new();
+    }
+
+    let mut scores = HashMap::<&HyphaId, usize>::new();
+    for (idx, word) in words.iter().enumerate() {
+      let mut matches = HashMap::<&HyphaId, usize>::new();
+      for (term, ids) in self
+        .terms
+        .range::<String, _>(word..)
+        .take_while(|(term, _)| term.starts_with(word.as_str()))
+      {
+        let exact = usize::from(term == word);
+        for id in ids {
+          let score = matches.entry(id).or_default();
+          *score = (*score).max(exact);
+        }
+      }
+      scores = if idx == 0 {
+        matches
+      } else {
+        scores
+          .into_iter()
+          .filter_map(|(id, score)| {
+            matches
+              .get(id)
+              .map(|exact| (id, score.saturating_add(*exact)))
+          })
+          .collect()
+      };
+    }
+
+    let mut hits = scores
+      .into_iter()
+      .filter_map(|(id, score)| {
+        self.entries.get(id).map(|entry| (score, entry))
+      })
+      .collect::<Vec<_>>();
+    hits.sort_by(|(left_score, left), (right_score, right)| {
+      right_score
+        .cmp(left_score)
+        .then_with(|| kind(&left.target).cmp(&kind(&right.target)))
+        .then_with(|| left.title.cmp(&right.title))
+    });
+    hits
+      .into_iter()
+      .map(|(_, entry)| HyphaSearchHit {
+        target: entry.target.clone(),
+        title: entry.title.clone(),
+      })
+      .collect()
+  }
+
+  fn insert(&mut self, target: HyphaSearchTarget, title: String, text: String) {
+    let id = target.id().clone();
+    self.remove(&id);
+    for term in tokenize(&text) {
+      self.terms.entry(term).or_default().insert(id.clone());
+    }
+    self.entries.insert(
+      id,
+      HyphaSearchEntry {
+        target,
+        title,
+        text,
+      },
+    );
+  }
+
+  fn remove(&mut self, id: &HyphaId) {
+    let Some(entry) = self.entries.remove(id) else {
+      return;
+    };
+    for term in tokenize(&entry.text) {
+      if let Some(ids) = self.terms.get_mut(&term) {
+        ids.remove(id);
+        if ids.is_empty() {
+          self.terms.remove(&term);
+        }
+      }
+    }
+  }
+}
+
+fn documents(file: &HyphaFile) -> Vec<(HyphaSearchTarget, String, String)> {
+  let mut documents = Vec::new();
+  for board in file.boards.iter() {
+    documents.push((
+      HyphaSearchTarget::Board(HyphaFileBoardRef {
+        board: board.id.clone(),
+      }),
+      board.title.clone(),
+      board.title.clone(),
+    ));
+    for (stage, list) in board.lists.iter().enumerate() {
+      documents.push((
+        HyphaSearchTarget::List(HyphaFileListRef {
+          list: list.id.clone(),
+          stage,
+          board: board.id.clone(),
+        }),
+        list.title.clone(),
+        list.title.clone(),
+      ));
+      for issue in list.issues.iter() {
+        documents.push((
+          HyphaSearchTarget::Issue(HyphaFileIssueRef {
+            issue: issue.id.clone(),
+            list: list.id.clone(),
+            stage,
+            board: board.id.clone(),
+          }),
+          issue.title.clone(),
+          issue_text(issue),
+        ));
+      }
+    }
+  }
+  documents
+}
+
+fn issue_text(issue: &HyphaIssue) -> String {
+  let mut text = vec![issue.title.as_str(), issue.description.as_str()];
+  text.extend(issue.tags.iter().map(String::as_str));
+  text.join("\n")
+}
+
+fn kind(target: &HyphaSearchTarget) -> u8 {
+  match target {
+    HyphaSearchTarget::Board(_) => 0,
+    HyphaSearchTarget::List(_) => 1,
+    HyphaSearchTarget::Issue(_) => 2,
+  }
+}
+
+fn tokenize(text: &str) -> BTreeSet<String> {
+  text
+    .split(|c: char| !c.is_alphanumeric())
+    .filter(|word| !word.is_empty())
+    .map(str::to_lowercase)
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::{edit, issue_ref, list_ref, work};
+
+  fn titles(hits: &[HyphaSearchHit]) -> Vec<&str> {
+    hits.iter().map(|hit| hit.title.as_str()).collect()
+  }
+
+  fn id(file: &HyphaFile, title: &str) -> HyphaId {
+    issue_ref(file, title).issue
+  }
+
+  #[test]
+  fn finds_nothing_for_empty_query() {
+    let index = HyphaSearchIndex::new(&work());
+    assert_eq!(index.search(""), []);
+    assert_eq!(index.search(" - "), []);
+  }
+
+  #[test]
+  fn ranks_whole_words_then_kind_then_title() {
+    let mut file = work();
+    edit(&mut file, "a", |issue| {
+      issue.title = "Todo later".to_string()
+    });
+    edit(&mut file, "b", |issue| issue.title = "Tod".to_string());
+    edit(&mut file, "c", |issue| issue.title = "Todo now".to_string());
+    let index = HyphaSearchIndex::new(&file);
+
+    assert_eq!(
+      titles(&index.search("todo")),
+      ["Todo", "Todo later", "Todo now"]
+    );
+    assert_eq!(
+      titles(&index.search("tod")),
+      ["Tod", "Todo", "Todo later", "Todo now"]
+    );
+    assert_eq!(titles(&index.search("todo NOW")), ["Todo now"]);
+  }
+
+  #[test]
+  fn searches_descriptions_and_tags() {
+    let mut file = work();
+    edit(&mut file, "a", |issue| {
+      issue.description = "Fix the login form".to_string();
+      issue.tags = vec!["frontend".to_string()];
+    });
+    let index = HyphaSearchIndex::new(&file);
+    assert_eq!(titles(&index.search("login")), ["a"]);
+    assert_eq!(titles(&index.search("front")), ["a"]);
+    assert_eq!(
+      index.search("login").first().map(|hit| hit.target.clone()),
+      Some(HyphaSearchTarget::Issue(issue_ref(&file, "a")))
+    );
+  }
+
+  #[test]
+  fn reindexes_renamed_items() {
+    let mut file = work();
+    let mut index = HyphaSearchIndex::new(&file);
+    edit(&mut file, "c", |issue| issue.title = "Release".to_string());
+    index.update(&file, &id(&file, "Release"));
+    assert_eq!(titles(&index.search("release")), ["Release"]);
+    assert_eq!(index.search("c"), []);
+    assert_eq!(index, HyphaSearchIndex::new(&file));
+  }
+
+  #[test]
+  fn drops_removed_and_follows_moved_items() {
+    let mut file = work();
+    let mut index = HyphaSearchIndex::new(&file);
+    file.remove_issue(issue_ref(&file, "a"));
+    file.move_issue(issue_ref(&file, "b"), list_ref(&file, "Done"), 0);
+    index.sync(&file);
+    assert_eq!(index.search("a"), []);
+    assert_eq!(
+      index.search("b").first().map(|hit| hit.target.clone()),
+      Some(HyphaSearchTarget::Issue(issue_ref(&file, "b")))
+    );
+    assert_eq!(index, HyphaSearchIndex::new(&file));
+  }
+
+  #[test]
+  fn syncs_when_updating_unknown_items() {
+    let mut file = work();
+    let mut index = HyphaSearchIndex::new(&file);
+    let added = file.add_issue(list_ref(&file, "Todo"));
+    if let Some(added) = added {
+      edit(&mut file, "", |issue| issue.title = "New".to_string());
+      index.update(&file, &added.issue);
+    }
+    assert_eq!(titles(&index.search("new")), ["New"]);
+    assert_eq!(index, HyphaSearchIndex::new(&file));
+  }
+}
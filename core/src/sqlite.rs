@@ -0,0 +1,727 @@
+use log::{info, warn};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::Value;
+use std::{
+  collections::{HashMap, HashSet},
+  fs,
+  path::Path,
+};
+
+use crate::{
+  board::HyphaBoard,
+  changes::HyphaChanges,
+  file::{FileError, HyphaFile, VERSION},
+  id::HyphaId,
+  issue::HyphaIssue,
+  list::HyphaList,
+  storage::{link_deps, HyphaStorage},
+  validate::HyphaReport,
+};
+
+const EXTENSIONS: [&str; 3] = ["db", "sqlite", "sqlite3"];
+
+// Boards, lists and issues each get a row keyed by id with the id of the
+// item holding them, their position in it and the rest of their fields as
+// JSON so new fields need no schema changes. Deps are pairs of issue ids and
+// are placed again on load.
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS meta (
+  key TEXT PRIMARY KEY,
+  value TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS boards (
+  id TEXT PRIMARY KEY,
+  parent TEXT NOT NULL,
+  position INTEGER NOT NULL,
+  data TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS lists (
+  id TEXT PRIMARY KEY,
+  parent TEXT NOT NULL,
+  position INTEGER NOT NULL,
+  data TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS issues (
+  id TEXT PRIMARY KEY,
+  parent TEXT NOT NULL,
+  position INTEGER NOT NULL,
+  data TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS deps (
+  blocker TEXT NOT NULL,
+  blocked TEXT NOT NULL,
+  PRIMARY KEY (blocker, blocked)
+);
+";
+
+/// Whether `path` is stored as a SQLite database.
+pub fn is_database(path: &Path) -> bool {
+  path
+    .extension()
+    .and_then(|extension| extension.to_str())
+    .is_some_and(|extension| {
+      EXTENSIONS.contains(&extension.to_ascii_lowercase().as_str())
+    })
+}
+
+/// The file stored in a SQLite database.
+///
+/// Saving writes only the rows of the items marked in the file's changes since
+/// its last save, so large files stay cheap to save after every edit. The
+/// first save of a session compares every row instead.
+pub struct HyphaSqliteStorage;
+
+impl HyphaStorage for HyphaSqliteStorage {
+  fn load(&self, path: &Path) -> Result<(HyphaFile, HyphaReport), FileError> {
+    if !path.exists() {
+      warn!(
+        "Hypha database not found at {}, creating default.",
+        path.display()
+      );
+      let file = HyphaFile {
+        path: path.to_path_buf(),
+        ..HyphaFile::default()
+      };
+      return Ok((file, HyphaReport::default()));
+    }
+
+    let connection = open(path)?;
+    Ok(load(&connection, path)?.check(""))
+  }
+
+  fn save(&self, file: &HyphaFile) -> Result<(), FileError> {
+    if let Some(parent_dir) = file.path.parent() {
+      fs::create_dir_all(parent_dir)?;
+    }
+
+    let mut connection = open(&file.path)?;
+    let transaction = connection.transaction()?;
+    save(&transaction, file)?;
+    transaction.commit()?;
+    Ok(())
+  }
+}
+
+#[derive(Debug, PartialEq)]
+struct Row {
+  id: String,
+  parent: String,
+  position: i64,
+  data: String,
+}
+
+impl Row {
+  fn new<T: Serialize>(
+    id: &HyphaId,
+    parent: &str,
+    position: usize,
+    item: &T,
+    children: &[&str],
+  ) -> Result<Self, FileError> {
+    let mut value = serde_json::to_value(item)?;
+    if let Value::Object(fields) = &mut value {
+      for child in children {
+        fields.remove(*child);
+      }
+    }
+    Ok(Self {
+      id: id.to_string(),
+      parent: parent.to_string(),
+      position: i64::try_from(position).unwrap_or(i64::MAX),
+      data: serde_json::to_string(&value)?,
+    })
+  }
+}
+
+fn open(path: &Path) -> Result<Connection, FileError> {
+  let connection = Connection::open(path)?;
+  connection.execute_batch(SCHEMA)?;
+  Ok(connection)
+}
+
+fn load(connection: &Connection, path: &Path) -> Result<HyphaFile, FileError> {
+  if let Some(version) = meta(connection, "version")? {
+    let version = version
+      .parse::<u32>()
+      .map_err(|_| FileError::InvalidVersion(version.clone()))?;
+    if version > VERSION {
+      return Err(FileError::UnsupportedVersion(version, VERSION));
+    }
+  }
+  let preferences = meta(connection, "preferences")?
+    .map(|preferences| serde_json::from_str(&preferences))
+    .transpose()?
+    .unwrap_or_default();
+  let views = meta(connection, "views")?
+    .map(|views| serde_json::from_str(&views))
+    .transpose()?
+    .unwrap_or_default();
+
+  let mut boards = items::<HyphaBoard>(connection, "boards")?
+    .into_iter()
+    .map(|(_, board)| board)
+    .collect::<Vec<_>>();
+  let board_positions = boards
+    .iter()
+    .enumerate()
+    .map(|(position, board)| (board.id.clone(), position))
+    .collect::<HashMap<_, _>>();
+
+  let mut list_positions = HashMap::new();
+  for (parent, list) in items::<HyphaList>(connection, "lists")? {
+    let board = board_positions.get(&parent).and_then(|position| {
+      boards.get_mut(*position).map(|board| (*position, board))
+    });
+    match board {
+      Some((position, board)) => {
+        list_positions.insert(list.id.clone(), (position, board.lists.len()));
+        board.lists.push(list);
+      }
+      None => warn!("Dropping list {} of missing board {parent}", list.id),
+    }
+  }
+
+  for (parent, issue) in items::<HyphaIssue>(connection, "issues")? {
+    let list = list_positions
+      .get(&parent)
+      .and_then(|(board, list)| boards.get_mut(*board)?.lists.get_mut(*list));
+    match list {
+      Some(list) => list.issues.push(issue),
+      None => warn!("Dropping issue {} of missing list {parent}", issue.id),
+    }
+  }
+
+  let mut file = HyphaFile {
+    version: VERSION,
+    boards,
+    deps: Vec::new(),
+    preferences,
+    views,
+    path: path.to_path_buf(),
+    changes: HyphaChanges::default(),
+  };
+  let mut statement =
+    connection.prepare("SELECT blocker, blocked FROM deps ORDER BY rowid")?;
+  let deps = statement
+    .query_map([], |row| {
+      Ok((
+        HyphaId::from(row.get::<_, String>(0)?),
+        HyphaId::from(row.get::<_, String>(1)?),
+      ))
+    })?
+    .collect::<Result<Vec<_>, _>>()?;
+  link_deps(&mut file, deps);
+  Ok(file)
+}
+
+// Items of `table` in order along with the id of the item holding them.
+fn items<T: DeserializeOwned>(
+  connection: &Connection,
+  table: &str,
+) -> Result<Vec<(HyphaId, T)>, FileError> {
+  let mut statement = connection.prepare(&format!(
+    "SELECT parent, data FROM {table} ORDER BY position"
+  ))?;
+  let rows = statement
+    .query_map([], |row| {
+      Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+    })?
+    .collect::<Result<Vec<_>, _>>()?;
+  rows
+    .into_iter()
+    .map(|(parent, data)| {
+      Ok((HyphaId::from(parent), serde_json::from_str(&data)?))
+    })
+    .collect()
+}
+
+fn save(connection: &Connection, file: &HyphaFile) -> Result<(), FileError> {
+  let changes = &file.changes;
+  let since = match meta(connection, "session")? {
+    Some(session) if session == changes.session.as_str() => {
+      meta(connection, "revision")?.and_then(|revision| revision.parse().ok())
+    }
+    _ => None,
+  };
+  let saved = match since {
+    Some(since) => save_changes(connection, file, since)?,
+    None => false,
+  };
+  if !saved {
+    save_all(connection, file)?;
+  }
+  set_meta(connection, "session", changes.session.as_str())?;
+  set_meta(connection, "revision", &changes.revision.to_string())
+}
+
+fn save_meta(
+  connection: &Connection,
+  file: &HyphaFile,
+) -> Result<(), FileError> {
+  set_meta(connection, "version", &file.version.to_string())?;
+  set_meta(
+    connection,
+    "preferences",
+    &serde_json::to_string(&file.preferences)?,
+  )?;
+  set_meta(connection, "views", &serde_json::to_string(&file.views)?)
+}
+
+fn board_row(position: usize, board: &HyphaBoard) -> Result<Row, FileError> {
+  Row::new(&board.id, "", position, board, &["lists", "deps"])
+}
+
+fn list_row(
+  board: &HyphaBoard,
+  position: usize,
+  list: &HyphaList,
+) -> Result<Row, FileError> {
+  Row::new(&list.id, board.id.as_str(), position, list, &["issues"])
+}
+
+fn issue_row(
+  list: &HyphaList,
+  position: usize,
+  issue: &HyphaIssue,
+) -> Result<Row, FileError> {
+  Row::new(&issue.id, list.id.as_str(), position, issue, &[])
+}
+
+// Writes the items marked after revision `since`, or returns false without
+// writing them when one is not where its mark says so the whole file is
+// compared instead.
+fn save_changes(
+  connection: &Connection,
+  file: &HyphaFile,
+  since: u64,
+) -> Result<bool, FileError> {
+  let changes = &file.changes;
+  let find_board = |id: &HyphaId| {
+    file
+      .boards
+      .iter()
+      .enumerate()
+      .find(|(_, board)| board.id == *id)
+  };
+
+  let mut boards = Vec::new();
+  let mut removed_boards = Vec::new();
+  for (id, change) in changes.boards.iter() {
+    if change.revision <= since {
+      continue;
+    }
+    if change.parent.is_none() {
+      removed_boards.push(id);
+      continue;
+    }
+    match find_board(id) {
+      Some((position, board)) => boards.push(board_row(position, board)?),
+      None => return Ok(false),
+    }
+  }
+
+  let mut lists = Vec::new();
+  let mut removed_lists = Vec::new();
+  for (id, change) in changes.lists.iter() {
+    if change.revision <= since {
+      continue;
+    }
+    let Some(board_id) = &change.parent else {
+      removed_lists.push(id);
+      continue;
+    };
+    let list = find_board(board_id).and_then(|(_, board)| {
+      let (position, list) = board
+        .lists
+        .iter()
+        .enumerate()
+        .find(|(_, list)| list.id == *id)?;
+      Some((board, position, list))
+    });
+    match list {
+      Some((board, position, list)) => {
+        lists.push(list_row(board, position, list)?)
+      }
+      None => return Ok(false),
+    }
+  }
+
+  let mut issues = Vec::new();
+  let mut removed_issues = Vec::new();
+  for (id, change) in changes.issues.iter() {
+    if change.revision <= since {
+      continue;
+    }
+    let Some((board_id, list_id)) = &change.parent else {
+      removed_issues.push(id);
+      continue;
+    };
+    let issue = find_board(board_id).and_then(|(_, board)| {
+      let list = board.lists.iter().find(|list| list.id == *list_id)?;
+      let (position, issue) = list
+        .issues
+        .iter()
+        .enumerate()
+        .find(|(_, issue)| issue.id == *id)?;
+      Some((list, position, issue))
+    });
+    match issue {
+      Some((list, position, issue)) => {
+        issues.push(issue_row(list, position, issue)?)
+      }
+      None => return Ok(false),
+    }
+  }
+
+  if changes.meta > since {
+    save_meta(connection, file)?;
+  }
+  for (table, rows, removed) in [
+    ("boards", boards, removed_boards),
+    ("lists", lists, removed_lists),
+    ("issues", issues, removed_issues),
+  ] {
+    for row in rows.iter() {
+      upsert(connection, table, row)?;
+    }
+    for id in removed.iter() {
+      delete(connection, table, id.as_str())?;
+    }
+    info!(
+      "Wrote {} and removed {} changed {table}",
+      rows.len(),
+      removed.len()
+    );
+  }
+
+  for ((blocker, blocked), change) in changes.deps.iter() {
+    if change.revision <= since {
+      continue;
+    }
+    let statement = match change.parent {
+      Some(()) => {
+        "INSERT OR IGNORE INTO deps (blocker, blocked) VALUES (?1, ?2)"
+      }
+      None => "DELETE FROM deps WHERE blocker = ?1 AND blocked = ?2",
+    };
+    connection
+      .prepare_cached(statement)?
+      .execute([blocker.as_str(), blocked.as_str()])?;
+  }
+  Ok(true)
+}
+
+// Compares every item with the stored rows.
+fn save_all(
+  connection: &Connection,
+  file: &HyphaFile,
+) -> Result<(), FileError> {
+  save_meta(connection, file)?;
+
+  let mut boards = Vec::new();
+  let mut lists = Vec::new();
+  let mut issues = Vec::new();
+  for (board_position, board) in file.boards.iter().enumerate() {
+    boards.push(board_row(board_position, board)?);
+    for (list_position, list) in board.lists.iter().enumerate() {
+      lists.push(list_row(board, list_position, list)?);
+      for (issue_position, issue) in list.issues.iter().enumerate() {
+        issues.push(issue_row(list, issue_position, issue)?);
+      }
+    }
+  }
+  sync(connection, "boards", &boards)?;
+  sync(connection, "lists", &lists)?;
+  sync(connection, "issues", &issues)?;
+
+  let deps = file
+    .deps
+    .iter()
+    .chain(file.boards.iter().flat_map(|board| board.deps.iter()))
+    .map(|dep| (dep.left.issue.to_string(), dep.right.issue.to_string()))
+    .collect::<HashSet<_>>();
+  sync_deps(connection, &deps)
+}
+
+fn upsert(
+  connection: &Connection,
+  table: &str,
+  row: &Row,
+) -> Result<(), FileError> {
+  connection
+    .prepare_cached(&format!(
+      "INSERT INTO {table} (id, parent, position, data)
+      VALUES (?1, ?2, ?3, ?4)
+      ON CONFLICT (id) DO UPDATE SET
+        parent = excluded.parent,
+        position = excluded.position,
+        data = excluded.data"
+    ))?
+    .execute(params![row.id, row.parent, row.position, row.data])?;
+  Ok(())
+}
+
+fn delete(
+  connection: &Connection,
+  table: &str,
+  id: &str,
+) -> Result<(), FileError> {
+  connection
+    .prepare_cached(&format!("DELETE FROM {table} WHERE id = ?1"))?
+    .execute([id])?;
+  Ok(())
+}
+
+// Writes rows that differ from the stored ones and deletes rows that are
+// gone.
+fn sync(
+  connection: &Connection,
+  table: &str,
+  rows: &[Row],
+) -> Result<(), FileError> {
+  let mut statement = connection
+    .prepare(&format!("SELECT id, parent, position, data FROM {table}"))?;
+  let existing = statement
+    .query_map([], |row| {
+      Ok(Row {
+        id: row.get(0)?,
+        parent: row.get(1)?,
+        position: row.get(2)?,
+        data: row.get(3)?,
+      })
+    })?
+    .map(|row| row.map(|row| (row.id.clone(), row)))
+    .collect::<Result<HashMap<_, _>, _>>()?;
+
+  let mut updated = 0_usize;
+  for row in rows.iter() {
+    if existing.get(&row.id) != Some(row) {
+      upsert(connection, table, row)?;
+      updated = updated.saturating_add(1);
+    }
+  }
+
+  let kept = rows.iter().map(|row| &row.id).collect::<HashSet<_>>();
+  let mut removed = 0_usize;
+  for id in existing.keys().filter(|id| !kept.contains(id)) {
+    delete(connection, table, id)?;
+    removed = removed.saturating_add(1);
+  }
+
+  if updated > 0 || removed > 0 {
+    info!("Updated {updated} and removed {removed} {table}");
+  }
+  Ok(())
+}
+
+fn sync_deps(
+  connection: &Connection,
+  deps: &HashSet<(String, String)>,
+) -> Result<(), FileError> {
+  let mut statement =
+    connection.prepare("SELECT blocker, blocked FROM deps")?;
+  let existing = statement
+    .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+    .collect::<Result<HashSet<(String, String)>, _>>()?;
+
+  let mut insert = connection
+    .prepare("INSERT INTO deps (blocker, blocked) VALUES (?1, ?2)")?;
+  for (blocker, blocked) in deps.difference(&existing) {
+    insert.execute([blocker, blocked])?;
+  }
+  let mut delete = connection
+    .prepare("DELETE FROM deps WHERE blocker = ?1 AND blocked = ?2")?;
+  for (blocker, blocked) in existing.difference(deps) {
+    delete.execute([blocker, blocked])?;
+  }
+  Ok(())
+}
+
+fn meta(
+  connection: &Connection,
+  key: &str,
+) -> Result<Option<String>, FileError> {
+  Ok(
+    connection
+      .query_row("SELECT value FROM meta WHERE key = ?1", [key], |row| {
+        row.get(0)
+      })
+      .optional()?,
+  )
+}
+
+fn set_meta(
+  connection: &Connection,
+  key: &str,
+  value: &str,
+) -> Result<(), FileError> {
+  if meta(connection, key)?.as_deref() == Some(value) {
+    return Ok(());
+  }
+  connection.execute(
+    "INSERT INTO meta (key, value) VALUES (?1, ?2)
+    ON CONFLICT (key) DO UPDATE SET value = excluded.value",
+    [key, value],
+  )?;
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    r#ref::{HyphaRef, WithHyphaRef},
+    testing::{board_ref, edit, issue_ref, list_ref, sample, temp_dir},
+  };
+
+  fn saved(
+    connection: &Connection,
+    file: &HyphaFile,
+  ) -> Result<u64, FileError> {
+    let before = connection.total_changes();
+    save(connection, file)?;
+    Ok(connection.total_changes().saturating_sub(before))
+  }
+
+  fn rename(file: &mut HyphaFile, title: &str, to: &str) {
+    let issue_ref = issue_ref(file, title);
+    if let Some(issue) = issue_ref.get_item_from_container(file).cloned() {
+      file.update_issue(WithHyphaRef {
+        item: HyphaIssue {
+          title: to.to_string(),
+          ..issue
+        },
+        r#ref: issue_ref,
+      });
+    }
+  }
+
+  fn database(dir: &Path) -> HyphaFile {
+    HyphaFile {
+      path: dir.join("hypha.db"),
+      ..sample()
+    }
+  }
+
+  #[test]
+  fn round_trips_database() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = temp_dir()?;
+    let file = database(&dir);
+    HyphaSqliteStorage.save(&file)?;
+    let (loaded, report) = HyphaSqliteStorage.load(&file.path)?;
+    assert_eq!(report.problems, []);
+    assert_eq!(loaded, file);
+    fs::remove_dir_all(dir)?;
+    Ok(())
+  }
+
+  #[test]
+  fn saves_only_marked_rows() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = temp_dir()?;
+    let mut file = database(&dir);
+    let connection = open(&file.path)?;
+    assert!(saved(&connection, &file)? > 0);
+    assert_eq!(saved(&connection, &file)?, 0);
+
+    // The issue and the revision of the save.
+    rename(&mut file, "c", "e");
+    assert_eq!(saved(&connection, &file)?, 2);
+
+    // Removing b also removes its dep on a.
+    file.remove_issue(issue_ref(&file, "b"));
+    file.add_issue(list_ref(&file, "Done"));
+    assert_eq!(saved(&connection, &file)?, 4);
+    assert_eq!(load(&connection, &file.path)?, file);
+    fs::remove_dir_all(dir)?;
+    Ok(())
+  }
+
+  #[test]
+  fn saves_moves_and_removals() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = temp_dir()?;
+    let mut file = database(&dir);
+    let connection = open(&file.path)?;
+    save(&connection, &file)?;
+
+    file.move_issue(issue_ref(&file, "a"), list_ref(&file, "Done"), 0);
+    file.move_list(list_ref(&file, "Done"), 0);
+    file.remove_list(list_ref(&file, "Doing"));
+    let home = file.add_board("Home".to_string());
+    file.add_list(home.clone(), "Todo".to_string());
+    if let Some(board) = home.get_item_from_container(&file).cloned() {
+      file.update_board(WithHyphaRef {
+        item: HyphaBoard {
+          title: "House".to_string(),
+          ..board
+        },
+        r#ref: home,
+      });
+    }
+    save(&connection, &file)?;
+    assert_eq!(load(&connection, &file.path)?, file);
+
+    file.remove_board(board_ref(&file, "Work"));
+    save(&connection, &file)?;
+    assert_eq!(load(&connection, &file.path)?, file);
+    fs::remove_dir_all(dir)?;
+    Ok(())
+  }
+
+  #[test]
+  fn compares_rows_after_reset() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = temp_dir()?;
+    let mut file = database(&dir);
+    let connection = open(&file.path)?;
+    save(&connection, &file)?;
+
+    edit(&mut file, "c", |issue| issue.title = "e".to_string());
+    file.changes.reset();
+    // The issue and the new session.
+    assert_eq!(saved(&connection, &file)?, 2);
+    assert_eq!(load(&connection, &file.path)?, file);
+    fs::remove_dir_all(dir)?;
+    Ok(())
+  }
+
+  #[test]
+  fn compares_rows_when_marks_are_stale(
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = temp_dir()?;
+    let mut file = database(&dir);
+    let connection = open(&file.path)?;
+    save(&connection, &file)?;
+
+    // c is marked in Doing but then moved without marking it again.
+    rename(&mut file, "c", "e");
+    if let Some(board) = file.boards.first_mut() {
+      let issues = board.lists.get_mut(1).map(|list| list.issues.split_off(0));
+      if let Some(list) = board.lists.get_mut(2) {
+        list.issues.extend(issues.into_iter().flatten());
+      }
+    }
+    save(&connection, &file)?;
+    assert_eq!(load(&connection, &file.path)?, file);
+    fs::remove_dir_all(dir)?;
+    Ok(())
+  }
+
+  #[test]
+  fn compares_rows_after_another_session(
+  ) -> Result<(), Box<dyn std::error::Error>> {
+    let dir = temp_dir()?;
+    let mut file = database(&dir);
+    let connection = open(&file.path)?;
+    save(&connection, &file)?;
+
+    let mut other = load(&connection, &file.path)?;
+    rename(&mut other, "a", "f");
+    save(&connection, &other)?;
+
+    rename(&mut file, "c", "e");
+    save(&connection, &file)?;
+    assert_eq!(load(&connection, &file.path)?, file);
+    fs::remove_dir_all(dir)?;
+    Ok(())
+  }
+}
@@ -0,0 +1,211 @@
+use log::{error, info, warn};
+use std::{
+  fs,
+  io::{self, Write},
+  path::{Path, PathBuf},
+};
+
+use crate::{
+  dep::HyphaDep,
+  file::{temp_path, FileError, HyphaFile},
+  format::HyphaFormat,
+  graph::HyphaDepGraph,
+  id::HyphaId,
+  markdown::{self, HyphaMarkdownStorage},
+  sqlite::{self, HyphaSqliteStorage},
+  validate::HyphaReport,
+};
+
+/// Backend a hypha file is loaded from and saved to.
+///
+/// Backends are blocking and run on a blocking thread when used from async
+/// code through `HyphaFile::load_validated_async` and
+/// `HyphaFile::save_async`.
+pub trait HyphaStorage: Send + Sync {
+  /// Loads the file at `path` along with the problems found in it, or a
+  /// default file when nothing is stored there yet.
+  fn load(&self, path: &Path) -> Result<(HyphaFile, HyphaReport), FileError>;
+
+  /// Stores `file` at its path.
+  fn save(&self, file: &HyphaFile) -> Result<(), FileError>;
+}
+
+/// Picks the backend for `path` from its extension.
+pub fn storage(path: &Path) -> Box<dyn HyphaStorage> {
+  if markdown::is_directory(path) {
+    Box::new(HyphaMarkdownStorage)
+  } else if sqlite::is_database(path) {
+    Box::new(HyphaSqliteStorage)
+  } else {
+    Box::new(HyphaTextStorage(HyphaFormat::from_path(path)))
+  }
+}
+
+/// The whole file serialized into a single document with backups kept next
+/// to it.
+pub struct HyphaTextStorage(pub HyphaFormat);
+
+impl HyphaStorage for HyphaTextStorage {
+  fn load(&self, path: &Path) -> Result<(HyphaFile, HyphaReport), FileError> {
+    match fs::read_to_string(path) {
+      Ok(content) => HyphaFile::parse(&content, path),
+      Err(e) if e.kind() == io::ErrorKind::NotFound => {
+        warn!(
+          "Hypha file not found at {}, creating default.",
+          path.display()
+        );
+        let file = HyphaFile {
+          path: path.to_path_buf(),
+          ..HyphaFile::default()
+        };
+        Ok((file, HyphaReport::default()))
+      }
+      Err(e) => {
+        error!("Failed to read hypha file: {e}");
+        Err(FileError::Io(e))
+      }
+    }
+  }
+
+  fn save(&self, file: &HyphaFile) -> Result<(), FileError> {
+    let path = file.path.as_path();
+    if let Some(parent_dir) = path.parent() {
+      fs::create_dir_all(parent_dir)?;
+      info!("Ensured hypha directory exists: {}", parent_dir.display());
+    }
+
+    let content = self.0.serialize(file)?;
+    let temp_path = write_temp(path, content.as_bytes())?;
+    if let Err(e) = HyphaFile::backup(path, false) {
+      remove_temp(&temp_path);
+      return Err(e);
+    }
+    replace(&temp_path, path)
+  }
+}
+
+/// Creates a new temp file next to `path` holding `content` synced to disk.
+pub(crate) fn write_temp(
+  path: &Path,
+  content: &[u8],
+) -> Result<PathBuf, FileError> {
+  let temp_path = temp_path(path);
+  let mut temp = fs::OpenOptions::new()
+    .write(true)
+    .create_new(true)
+    .open(&temp_path)
+    .map_err(|e| FileError::Temp(temp_path.clone(), e))?;
+  let written = temp
+    .write_all(content)
+    .map_err(|e| FileError::Temp(temp_path.clone(), e))
+    .and_then(|()| {
+      temp
+        .sync_all()
+        .map_err(|e| FileError::Sync(temp_path.clone(), e))
+    });
+  if let Err(e) = written {
+    remove_temp(&temp_path);
+    return Err(e);
+  }
+  Ok(temp_path)
+}
+
+/// Moves a temp file from [`write_temp`] over `path` and syncs the directory
+/// so the rename itself survives a crash.
+pub(crate) fn replace(temp_path: &Path, path: &Path) -> Result<(), FileError> {
+  if let Err(e) = fs::rename(temp_path, path) {
+    remove_temp(temp_path);
+    return Err(FileError::Rename(path.to_path_buf(), e));
+  }
+  sync_dir(path)
+}
+
+#[cfg(unix)]
+fn sync_dir(path: &Path) -> Result<(), FileError> {
+  let dir = match path.parent() {
+    Some(dir) if !dir.as_os_str().is_empty() => dir,
+    _ => Path::new("."),
+  };
+  fs::File::open(dir)
+    .and_then(|dir| dir.sync_all())
+    .map_err(|e| FileError::Sync(dir.to_path_buf(), e))
+}
+
+// Directories can not be opened as files to sync them elsewhere.
+#[cfg(not(unix))]
+fn sync_dir(_path: &Path) -> Result<(), FileError> {
+  Ok(())
+}
+
+fn remove_temp(temp_path: &Path) {
+  if let Err(e) = fs::remove_file(temp_path) {
+    warn!("Failed to remove temp file {}: {e}", temp_path.display());
+  }
+}
+
+/// Adds deps stored as pairs of blocking and blocked issue ids, keeping
+/// those within one board on the board and dropping those on missing issues.
+pub(crate) fn link_deps(
+  file: &mut HyphaFile,
+  blockers: impl IntoIterator<Item = (HyphaId, HyphaId)>,
+) {
+  let graph = HyphaDepGraph::new(file);
+  let deps = blockers
+    .into_iter()
+    .filter_map(|(left, right)| {
+      match (graph.issue(&left), graph.issue(&right)) {
+        (Some(left), Some(right)) => Some(HyphaDep {
+          left: left.clone(),
+          right: right.clone(),
+        }),
+        _ => {
+          warn!("Dropping dep of issue {right} on missing issue {left}");
+          None
+        }
+      }
+    })
+    .collect::<Vec<_>>();
+  for dep in deps {
+    let board = file
+      .boards
+      .iter_mut()
+      .filter(|_| dep.left.board == dep.right.board)
+      .find(|board| board.id == dep.left.board);
+    match board {
+      Some(board) => board.deps.push(dep),
+      None => file.deps.push(dep),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{file::temp_path, testing::temp_dir};
+
+  #[test]
+  fn temp_paths_are_unique() {
+    let path = Path::new("/tmp/hypha.toml");
+    assert_ne!(temp_path(path), temp_path(path));
+    assert_eq!(temp_path(path).parent(), path.parent());
+  }
+
+  #[test]
+  fn replaces_file_without_leftovers() -> Result<(), Box<dyn std::error::Error>>
+  {
+    let dir = temp_dir()?;
+    let path = dir.join("hypha.toml");
+    fs::write(&path, "old")?;
+
+    let first = write_temp(&path, b"first")?;
+    let second = write_temp(&path, b"second")?;
+    replace(&first, &path)?;
+    assert_eq!(fs::read_to_string(&path)?, "first");
+    replace(&second, &path)?;
+    assert_eq!(fs::read_to_string(&path)?, "second");
+
+    assert_eq!(fs::read_dir(&dir)?.count(), 1);
+    fs::remove_dir_all(dir)?;
+    Ok(())
+  }
+}
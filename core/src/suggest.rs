@@ -0,0 +1,369 @@
+use chrono::NaiveDate;
+use log::debug;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::{
+  file::HyphaFile,
+  graph::HyphaDepGraph,
+  id::HyphaId,
+  issue::is_valid_estimate,
+  r#ref::{HyphaFileIssueRef, HyphaRef},
+  workspace::{HyphaWorkspace, HyphaWorkspaceIssueRef},
+};
+
+/// Suggests unblocked issues to focus on today, scored by how many issues they
+/// unblock, their priority, due date and age, up to the daily capacity.
+pub fn suggest(file: &HyphaFile, today: NaiveDate) -> Vec<HyphaFileIssueRef> {
+  debug!("Computing suggestions for {today}");
+
+  let graph = HyphaDepGraph::new(file);
+
+  let done_stages = file
+    .boards
+    .iter()
+    .filter_map(|board| {
+      board
+        .lists
+        .len()
+        .checked_sub(1)
+        .map(|stage| (board.id.clone(), stage))
+    })
+    .collect::<HashMap<_, _>>();
+  let is_done = |issue_ref: &HyphaFileIssueRef| {
+    done_stages.get(&issue_ref.board) == Some(&issue_ref.stage)
+  };
+
+  let is_unblocked = |issue_ref: &HyphaFileIssueRef| {
+    graph
+      .prerequisites(&issue_ref.issue)
+      .iter()
+      .filter_map(|id| graph.issue(id))
+      .all(|prerequisite| {
+        is_done(prerequisite)
+          || (prerequisite.board == issue_ref.board
+            && prerequisite.stage > issue_ref.stage)
+      })
+  };
+
+  let unblocks = |issue_ref: &HyphaFileIssueRef| {
+    let mut seen = HashSet::<HyphaId>::new();
+    let mut queue = VecDeque::from([issue_ref.issue.clone()]);
+    while let Some(current) = queue.pop_front() {
+      for dependent in graph.dependents(&current) {
+        let pending = graph
+          .issue(dependent)
+          .is_some_and(|dependent| !is_done(dependent));
+        if pending && seen.insert(dependent.clone()) {
+          queue.push_back(dependent.clone());
+        }
+      }
+    }
+    seen.len()
+  };
+
+  let preferences = file
+    .boards
+    .iter()
+    .map(|board| (board.id.clone(), file.preferences.with(&board.preferences)))
+    .collect::<HashMap<_, _>>();
+  let preferences = |issue_ref: &HyphaFileIssueRef| {
+    preferences
+      .get(&issue_ref.board)
+      .unwrap_or(&file.preferences)
+  };
+
+  let score = |issue_ref: &HyphaFileIssueRef| {
+    let preferences = preferences(issue_ref);
+    let mut score = preferences.unblocking * unblocks(issue_ref) as f64;
+    let Some(issue) = issue_ref.get_item_from_container(file) else {
+      return score;
+    };
+    if let Some(priority) = issue.priority {
+      score += preferences.priority * priority.weight();
+    }
+    if let Some(due) = issue.due {
+      let days = due.signed_duration_since(today).num_days().max(0);
+      score += preferences.due / (days as f64 + 1.0);
+    }
+    if let Some(created) = issue.created {
+      let days = today
+        .signed_duration_since(created.date_naive())
+        .num_days()
+        .max(0);
+      score += preferences.age * days as f64;
+    }
+    score
+  };
+  let cost = |issue_ref: &HyphaFileIssueRef| {
+    issue_ref
+      .get_item_from_container(file)
+      .and_then(|issue| issue.estimate)
+      .filter(|estimate| is_valid_estimate(*estimate))
+      .unwrap_or(1.0)
+  };
+
+  let mut suggestions = graph
+    .issues()
+    .filter(|issue_ref| !is_done(issue_ref) && is_unblocked(issue_ref))
+    .map(|issue_ref| (score(issue_ref), issue_ref.clone()))
+    .collect::<Vec<_>>();
+  suggestions.sort_by(|(left_score, left), (right_score, right)| {
+    right_score
+      .total_cmp(left_score)
+      .then(right.stage.cmp(&left.stage))
+  });
+
+  let mut used = 0.0;
+  let mut used_by_board = HashMap::<HyphaId, f64>::new();
+  let mut picked = Vec::new();
+  for (_, issue_ref) in suggestions {
+    let capacity = preferences(&issue_ref)
+      .capacity
+      .min(file.preferences.capacity);
+    let cost = cost(&issue_ref).min(capacity);
+    let board_used = used_by_board.entry(issue_ref.board.clone()).or_default();
+    if used + cost > file.preferences.capacity
+      || *board_used + cost > preferences(&issue_ref).capacity
+    {
+      continue;
+    }
+    used += cost;
+    *board_used += cost;
+    picked.push(issue_ref);
+  }
+  picked
+}
+
+/// Suggests issues to focus on today across every loaded file of the
+/// workspace.
+///
+/// Works like `suggest` on all files merged into one, so deps across files
+/// block issues too and the workspace capacity is shared by all files.
+pub fn suggest_workspace(
+  workspace: &HyphaWorkspace,
+  today: NaiveDate,
+) -> Vec<HyphaWorkspaceIssueRef> {
+  let files = workspace
+    .files
+    .iter()
+    .filter_map(|file| Some((file.id.clone(), file.loaded.as_ref()?)))
+    .flat_map(|(id, loaded)| {
+      loaded
+        .boards
+        .iter()
+        .map(move |board| (board.id.clone(), id.clone()))
+    })
+    .collect::<HashMap<_, _>>();
+
+  suggest(&workspace.combined(), today)
+    .into_iter()
+    .filter_map(|issue_ref| {
+      let file = files.get(&issue_ref.board)?.clone();
+      Some(HyphaWorkspaceIssueRef::new(file, issue_ref))
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::{TimeZone, Utc};
+
+  use super::*;
+  use crate::{
+    issue::HyphaPriority,
+    preferences::{HyphaPreferences, HyphaPreferencesOverride},
+    testing::{board, edit, file, link, list, titles, work},
+  };
+
+  fn today() -> NaiveDate {
+    NaiveDate::from_ymd_opt(2025, 1, 10).unwrap_or_default()
+  }
+
+  fn weights(priority: f64, age: f64, due: f64) -> HyphaPreferences {
+    HyphaPreferences {
+      priority,
+      age,
+      due,
+      unblocking: 0.0,
+      ..Default::default()
+    }
+  }
+
+  #[test]
+  fn ranks_by_priority_weight() {
+    let mut file = work();
+    file.preferences = weights(1.0, 0.0, 0.0);
+    edit(&mut file, "a", |issue| {
+      issue.priority = Some(HyphaPriority::Low)
+    });
+    edit(&mut file, "b", |issue| {
+      issue.priority = Some(HyphaPriority::Urgent)
+    });
+
+    assert_eq!(titles(&file, &suggest(&file, today())), ["b", "a", "c"]);
+  }
+
+  #[test]
+  fn ranks_ties_by_later_stage() {
+    let mut file = work();
+    file.preferences = weights(0.0, 0.0, 0.0);
+    edit(&mut file, "b", |issue| {
+      issue.priority = Some(HyphaPriority::Urgent)
+    });
+
+    assert_eq!(titles(&file, &suggest(&file, today())), ["c", "a", "b"]);
+  }
+
+  #[test]
+  fn ranks_by_due_weight() {
+    let mut file = work();
+    file.preferences = weights(0.0, 0.0, 1.0);
+    edit(&mut file, "a", |issue| {
+      issue.due = NaiveDate::from_ymd_opt(2025, 1, 20)
+    });
+    edit(&mut file, "b", |issue| {
+      issue.due = NaiveDate::from_ymd_opt(2025, 1, 11)
+    });
+
+    assert_eq!(titles(&file, &suggest(&file, today())), ["b", "a", "c"]);
+  }
+
+  #[test]
+  fn ranks_by_age_weight() {
+    let mut file = work();
+    file.preferences = weights(0.0, 1.0, 0.0);
+    edit(&mut file, "a", |issue| {
+      issue.created = Utc.with_ymd_and_hms(2025, 1, 9, 12, 0, 0).single()
+    });
+    edit(&mut file, "b", |issue| {
+      issue.created = Utc.with_ymd_and_hms(2024, 12, 31, 12, 0, 0).single()
+    });
+
+    assert_eq!(titles(&file, &suggest(&file, today())), ["b", "a", "c"]);
+  }
+
+  #[test]
+  fn ranks_with_board_overrides() {
+    let mut file = file(vec![
+      board("Home", vec![list("Todo", &["a"]), list("Done", &[])]),
+      board("Work", vec![list("Todo", &["b"]), list("Done", &[])]),
+    ]);
+    file.preferences = weights(1.0, 0.0, 0.0);
+    file.boards[0].preferences = HyphaPreferencesOverride {
+      priority: Some(10.0),
+      ..Default::default()
+    };
+    edit(&mut file, "a", |issue| {
+      issue.priority = Some(HyphaPriority::Low)
+    });
+    edit(&mut file, "b", |issue| {
+      issue.priority = Some(HyphaPriority::Urgent)
+    });
+
+    assert_eq!(titles(&file, &suggest(&file, today())), ["a", "b"]);
+  }
+
+  #[test]
+  fn picks_within_capacity() {
+    let mut file = work();
+    file.preferences = HyphaPreferences {
+      capacity: 3.0,
+      ..weights(1.0, 0.0, 0.0)
+    };
+    edit(&mut file, "a", |issue| {
+      issue.priority = Some(HyphaPriority::Urgent);
+      issue.estimate = Some(2.0);
+    });
+    edit(&mut file, "b", |issue| {
+      issue.priority = Some(HyphaPriority::High);
+      issue.estimate = Some(2.0);
+    });
+
+    assert_eq!(titles(&file, &suggest(&file, today())), ["a", "c"]);
+  }
+
+  #[test]
+  fn ignores_invalid_estimates() {
+    let mut file = work();
+    file.preferences = HyphaPreferences {
+      capacity: 2.0,
+      ..weights(1.0, 0.0, 0.0)
+    };
+    edit(&mut file, "a", |issue| {
+      issue.priority = Some(HyphaPriority::Urgent);
+      issue.estimate = Some(-5.0);
+    });
+    edit(&mut file, "b", |issue| {
+      issue.priority = Some(HyphaPriority::High);
+      issue.estimate = Some(f64::NAN);
+    });
+
+    assert_eq!(titles(&file, &suggest(&file, today())), ["a", "b"]);
+  }
+
+  #[test]
+  fn picks_oversized_issue_alone_when_ranked_first() {
+    let mut file = work();
+    file.preferences = weights(1.0, 0.0, 0.0);
+    edit(&mut file, "a", |issue| {
+      issue.priority = Some(HyphaPriority::Urgent);
+      issue.estimate = Some(8.0);
+    });
+
+    assert_eq!(titles(&file, &suggest(&file, today())), ["a"]);
+  }
+
+  #[test]
+  fn skips_oversized_issue_after_others() {
+    let mut file = work();
+    file.preferences = weights(1.0, 0.0, 0.0);
+    edit(&mut file, "a", |issue| {
+      issue.priority = Some(HyphaPriority::High)
+    });
+    edit(&mut file, "b", |issue| issue.estimate = Some(8.0));
+
+    assert_eq!(titles(&file, &suggest(&file, today())), ["a", "c"]);
+  }
+
+  #[test]
+  fn picks_within_board_capacity() {
+    let mut file = work();
+    file.preferences = weights(0.0, 0.0, 0.0);
+    file.boards[0].preferences = HyphaPreferencesOverride {
+      capacity: Some(2.0),
+      ..Default::default()
+    };
+
+    assert_eq!(titles(&file, &suggest(&file, today())), ["c", "a"]);
+  }
+
+  #[test]
+  fn skips_done_and_blocked_issues() {
+    let mut file = work();
+    file.preferences = weights(0.0, 0.0, 0.0);
+    link(&mut file, &[("a", "b")]);
+
+    assert_eq!(titles(&file, &suggest(&file, today())), ["c", "a"]);
+  }
+
+  #[test]
+  fn unblocks_issues_behind_done_or_later_prerequisites() {
+    let mut file = work();
+    file.preferences = weights(0.0, 0.0, 0.0);
+    link(&mut file, &[("d", "a"), ("c", "b")]);
+
+    assert_eq!(titles(&file, &suggest(&file, today())), ["c", "a", "b"]);
+  }
+
+  #[test]
+  fn ranks_by_unblocked_issues() {
+    let mut file = work();
+    file.preferences = HyphaPreferences {
+      unblocking: 1.0,
+      ..weights(0.0, 0.0, 0.0)
+    };
+    link(&mut file, &[("b", "c")]);
+
+    assert_eq!(titles(&file, &suggest(&file, today())), ["b", "a"]);
+  }
+}
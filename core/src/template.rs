@@ -0,0 +1,192 @@
+use log::info;
+use std::path::Path;
+use tokio::task;
+
+use crate::{
+  board::HyphaBoard,
+  changes::HyphaChanges,
+  file::{FileError, HyphaFile, VERSION},
+  list::HyphaList,
+  preferences::HyphaPreferences,
+};
+
+/// Starting point for a new hypha file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HyphaTemplate {
+  Example,
+  Kanban,
+  Project,
+  Personal,
+}
+
+impl HyphaTemplate {
+  pub const ALL: [HyphaTemplate; 4] = [
+    HyphaTemplate::Example,
+    HyphaTemplate::Kanban,
+    HyphaTemplate::Project,
+    HyphaTemplate::Personal,
+  ];
+
+  pub fn as_str(self) -> &'static str {
+    match self {
+      HyphaTemplate::Example => "example",
+      HyphaTemplate::Kanban => "kanban",
+      HyphaTemplate::Project => "project",
+      HyphaTemplate::Personal => "personal",
+    }
+  }
+
+  pub fn parse(value: &str) -> Option<Self> {
+    Self::ALL
+      .into_iter()
+      .find(|template| template.as_str() == value)
+  }
+
+  pub fn label(self) -> &'static str {
+    match self {
+      HyphaTemplate::Example => "Example board with generated issues",
+      HyphaTemplate::Kanban => "To do, in progress and done",
+      HyphaTemplate::Project => "Backlog through review to done",
+      HyphaTemplate::Personal => "Someday, this week and today",
+    }
+  }
+
+  fn lists(self) -> &'static [&'static str] {
+    match self {
+      HyphaTemplate::Example => &[],
+      HyphaTemplate::Kanban => &["To do", "In progress", "Done"],
+      HyphaTemplate::Project => {
+        &["Backlog", "To do", "In progress", "Review", "Done"]
+      }
+      HyphaTemplate::Personal => &["Someday", "This week", "Today", "Done"],
+    }
+  }
+
+  /// A new file at `path` built from the template.
+  pub fn file(self, path: &Path) -> HyphaFile {
+    if self == HyphaTemplate::Example {
+      return HyphaFile {
+        path: path.to_path_buf(),
+        ..HyphaFile::default()
+      };
+    }
+
+    let mut board = HyphaBoard::new("Board".to_string());
+    board.lists = self
+      .lists()
+      .iter()
+      .map(|title| HyphaList::new(title.to_string()))
+      .collect();
+    HyphaFile {
+      version: VERSION,
+      boards: vec![board],
+      deps: Vec::new(),
+      preferences: HyphaPreferences::default(),
+      views: Vec::new(),
+      path: path.to_path_buf(),
+      changes: HyphaChanges::default(),
+    }
+  }
+
+  /// Creates and saves a new file at `path`, refusing to replace anything
+  /// already there.
+  pub fn create(self, path: &Path) -> Result<HyphaFile, FileError> {
+    if path.exists() {
+      return Err(FileError::Exists(path.to_path_buf()));
+    }
+    let file = self.file(path);
+    file.save()?;
+    info!(
+      "Created hypha file {} from the {} template",
+      path.display(),
+      self.as_str()
+    );
+    Ok(file)
+  }
+
+  pub async fn create_async(self, path: &Path) -> Result<HyphaFile, FileError> {
+    let path = path.to_path_buf();
+    task::spawn_blocking(move || self.create(&path)).await?
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::temp_dir;
+  use std::{collections::HashSet, fs};
+
+  fn shape(file: &HyphaFile) -> Vec<(&str, Vec<&str>)> {
+    file
+      .boards
+      .iter()
+      .map(|board| {
+        let lists = board.lists.iter().map(|list| list.title.as_str());
+        (board.title.as_str(), lists.collect())
+      })
+      .collect()
+  }
+
+  #[test]
+  fn builds_empty_lists_for_each_template() {
+    let path = Path::new("hypha.toml");
+    let shapes = [
+      (HyphaTemplate::Kanban, vec!["To do", "In progress", "Done"]),
+      (
+        HyphaTemplate::Project,
+        vec!["Backlog", "To do", "In progress", "Review", "Done"],
+      ),
+      (
+        HyphaTemplate::Personal,
+        vec!["Someday", "This week", "Today", "Done"],
+      ),
+    ];
+    for (template, lists) in shapes {
+      let file = template.file(path);
+      assert_eq!(shape(&file), [("Board", lists)]);
+      assert_eq!(file.path, path);
+      assert!(file.deps.is_empty());
+      assert!(file
+        .boards
+        .iter()
+        .flat_map(|board| board.lists.iter())
+        .all(|list| list.issues.is_empty()));
+      let ids = file
+        .boards
+        .iter()
+        .flat_map(|board| board.lists.iter())
+        .map(|list| &list.id)
+        .collect::<HashSet<_>>();
+      assert_eq!(ids.len(), template.lists().len());
+    }
+  }
+
+  #[test]
+  fn builds_example_board() {
+    let file = HyphaTemplate::Example.file(Path::new("hypha.toml"));
+    assert_eq!(file.boards.len(), 1);
+    assert!(file.boards.iter().all(|board| !board.lists.is_empty()));
+  }
+
+  #[test]
+  fn parses_every_template_name() {
+    for template in HyphaTemplate::ALL {
+      assert_eq!(HyphaTemplate::parse(template.as_str()), Some(template));
+    }
+    assert_eq!(HyphaTemplate::parse("other"), None);
+  }
+
+  #[test]
+  fn creates_new_files_only() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = temp_dir()?;
+    let path = dir.join("hypha.toml");
+    let created = HyphaTemplate::Kanban.create(&path)?;
+    assert_eq!(HyphaFile::load(&path)?, created);
+    assert!(matches!(
+      HyphaTemplate::Personal.create(&path),
+      Err(FileError::Exists(_))
+    ));
+    fs::remove_dir_all(dir)?;
+    Ok(())
+  }
+}
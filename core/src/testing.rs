@@ -0,0 +1,228 @@
+//! Small files built by hand for the unit tests.
+
+use chrono::{NaiveDate, TimeZone, Utc};
+use std::{fs, io, path::PathBuf};
+use uuid::Uuid;
+
+use crate::{
+  board::HyphaBoard,
+  changes::HyphaChanges,
+  dep::HyphaDep,
+  file::{HyphaFile, VERSION},
+  id::HyphaId,
+  issue::{HyphaIssue, HyphaPriority},
+  list::HyphaList,
+  preferences::{HyphaPreferences, HyphaPreferencesOverride},
+  r#ref::{HyphaFileBoardRef, HyphaFileIssueRef, HyphaFileListRef, HyphaRef},
+  view::{HyphaView, HyphaViewGroup, HyphaViewSort},
+};
+
+pub(crate) fn issue(title: &str) -> HyphaIssue {
+  HyphaIssue {
+    id: HyphaId::from(format!("issue-{title}")),
+    title: title.to_string(),
+    description: String::new(),
+    priority: None,
+    estimate: None,
+    due: None,
+    tags: vec![],
+    assignee: None,
+    created: None,
+    updated: None,
+  }
+}
+
+pub(crate) fn list(title: &str, issues: &[&str]) -> HyphaList {
+  HyphaList {
+    id: HyphaId::from(format!("list-{title}")),
+    title: title.to_string(),
+    issues: issues.iter().map(|title| issue(title)).collect(),
+  }
+}
+
+pub(crate) fn board(title: &str, lists: Vec<HyphaList>) -> HyphaBoard {
+  HyphaBoard {
+    id: HyphaId::from(format!("board-{title}")),
+    title: title.to_string(),
+    lists,
+    deps: vec![],
+    preferences: Default::default(),
+  }
+}
+
+pub(crate) fn file(boards: Vec<HyphaBoard>) -> HyphaFile {
+  HyphaFile {
+    version: VERSION,
+    boards,
+    deps: vec![],
+    preferences: HyphaPreferences::default(),
+    views: vec![],
+    path: PathBuf::new(),
+    changes: HyphaChanges::default(),
+  }
+}
+
+/// Board "Work" with the lists "Todo" holding issues a and b, "Doing" holding
+/// c and "Done" holding d.
+pub(crate) fn work() -> HyphaFile {
+  file(vec![board(
+    "Work",
+    vec![
+      list("Todo", &["a", "b"]),
+      list("Doing", &["c"]),
+      list("Done", &["d"]),
+    ],
+  )])
+}
+
+/// [`work`] with every kind of field set on some item, a dep and a view.
+pub(crate) fn sample() -> HyphaFile {
+  let mut file = work();
+  edit(&mut file, "a", |issue| {
+    issue.description = "Line one\nline \"two\"".to_string();
+    issue.priority = Some(HyphaPriority::High);
+    issue.estimate = Some(2.5);
+    issue.due = NaiveDate::from_ymd_opt(2025, 2, 28);
+    issue.tags = vec!["bug".to_string(), "ui".to_string()];
+    issue.assignee = Some("sam".to_string());
+    issue.created = Utc.with_ymd_and_hms(2025, 1, 2, 3, 4, 5).single();
+    issue.updated = Utc.timestamp_millis_opt(1_735_877_106_789).single();
+  });
+  link(&mut file, &[("a", "b")]);
+  file.preferences.capacity = 6.0;
+  if let Some(board) = file.boards.first_mut() {
+    board.preferences = HyphaPreferencesOverride {
+      age: Some(0.5),
+      ..Default::default()
+    };
+  }
+  file.views.push(HyphaView {
+    sort: HyphaViewSort::Due,
+    group: HyphaViewGroup::Tag,
+    ..HyphaView::new("Bugs".to_string(), "tag:bug".to_string())
+  });
+  file
+}
+
+/// New empty directory under the system temp directory.
+pub(crate) fn temp_dir() -> io::Result<PathBuf> {
+  let dir = std::env::temp_dir().join(format!("hypha-{}", Uuid::new_v4()));
+  fs::create_dir_all(&dir)?;
+  Ok(dir)
+}
+
+pub(crate) fn board_ref(file: &HyphaFile, title: &str) -> HyphaFileBoardRef {
+  HyphaFileBoardRef {
+    board: file
+      .boards
+      .iter()
+      .find(|board| board.title == title)
+      .map(|board| board.id.clone())
+      .unwrap_or_else(|| HyphaId::from(format!("board-{title}"))),
+  }
+}
+
+pub(crate) fn list_ref(file: &HyphaFile, title: &str) -> HyphaFileListRef {
+  file
+    .boards
+    .iter()
+    .find_map(|board| {
+      let stage = board.lists.iter().position(|list| list.title == title)?;
+      Some(HyphaFileListRef {
+        list: board.lists.get(stage)?.id.clone(),
+        stage,
+        board: board.id.clone(),
+      })
+    })
+    .unwrap_or_else(|| HyphaFileListRef {
+      list: HyphaId::from(format!("list-{title}")),
+      stage: 0,
+      board: HyphaId::default(),
+    })
+}
+
+pub(crate) fn issue_ref(file: &HyphaFile, title: &str) -> HyphaFileIssueRef {
+  file
+    .boards
+    .iter()
+    .flat_map(|board| {
+      board
+        .lists
+        .iter()
+        .enumerate()
+        .map(move |(stage, list)| (board, stage, list))
+    })
+    .find_map(|(board, stage, list)| {
+      let issue = list.issues.iter().find(|issue| issue.title == title)?;
+      Some(HyphaFileIssueRef {
+        issue: issue.id.clone(),
+        list: list.id.clone(),
+        stage,
+        board: board.id.clone(),
+      })
+    })
+    .unwrap_or_else(|| HyphaFileIssueRef {
+      issue: HyphaId::from(format!("issue-{title}")),
+      list: HyphaId::default(),
+      stage: 0,
+      board: HyphaId::default(),
+    })
+}
+
+/// Dep where the issue titled `left` blocks the one titled `right`.
+pub(crate) fn dep(
+  file: &HyphaFile,
+  left: &str,
+  right: &str,
+) -> HyphaDep<HyphaFileIssueRef> {
+  HyphaDep {
+    left: issue_ref(file, left),
+    right: issue_ref(file, right),
+  }
+}
+
+/// Adds deps between issues by title where `add_dep` would put them, on their
+/// board or on the file when they cross boards.
+pub(crate) fn link(file: &mut HyphaFile, pairs: &[(&str, &str)]) {
+  for (left, right) in pairs {
+    let dep = dep(file, left, right);
+    let board = file
+      .boards
+      .iter_mut()
+      .filter(|_| dep.left.board == dep.right.board)
+      .find(|board| board.id == dep.left.board);
+    match board {
+      Some(board) => board.deps.push(dep),
+      None => file.deps.push(dep),
+    }
+  }
+}
+
+/// Edits the issue titled `title` in place.
+pub(crate) fn edit(
+  file: &mut HyphaFile,
+  title: &str,
+  change: impl FnOnce(&mut HyphaIssue),
+) {
+  let issue = file
+    .boards
+    .iter_mut()
+    .flat_map(|board| board.lists.iter_mut())
+    .flat_map(|list| list.issues.iter_mut())
+    .find(|issue| issue.title == title);
+  if let Some(issue) = issue {
+    change(issue);
+  }
+}
+
+/// Titles of the issues behind `issue_refs`.
+pub(crate) fn titles(
+  file: &HyphaFile,
+  issue_refs: &[HyphaFileIssueRef],
+) -> Vec<String> {
+  issue_refs
+    .iter()
+    .filter_map(|issue_ref| issue_ref.get_item_from_container(file))
+    .map(|issue| issue.title.clone())
+    .collect()
+}
@@ -0,0 +1,429 @@
+use std::{collections::HashSet, fmt};
+use toml_edit::{ImDocument, Item};
+
+use crate::{
+  dep::HyphaDep, file::HyphaFile, graph::HyphaDepGraph, id::HyphaId, issue,
+  r#ref::HyphaFileIssueRef,
+};
+
+const UNTITLED: &str = "Untitled";
+
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct HyphaReport {
+  pub problems: Vec<HyphaProblem>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct HyphaProblem {
+  pub kind: HyphaProblemKind,
+  pub item: String,
+  pub location: Option<HyphaLocation>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HyphaLocation {
+  pub line: usize,
+  pub column: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum HyphaProblemKind {
+  DuplicateId,
+  DuplicateTitle,
+  EmptyTitle,
+  DanglingDep,
+  StaleDep,
+}
+
+impl fmt::Display for HyphaProblemKind {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      HyphaProblemKind::DuplicateId => {
+        write!(f, "shares its id with another item and got a new one")
+      }
+      HyphaProblemKind::DuplicateTitle => {
+        write!(f, "has the same title as a sibling")
+      }
+      HyphaProblemKind::EmptyTitle => write!(f, "has an empty title"),
+      HyphaProblemKind::DanglingDep => {
+        write!(f, "references an issue that does not exist")
+      }
+      HyphaProblemKind::StaleDep => {
+        write!(f, "references an issue at a list or stage it is not in")
+      }
+    }
+  }
+}
+
+impl fmt::Display for HyphaProblem {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if let Some(location) = self.location {
+      write!(f, "{}:{}: ", location.line, location.column)?;
+    }
+    write!(f, "{} {}", self.item, self.kind)
+  }
+}
+
+impl HyphaReport {
+  pub fn is_empty(&self) -> bool {
+    self.problems.is_empty()
+  }
+}
+
+/// Checks a freshly parsed file for problems, locating them in `content`.
+///
+/// This runs before duplicate ids are regenerated so they can be reported.
+pub fn validate(file: &HyphaFile, content: &str) -> HyphaReport {
+  let document = ImDocument::parse(content).ok();
+  let mut problems = Vec::new();
+  let mut report = |kind, item: String, target: Target| {
+    let location = document
+      .as_ref()
+      .and_then(|document| target.locate(document.as_item(), content));
+    problems.push(HyphaProblem {
+      kind,
+      item,
+      location,
+    });
+  };
+
+  let mut ids = HashSet::new();
+  let mut board_titles = HashSet::new();
+  for (board_idx, board) in file.boards.iter().enumerate() {
+    let item = format!("Board '{}'", board.title);
+    let target = Target::Board(board_idx);
+    check_item(
+      &mut report,
+      &mut ids,
+      &mut board_titles,
+      (&board.id, &board.title),
+      item,
+      target,
+    );
+
+    let mut list_titles = HashSet::new();
+    for (list_idx, list) in board.lists.iter().enumerate() {
+      let item = format!("List '{}'", list.title);
+      let target = Target::List(board_idx, list_idx);
+      check_item(
+        &mut report,
+        &mut ids,
+        &mut list_titles,
+        (&list.id, &list.title),
+        item,
+        target,
+      );
+
+      let mut issue_titles = HashSet::new();
+      for (issue_idx, issue) in list.issues.iter().enumerate() {
+        let item = format!("Issue '{}'", issue.title);
+        let target = Target::Issue(board_idx, list_idx, issue_idx);
+        check_item(
+          &mut report,
+          &mut ids,
+          &mut issue_titles,
+          (&issue.id, &issue.title),
+          item,
+          target,
+        );
+      }
+    }
+  }
+
+  let graph = HyphaDepGraph::new(file);
+  let deps = file
+    .deps
+    .iter()
+    .enumerate()
+    .map(|(dep_idx, dep)| (Target::Dep(None, dep_idx), dep))
+    .chain(
+      file
+        .boards
+        .iter()
+        .enumerate()
+        .flat_map(|(board_idx, board)| {
+          board.deps.iter().enumerate().map(move |(dep_idx, dep)| {
+            (Target::Dep(Some(board_idx), dep_idx), dep)
+          })
+        }),
+    );
+  let name = |issue_ref: &HyphaFileIssueRef| {
+    graph
+      .issue(&issue_ref.issue)
+      .and_then(|actual| issue::label(file, actual))
+      .map_or_else(
+        || format!("missing issue {}", issue_ref.issue),
+        |label| format!("'{label}'"),
+      )
+  };
+  for (target, dep) in deps {
+    let item = format!("Dep {} -> {}", name(&dep.left), name(&dep.right));
+    let actual = [&dep.left, &dep.right].map(|issue_ref| {
+      graph
+        .issue(&issue_ref.issue)
+        .map(|actual| actual == issue_ref)
+    });
+    if actual.contains(&None) {
+      report(HyphaProblemKind::DanglingDep, item, target);
+    } else if actual.contains(&Some(false)) {
+      report(HyphaProblemKind::StaleDep, item, target);
+    }
+  }
+
+  HyphaReport { problems }
+}
+
+fn check_item(
+  report: &mut impl FnMut(HyphaProblemKind, String, Target),
+  ids: &mut HashSet<String>,
+  titles: &mut HashSet<String>,
+  (id, title): (&HyphaId, &str),
+  item: String,
+  target: Target,
+) {
+  if !ids.insert(id.to_string()) {
+    report(HyphaProblemKind::DuplicateId, item.clone(), target);
+  }
+  if title.trim().is_empty() {
+    report(HyphaProblemKind::EmptyTitle, item, target);
+  } else if !titles.insert(title.to_string()) {
+    report(HyphaProblemKind::DuplicateTitle, item, target);
+  }
+}
+
+/// Fixes every problem `validate` reports.
+///
+/// Empty titles are named "Untitled", duplicate titles get a counter
+/// appended, dangling deps are removed and stale deps are pointed at where
+/// their issues actually are.
+pub fn fix(file: &mut HyphaFile) {
+  retitle(file.boards.iter_mut().map(|board| &mut board.title));
+  for board in file.boards.iter_mut() {
+    retitle(board.lists.iter_mut().map(|list| &mut list.title));
+    for list in board.lists.iter_mut() {
+      retitle(list.issues.iter_mut().map(|issue| &mut issue.title));
+    }
+  }
+
+  let graph = HyphaDepGraph::new(file);
+  let fix_deps = |deps: &mut Vec<HyphaDep<HyphaFileIssueRef>>| {
+    *deps = deps
+      .drain(..)
+      .filter_map(|dep| {
+        Some(HyphaDep {
+          left: graph.issue(&dep.left.issue)?.clone(),
+          right: graph.issue(&dep.right.issue)?.clone(),
+        })
+      })
+      .collect();
+  };
+  fix_deps(&mut file.deps);
+  for board in file.boards.iter_mut() {
+    fix_deps(&mut board.deps);
+  }
+}
+
+fn retitle<'a>(titles: impl Iterator<Item = &'a mut String>) {
+  let mut titles = titles.collect::<Vec<_>>();
+  for title in titles.iter_mut() {
+    if title.trim().is_empty() {
+      **title = UNTITLED.to_string();
+    }
+  }
+
+  let mut used = titles
+    .iter()
+    .map(|title| title.to_string())
+    .collect::<HashSet<_>>();
+  let mut seen = HashSet::new();
+  for title in titles {
+    if seen.insert(title.clone()) {
+      continue;
+    }
+    let mut count = 2_usize;
+    loop {
+      let candidate = format!("{title} ({count})");
+      if used.insert(candidate.clone()) {
+        seen.insert(candidate.clone());
+        *title = candidate;
+        break;
+      }
+      count = count.saturating_add(1);
+    }
+  }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Target {
+  Board(usize),
+  List(usize, usize),
+  Issue(usize, usize, usize),
+  Dep(Option<usize>, usize),
+}
+
+impl Target {
+  fn locate(self, root: &Item, content: &str) -> Option<HyphaLocation> {
+    let item = match self {
+      Target::Board(board) => root.get("boards")?.get(board),
+      Target::List(board, list) => {
+        root.get("boards")?.get(board)?.get("lists")?.get(list)
+      }
+      Target::Issue(board, list, issue) => root
+        .get("boards")?
+        .get(board)?
+        .get("lists")?
+        .get(list)?
+        .get("issues")?
+        .get(issue),
+      Target::Dep(None, dep) => root.get("deps")?.get(dep),
+      Target::Dep(Some(board), dep) => {
+        root.get("boards")?.get(board)?.get("deps")?.get(dep)
+      }
+    }?;
+
+    let before = content.get(..item.span()?.start)?;
+    let line = before.matches('\n').count().saturating_add(1);
+    let column = before
+      .rsplit('\n')
+      .next()
+      .map_or(0, |line| line.chars().count())
+      .saturating_add(1);
+    Some(HyphaLocation { line, column })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::{dep, edit, link, list_ref, work};
+
+  fn kinds(file: &HyphaFile) -> Vec<(HyphaProblemKind, String)> {
+    let content = toml::to_string(file).unwrap_or_default();
+    validate(file, &content)
+      .problems
+      .into_iter()
+      .map(|problem| (problem.kind, problem.item))
+      .collect()
+  }
+
+  fn issue_titles(file: &HyphaFile) -> Vec<&str> {
+    file
+      .boards
+      .iter()
+      .flat_map(|board| board.lists.iter())
+      .flat_map(|list| list.issues.iter())
+      .map(|issue| issue.title.as_str())
+      .collect()
+  }
+
+  #[test]
+  fn accepts_clean_file() {
+    let mut file = work();
+    link(&mut file, &[("a", "b")]);
+    assert_eq!(kinds(&file), []);
+  }
+
+  #[test]
+  fn reports_duplicate_ids_and_titles() {
+    let mut file = work();
+    edit(&mut file, "b", |issue| {
+      issue.id = HyphaId::from("issue-a".to_string());
+      issue.title = "a".to_string();
+    });
+    assert_eq!(
+      kinds(&file),
+      [
+        (HyphaProblemKind::DuplicateId, "Issue 'a'".to_string()),
+        (HyphaProblemKind::DuplicateTitle, "Issue 'a'".to_string()),
+      ]
+    );
+  }
+
+  #[test]
+  fn reports_empty_titles() {
+    let mut file = work();
+    if let Some(list) =
+      file.boards.first_mut().and_then(|b| b.lists.first_mut())
+    {
+      list.title = " ".to_string();
+    }
+    assert_eq!(
+      kinds(&file),
+      [(HyphaProblemKind::EmptyTitle, "List ' '".to_string())]
+    );
+  }
+
+  #[test]
+  fn reports_deps_by_issue_label() {
+    let mut file = work();
+    let mut stale = dep(&file, "a", "c");
+    stale.right.stage = 0;
+    let mut dangling = dep(&file, "a", "b");
+    dangling.right.issue = HyphaId::from("gone".to_string());
+    if let Some(board) = file.boards.first_mut() {
+      board.deps = vec![stale, dangling];
+    }
+    assert_eq!(
+      kinds(&file),
+      [
+        (
+          HyphaProblemKind::StaleDep,
+          "Dep 'a (Work / Todo)' -> 'c (Work / Doing)'".to_string()
+        ),
+        (
+          HyphaProblemKind::DanglingDep,
+          "Dep 'a (Work / Todo)' -> missing issue gone".to_string()
+        ),
+      ]
+    );
+  }
+
+  #[test]
+  fn locates_problems_in_content() {
+    let mut file = work();
+    edit(&mut file, "c", |issue| issue.title = String::new());
+    let content = toml::to_string(&file).unwrap_or_default();
+    let report = validate(&file, &content);
+    let line = content
+      .lines()
+      .position(|line| line.contains("issue-c"))
+      .map(|line| line.saturating_add(1));
+    let location = report.problems.first().and_then(|problem| problem.location);
+    assert!(location.is_some_and(|location| {
+      line.is_some_and(|line| location.line.abs_diff(line) <= 1)
+    }));
+  }
+
+  #[test]
+  fn fixes_every_problem() {
+    let mut file = work();
+    edit(&mut file, "b", |issue| issue.title = "a".to_string());
+    edit(&mut file, "c", |issue| issue.title = String::new());
+    let mut stale = dep(&file, "a", "c");
+    stale.right.list = list_ref(&file, "Todo").list;
+    stale.right.stage = 0;
+    let mut dangling = dep(&file, "a", "d");
+    dangling.left.issue = HyphaId::from("gone".to_string());
+    file.deps = vec![stale, dangling];
+
+    fix(&mut file);
+    assert_eq!(kinds(&file), []);
+    assert_eq!(issue_titles(&file), ["a", "a (2)", "Untitled", "d"]);
+    assert_eq!(file.deps, [dep(&file, "a", "Untitled")]);
+  }
+
+  #[test]
+  fn picks_suffixes_not_in_use() {
+    let mut file = work();
+    edit(&mut file, "b", |issue| issue.title = "a".to_string());
+    if let Some(list) =
+      file.boards.first_mut().and_then(|b| b.lists.first_mut())
+    {
+      let mut issue = crate::testing::issue("a (2)");
+      issue.id = HyphaId::from("issue-e".to_string());
+      list.issues.push(issue);
+    }
+    fix(&mut file);
+    assert_eq!(issue_titles(&file), ["a", "a (3)", "a (2)", "c", "d"]);
+  }
+}
@@ -0,0 +1,462 @@
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+use crate::{
+  id::HyphaId,
+  issue::{HyphaIssue, HyphaPriority},
+  item::HyphaItem,
+  query::{HyphaQuery, HyphaQueryScope, QueryError},
+  r#ref::{HyphaFileIssueRef, HyphaRef},
+};
+
+/// Named query shown as lists of issues gathered from every board.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HyphaView {
+  #[serde(default)]
+  pub id: HyphaId,
+  pub title: String,
+  #[serde(default)]
+  pub query: String,
+  #[serde(default)]
+  pub sort: HyphaViewSort,
+  #[serde(default)]
+  pub group: HyphaViewGroup,
+}
+
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum HyphaViewSort {
+  #[default]
+  Position,
+  Title,
+  Priority,
+  Due,
+  Estimate,
+  Created,
+  Updated,
+}
+
+#[derive(
+  Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum HyphaViewGroup {
+  #[default]
+  None,
+  Board,
+  List,
+  Priority,
+  Assignee,
+  Tag,
+}
+
+/// Virtual list of a view holding refs to issues that stay where they are.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HyphaViewList {
+  pub title: String,
+  pub issues: Vec<HyphaFileIssueRef>,
+}
+
+impl HyphaItem for HyphaView {
+  fn id(&self) -> &HyphaId {
+    &self.id
+  }
+
+  fn title(&self) -> &str {
+    &self.title
+  }
+}
+
+impl HyphaView {
+  pub fn new(title: String, query: String) -> Self {
+    Self {
+      id: HyphaId::new(),
+      title,
+      query,
+      sort: HyphaViewSort::default(),
+      group: HyphaViewGroup::default(),
+    }
+  }
+
+  /// Lists of the issues matching the query, grouped and sorted.
+  ///
+  /// Without grouping there is a single list titled like the view. Groups are
+  /// ordered by their first issue in the file except for priorities which go
+  /// from urgent to low. Issues without the grouped field end up in a last
+  /// list and issues with several tags in each of their tags.
+  pub fn lists(
+    &self,
+    scope: &HyphaQueryScope,
+  ) -> Result<Vec<HyphaViewList>, QueryError> {
+    let query = HyphaQuery::parse(&self.query)?;
+    let file = scope.file();
+    let mut issues = query
+      .filter(scope)
+      .into_iter()
+      .filter_map(|issue_ref| {
+        let issue = issue_ref.get_item_from_container(file)?;
+        Some((issue, issue_ref))
+      })
+      .collect::<Vec<_>>();
+    issues.sort_by(|(left, _), (right, _)| self.sort.compare(left, right));
+
+    let mut lists = Vec::<HyphaViewList>::new();
+    let mut missing = Vec::new();
+    for (issue, issue_ref) in issues {
+      let titles = match self.group {
+        HyphaViewGroup::None => vec![self.title.clone()],
+        HyphaViewGroup::Board => file
+          .boards
+          .iter()
+          .find(|board| board.id == issue_ref.board)
+          .map(|board| board.title.clone())
+          .into_iter()
+          .collect(),
+        HyphaViewGroup::List => file
+          .boards
+          .iter()
+          .find(|board| board.id == issue_ref.board)
+          .and_then(|board| board.lists.get(issue_ref.stage))
+          .map(|list| list.title.clone())
+          .into_iter()
+          .collect(),
+        HyphaViewGroup::Priority => issue
+          .priority
+          .map(|priority| priority.as_str().to_string())
+          .into_iter()
+          .collect(),
+        HyphaViewGroup::Assignee => {
+          issue.assignee.clone().into_iter().collect()
+        }
+        HyphaViewGroup::Tag => issue.tags.clone(),
+      };
+      if titles.is_empty() {
+        missing.push(issue_ref);
+        continue;
+      }
+      for title in titles {
+        match lists.iter_mut().find(|list| list.title == title) {
+          Some(list) => list.issues.push(issue_ref.clone()),
+          None => lists.push(HyphaViewList {
+            title,
+            issues: vec![issue_ref.clone()],
+          }),
+        }
+      }
+    }
+
+    if self.group == HyphaViewGroup::Priority {
+      lists.sort_by_key(|list| {
+        HyphaPriority::parse(&list.title).map(|priority| {
+          HyphaPriority::ALL
+            .iter()
+            .rev()
+            .position(|candidate| *candidate == priority)
+        })
+      });
+    }
+    if !missing.is_empty() {
+      lists.push(HyphaViewList {
+        title: self.group.missing().to_string(),
+        issues: missing,
+      });
+    }
+    Ok(lists)
+  }
+}
+
+impl HyphaViewSort {
+  pub const ALL: [HyphaViewSort; 7] = [
+    HyphaViewSort::Position,
+    HyphaViewSort::Title,
+    HyphaViewSort::Priority,
+    HyphaViewSort::Due,
+    HyphaViewSort::Estimate,
+    HyphaViewSort::Created,
+    HyphaViewSort::Updated,
+  ];
+
+  pub fn as_str(self) -> &'static str {
+    match self {
+      HyphaViewSort::Position => "position",
+      HyphaViewSort::Title => "title",
+      HyphaViewSort::Priority => "priority",
+      HyphaViewSort::Due => "due",
+      HyphaViewSort::Estimate => "estimate",
+      HyphaViewSort::Created => "created",
+      HyphaViewSort::Updated => "updated",
+    }
+  }
+
+  pub fn parse(value: &str) -> Option<Self> {
+    Self::ALL.into_iter().find(|sort| sort.as_str() == value)
+  }
+
+  // Most urgent, soonest due, smallest and most recent first with issues
+  // missing the field last. Ties keep their position in the file.
+  fn compare(self, left: &HyphaIssue, right: &HyphaIssue) -> Ordering {
+    match self {
+      HyphaViewSort::Position => Ordering::Equal,
+      HyphaViewSort::Title => {
+        left.title.to_lowercase().cmp(&right.title.to_lowercase())
+      }
+      HyphaViewSort::Priority => missing_last(
+        left.priority.map(HyphaPriority::weight),
+        right.priority.map(HyphaPriority::weight),
+        true,
+      ),
+      HyphaViewSort::Due => missing_last(left.due, right.due, false),
+      HyphaViewSort::Estimate => {
+        missing_last(left.estimate, right.estimate, false)
+      }
+      HyphaViewSort::Created => missing_last(left.created, right.created, true),
+      HyphaViewSort::Updated => missing_last(left.updated, right.updated, true),
+    }
+  }
+}
+
+impl HyphaViewGroup {
+  pub const ALL: [HyphaViewGroup; 6] = [
+    HyphaViewGroup::None,
+    HyphaViewGroup::Board,
+    HyphaViewGroup::List,
+    HyphaViewGroup::Priority,
+    HyphaViewGroup::Assignee,
+    HyphaViewGroup::Tag,
+  ];
+
+  pub fn as_str(self) -> &'static str {
+    match self {
+      HyphaViewGroup::None => "none",
+      HyphaViewGroup::Board => "board",
+      HyphaViewGroup::List => "list",
+      HyphaViewGroup::Priority => "priority",
+      HyphaViewGroup::Assignee => "assignee",
+      HyphaViewGroup::Tag => "tag",
+    }
+  }
+
+  pub fn parse(value: &str) -> Option<Self> {
+    Self::ALL.into_iter().find(|group| group.as_str() == value)
+  }
+
+  fn missing(self) -> &'static str {
+    match self {
+      HyphaViewGroup::Priority => "No priority",
+      HyphaViewGroup::Assignee => "Unassigned",
+      HyphaViewGroup::Tag => "No tags",
+      HyphaViewGroup::None | HyphaViewGroup::Board | HyphaViewGroup::List => {
+        "Other"
+      }
+    }
+  }
+}
+
+fn missing_last<T: PartialOrd>(
+  left: Option<T>,
+  right: Option<T>,
+  descending: bool,
+) -> Ordering {
+  match (left, right) {
+    (Some(left), Some(right)) => {
+      let ordering = left.partial_cmp(&right).unwrap_or(Ordering::Equal);
+      if descending {
+        ordering.reverse()
+      } else {
+        ordering
+      }
+    }
+    (Some(_), None) => Ordering::Less,
+    (None, Some(_)) => Ordering::Greater,
+    (None, None) => Ordering::Equal,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use chrono::{NaiveDate, TimeZone, Utc};
+
+  use super::*;
+  use crate::{
+    file::HyphaFile,
+    testing::{board, board_ref, edit, list, list_ref, titles, work},
+  };
+
+  /// Board "Home" with the list "Chores" holding e before [`work`], with
+  /// fields set on a, b and c.
+  fn home() -> HyphaFile {
+    let mut file = work();
+    file
+      .boards
+      .insert(0, board("Home", vec![list("Chores", &["e"])]));
+    edit(&mut file, "a", |issue| {
+      issue.priority = Some(HyphaPriority::High);
+      issue.due = NaiveDate::from_ymd_opt(2025, 3, 1);
+      issue.estimate = Some(3.0);
+      issue.tags = vec!["bug".to_string(), "ui".to_string()];
+      issue.assignee = Some("sam".to_string());
+      issue.created = Utc.with_ymd_and_hms(2025, 1, 2, 0, 0, 0).single();
+      issue.updated = Utc.with_ymd_and_hms(2025, 1, 5, 0, 0, 0).single();
+    });
+    edit(&mut file, "b", |issue| {
+      issue.priority = Some(HyphaPriority::Low);
+      issue.due = NaiveDate::from_ymd_opt(2025, 2, 25);
+      issue.estimate = Some(1.0);
+      issue.tags = vec!["ui".to_string()];
+      issue.created = Utc.with_ymd_and_hms(2025, 1, 3, 0, 0, 0).single();
+    });
+    edit(&mut file, "c", |issue| {
+      issue.priority = Some(HyphaPriority::Urgent);
+      issue.estimate = Some(2.0);
+      issue.assignee = Some("kim".to_string());
+      issue.updated = Utc.with_ymd_and_hms(2025, 1, 6, 0, 0, 0).single();
+    });
+    file
+  }
+
+  fn lists(
+    file: &HyphaFile,
+    query: &str,
+    sort: HyphaViewSort,
+    group: HyphaViewGroup,
+  ) -> Result<Vec<(String, Vec<String>)>, QueryError> {
+    let view = HyphaView {
+      sort,
+      group,
+      ..HyphaView::new("All".to_string(), query.to_string())
+    };
+    let today = NaiveDate::from_ymd_opt(2025, 2, 20).unwrap_or_default();
+    let scope = HyphaQueryScope::new(file, today);
+    Ok(
+      view
+        .lists(&scope)?
+        .into_iter()
+        .map(|list| (list.title, titles(file, &list.issues)))
+        .collect(),
+    )
+  }
+
+  fn expected(lists: &[(&str, &[&str])]) -> Vec<(String, Vec<String>)> {
+    lists
+      .iter()
+      .map(|(title, issues)| {
+        (
+          title.to_string(),
+          issues.iter().map(|issue| issue.to_string()).collect(),
+        )
+      })
+      .collect()
+  }
+
+  #[test]
+  fn sorts_issues() -> Result<(), QueryError> {
+    let file = home();
+    let sorted = |sort| lists(&file, "", sort, HyphaViewGroup::None);
+    let cases: [(HyphaViewSort, &[&str]); 7] = [
+      (HyphaViewSort::Position, &["e", "a", "b", "c", "d"]),
+      (HyphaViewSort::Title, &["a", "b", "c", "d", "e"]),
+      (HyphaViewSort::Priority, &["c", "a", "b", "e", "d"]),
+      (HyphaViewSort::Due, &["b", "a", "e", "c", "d"]),
+      (HyphaViewSort::Estimate, &["b", "c", "a", "e", "d"]),
+      (HyphaViewSort::Created, &["b", "a", "e", "c", "d"]),
+      (HyphaViewSort::Updated, &["c", "a", "e", "b", "d"]),
+    ];
+    for (sort, issues) in cases {
+      assert_eq!(sorted(sort)?, expected(&[("All", issues)]), "{sort:?}");
+    }
+    Ok(())
+  }
+
+  #[test]
+  fn groups_issues() -> Result<(), QueryError> {
+    let file = home();
+    let grouped = |group| lists(&file, "", HyphaViewSort::Position, group);
+    assert_eq!(
+      grouped(HyphaViewGroup::Board)?,
+      expected(&[("Home", &["e"]), ("Work", &["a", "b", "c", "d"])])
+    );
+    assert_eq!(
+      grouped(HyphaViewGroup::List)?,
+      expected(&[
+        ("Chores", &["e"]),
+        ("Todo", &["a", "b"]),
+        ("Doing", &["c"]),
+        ("Done", &["d"]),
+      ])
+    );
+    assert_eq!(
+      grouped(HyphaViewGroup::Priority)?,
+      expected(&[
+        ("urgent", &["c"]),
+        ("high", &["a"]),
+        ("low", &["b"]),
+        ("No priority", &["e", "d"]),
+      ])
+    );
+    assert_eq!(
+      grouped(HyphaViewGroup::Assignee)?,
+      expected(&[
+        ("sam", &["a"]),
+        ("kim", &["c"]),
+        ("Unassigned", &["e", "b", "d"]),
+      ])
+    );
+    assert_eq!(
+      grouped(HyphaViewGroup::Tag)?,
+      expected(&[
+        ("bug", &["a"]),
+        ("ui", &["a", "b"]),
+        ("No tags", &["e", "c", "d"]),
+      ])
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn groups_filtered_and_sorted_issues() -> Result<(), QueryError> {
+    assert_eq!(
+      lists(&home(), "tag:ui", HyphaViewSort::Due, HyphaViewGroup::Tag)?,
+      expected(&[("ui", &["b", "a"]), ("bug", &["a"])])
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn shows_nothing_for_removed_lists_and_labels() -> Result<(), QueryError> {
+    let mut file = home();
+    let none = Vec::new();
+    file.remove_list(list_ref(&file, "Doing"));
+    file.remove_board(board_ref(&file, "Home"));
+    edit(&mut file, "b", |issue| issue.tags.clear());
+    let group = HyphaViewGroup::List;
+    assert_eq!(lists(&file, "list:doing", HyphaViewSort::Due, group)?, none);
+    assert_eq!(lists(&file, "board:home", HyphaViewSort::Due, group)?, none);
+    assert_eq!(
+      lists(&file, "tag:ui -tag:bug", HyphaViewSort::Title, group)?,
+      none
+    );
+    assert_eq!(
+      lists(&file, "-list:doing", HyphaViewSort::Title, group)?,
+      expected(&[("Todo", &["a", "b"]), ("Done", &["d"])])
+    );
+    Ok(())
+  }
+
+  #[test]
+  fn rejects_invalid_queries() {
+    assert_eq!(
+      lists(
+        &home(),
+        "due<soon",
+        HyphaViewSort::Due,
+        HyphaViewGroup::None
+      ),
+      Err(QueryError::InvalidValue(
+        "due".to_string(),
+        "soon".to_string()
+      ))
+    );
+  }
+}
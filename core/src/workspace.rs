@@ -0,0 +1,640 @@
+use directories::ProjectDirs;
+use log::{error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::{
+  collections::HashMap,
+  env, fs, io,
+  path::{Path, PathBuf},
+};
+use tokio::task;
+
+use crate::{
+  container::HyphaContainer,
+  dep::HyphaDep,
+  file::{expand, FileError, HyphaFile},
+  graph::{DepError, HyphaDepGraph},
+  id::HyphaId,
+  issue::{self, HyphaIssue},
+  item::HyphaItem,
+  preferences::HyphaPreferencesOverride,
+  r#ref::{HyphaFileIssueRef, HyphaRef},
+  storage::{replace, write_temp},
+};
+
+/// Several hypha files opened together along with the deps between them.
+///
+/// Deps within one file stay in that file, only deps across files are kept
+/// here with refs qualified by the id of the file.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct HyphaWorkspace {
+  #[serde(default)]
+  pub files: Vec<HyphaWorkspaceFile>,
+  #[serde(default)]
+  pub deps: Vec<HyphaDep<HyphaWorkspaceIssueRef>>,
+  /// Daily capacity shared by every file when suggesting across the
+  /// workspace, defaulting to the largest capacity of any file.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub capacity: Option<f64>,
+  #[serde(skip)]
+  pub path: PathBuf,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct HyphaWorkspaceFile {
+  #[serde(default)]
+  pub id: HyphaId,
+  pub name: String,
+  pub path: PathBuf,
+  /// The file as last loaded or saved, if it could be loaded.
+  #[serde(skip)]
+  pub loaded: Option<HyphaFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct HyphaWorkspaceIssueRef {
+  pub file: HyphaId,
+  pub issue: HyphaId,
+  pub list: HyphaId,
+  pub stage: usize,
+  pub board: HyphaId,
+}
+
+impl HyphaWorkspaceIssueRef {
+  pub fn new(file: HyphaId, issue_ref: HyphaFileIssueRef) -> Self {
+    Self {
+      file,
+      issue: issue_ref.issue,
+      list: issue_ref.list,
+      stage: issue_ref.stage,
+      board: issue_ref.board,
+    }
+  }
+
+  /// The ref within its own file.
+  pub fn file_ref(&self) -> HyphaFileIssueRef {
+    HyphaFileIssueRef {
+      issue: self.issue.clone(),
+      list: self.list.clone(),
+      stage: self.stage,
+      board: self.board.clone(),
+    }
+  }
+}
+
+impl HyphaItem for HyphaWorkspaceFile {
+  fn id(&self) -> &HyphaId {
+    &self.id
+  }
+
+  fn title(&self) -> &str {
+    &self.name
+  }
+}
+
+impl HyphaContainer for HyphaWorkspace {
+  type Item = HyphaWorkspaceFile;
+  type Ref = HyphaWorkspaceIssueRef;
+
+  fn items(&self) -> &Vec<Self::Item> {
+    &self.files
+  }
+
+  fn items_mut(&mut self) -> &mut Vec<Self::Item> {
+    &mut self.files
+  }
+}
+
+impl HyphaRef for HyphaWorkspaceIssueRef {
+  type Item = HyphaIssue;
+  type Container = HyphaWorkspace;
+
+  fn get_item_from_container<'a>(
+    &self,
+    container: &'a Self::Container,
+  ) -> Option<&'a Self::Item> {
+    let file = container.file(&self.file)?.loaded.as_ref()?;
+    self.file_ref().get_item_from_container(file)
+  }
+
+  fn get_item_from_container_mut<'a>(
+    &self,
+    container: &'a mut Self::Container,
+  ) -> Option<&'a mut Self::Item> {
+    let file = container
+      .files
+      .iter_mut()
+      .find(|file| file.id == self.file)?
+      .loaded
+      .as_mut()?;
+    self.file_ref().get_item_from_container_mut(file)
+  }
+
+  fn remove_item_from_container(
+    &self,
+    container: &mut Self::Container,
+  ) -> bool {
+    let file = container
+      .files
+      .iter_mut()
+      .find(|file| file.id == self.file)
+      .and_then(|file| file.loaded.as_mut());
+    match file {
+      Some(file) => self.file_ref().remove_item_from_container(file),
+      None => false,
+    }
+  }
+}
+
+impl HyphaWorkspace {
+  pub fn path() -> Result<PathBuf, FileError> {
+    if let Ok(path_str) = env::var("HYPHA_WORKSPACE") {
+      let expanded_path = expand(&path_str)?;
+      info!(
+        "Using workspace path from HYPHA_WORKSPACE env var: {}",
+        expanded_path.display()
+      );
+      return Ok(expanded_path);
+    }
+
+    if let Some(proj_dirs) = ProjectDirs::from("com", "HyphaApp", "Hypha") {
+      let default_path = proj_dirs.config_dir().join("workspace.toml");
+      info!("Using default workspace path: {}", default_path.display());
+      Ok(default_path)
+    } else {
+      error!(
+        "Could not determine project directory for default workspace path."
+      );
+      Err(FileError::Directory)
+    }
+  }
+
+  /// Loads the workspace at `path` without loading its files.
+  ///
+  /// A missing workspace starts out with just the default hypha file.
+  pub fn load(path: &Path) -> Result<HyphaWorkspace, FileError> {
+    info!("Attempting to load workspace from: {}", path.display());
+    let mut workspace = match fs::read_to_string(path) {
+      Ok(content) => toml::from_str::<HyphaWorkspace>(&content)?,
+      Err(e) if e.kind() == io::ErrorKind::NotFound => {
+        warn!(
+          "Workspace not found at {}, starting with the default file.",
+          path.display()
+        );
+        let mut workspace = HyphaWorkspace::default();
+        if let Ok(file_path) = HyphaFile::path() {
+          workspace.add_file(file_path);
+        }
+        workspace
+      }
+      Err(e) => return Err(FileError::Io(e)),
+    };
+    workspace.path = path.to_path_buf();
+    Ok(workspace)
+  }
+
+  pub async fn load_async(path: &Path) -> Result<HyphaWorkspace, FileError> {
+    let path = path.to_path_buf();
+    task::spawn_blocking(move || Self::load(&path)).await?
+  }
+
+  /// Loads every file of the workspace, leaving files that fail to load
+  /// unloaded.
+  pub fn load_files(&mut self) {
+    for file in self.files.iter_mut() {
+      file.loaded = match HyphaFile::load(&file.path) {
+        Ok(loaded) => Some(HyphaFile {
+          path: file.path.clone(),
+          ..loaded
+        }),
+        Err(e) => {
+          warn!("Failed to load workspace file {}: {e}", file.path.display());
+          None
+        }
+      };
+    }
+    self.restage();
+  }
+
+  pub async fn load_files_async(mut self) -> Result<HyphaWorkspace, FileError> {
+    task::spawn_blocking(move || {
+      self.load_files();
+      self
+    })
+    .await
+    .map_err(FileError::from)
+  }
+
+  pub fn save(&self) -> Result<(), FileError> {
+    let path = self.path.as_path();
+    if let Some(parent_dir) = path.parent() {
+      fs::create_dir_all(parent_dir)?;
+    }
+    let content = toml::to_string_pretty(self)?;
+    let temp_path = write_temp(path, content.as_bytes())?;
+    replace(&temp_path, path)?;
+    info!("Workspace saved successfully to: {}", path.display());
+    Ok(())
+  }
+
+  pub async fn save_async(&self) -> Result<(), FileError> {
+    let workspace = self.clone();
+    task::spawn_blocking(move || workspace.save()).await?
+  }
+
+  pub fn file(&self, id: &HyphaId) -> Option<&HyphaWorkspaceFile> {
+    self.files.iter().find(|file| &file.id == id)
+  }
+
+  /// Adds the file at `path` named after its file stem, or returns the id of
+  /// the file already at `path`.
+  pub fn add_file(&mut self, path: PathBuf) -> HyphaId {
+    if let Some(file) = self.files.iter().find(|file| file.path == path) {
+      return file.id.clone();
+    }
+
+    let stem = path
+      .file_stem()
+      .and_then(|stem| stem.to_str())
+      .filter(|stem| !stem.is_empty())
+      .unwrap_or("hypha")
+      .to_string();
+    let mut name = stem.clone();
+    let mut count = 1_usize;
+    while self.files.iter().any(|file| file.name == name) {
+      count = count.saturating_add(1);
+      name = format!("{stem}-{count}");
+    }
+
+    let id = HyphaId::new();
+    self.files.push(HyphaWorkspaceFile {
+      id: id.clone(),
+      name,
+      path,
+      loaded: None,
+    });
+    id
+  }
+
+  /// Removes a file from the workspace along with its deps on other files.
+  pub fn remove_file(&mut self, id: &HyphaId) -> bool {
+    let count = self.files.len();
+    self.files.retain(|file| &file.id != id);
+    self
+      .deps
+      .retain(|dep| &dep.left.file != id && &dep.right.file != id);
+    self.files.len() != count
+  }
+
+  /// Replaces the loaded copy of a file, keeping deps on it in place.
+  pub fn update_file(&mut self, id: &HyphaId, loaded: HyphaFile) {
+    if let Some(file) = self.files.iter_mut().find(|file| &file.id == id) {
+      file.loaded = Some(loaded);
+    }
+    self.restage();
+  }
+
+  pub fn add_dep(
+    &mut self,
+    dep: HyphaDep<HyphaWorkspaceIssueRef>,
+  ) -> Result<(), DepError> {
+    if dep.left.file == dep.right.file {
+      return Err(DepError::SameFile(dep.left.issue, dep.right.issue));
+    }
+    self.check_dep(&dep)?;
+    self.deps.push(dep);
+    Ok(())
+  }
+
+  /// Checks a dep within or across files against the deps of every loaded
+  /// file and between them, so cycles through other files are caught too.
+  pub fn check_dep(
+    &self,
+    dep: &HyphaDep<HyphaWorkspaceIssueRef>,
+  ) -> Result<(), DepError> {
+    for issue_ref in [&dep.left, &dep.right] {
+      if issue_ref.get_item_from_container(self).is_none() {
+        return Err(DepError::Missing(issue_ref.issue.clone()));
+      }
+    }
+
+    let combined = self.combined();
+    HyphaDepGraph::new(&combined).check(&HyphaDep {
+      left: dep.left.file_ref(),
+      right: dep.right.file_ref(),
+    })
+  }
+
+  pub fn remove_dep(&mut self, dep: HyphaDep<HyphaWorkspaceIssueRef>) -> bool {
+    let count = self.deps.len();
+    self.deps.retain(|other| {
+      other.left.issue != dep.left.issue || other.right.issue != dep.right.issue
+    });
+    self.deps.len() != count
+  }
+
+  /// Qualifies a ref to an issue of one of the loaded files with its file.
+  pub fn qualify(
+    &self,
+    issue_ref: HyphaFileIssueRef,
+  ) -> Option<HyphaWorkspaceIssueRef> {
+    let file = self.files.iter().find(|file| {
+      file.loaded.as_ref().is_some_and(|loaded| {
+        loaded
+          .boards
+          .iter()
+          .any(|board| board.id == issue_ref.board)
+      })
+    })?;
+    Some(HyphaWorkspaceIssueRef::new(file.id.clone(), issue_ref))
+  }
+
+  /// Label of an issue prefixed with the name of its file.
+  pub fn label(&self, issue_ref: &HyphaWorkspaceIssueRef) -> Option<String> {
+    let file = self.file(&issue_ref.file)?;
+    let label = issue::label(file.loaded.as_ref()?, &issue_ref.file_ref())?;
+    Some(format!("{}: {label}", file.name))
+  }
+
+  /// Every loaded file merged into one with the deps across files added to
+  /// it.
+  ///
+  /// Boards keep the preferences of their file as overrides so suggestions
+  /// weigh them the same as in their own file.
+  pub fn combined(&self) -> HyphaFile {
+    let mut combined = HyphaFile {
+      boards: Vec::new(),
+      deps: Vec::new(),
+      path: PathBuf::new(),
+      ..HyphaFile::default()
+    };
+    let mut capacity = None::<f64>;
+    for file in self.files.iter().filter_map(|file| file.loaded.as_ref()) {
+      capacity = Some(capacity.map_or(file.preferences.capacity, |capacity| {
+        capacity.max(file.preferences.capacity)
+      }));
+      combined.deps.extend(file.deps.iter().cloned());
+      combined.boards.extend(file.boards.iter().map(|board| {
+        let mut board = board.clone();
+        let preferences = file.preferences.with(&board.preferences);
+        board.preferences = HyphaPreferencesOverride::from(&preferences);
+        board
+      }));
+    }
+    combined.preferences.capacity = self
+      .capacity
+      .or(capacity)
+      .unwrap_or(combined.preferences.capacity);
+    combined.deps.extend(self.deps.iter().map(|dep| HyphaDep {
+      left: dep.left.file_ref(),
+      right: dep.right.file_ref(),
+    }));
+    combined
+  }
+
+  // Deps across files are not updated when issues move within their file so
+  // their lists and stages are looked up again from the loaded files.
+  fn restage(&mut self) {
+    let mut positions = HashMap::new();
+    for file in self.files.iter() {
+      let Some(loaded) = file.loaded.as_ref() else {
+        continue;
+      };
+      for issue_ref in HyphaDepGraph::new(loaded).issues() {
+        positions.insert(
+          issue_ref.issue.clone(),
+          HyphaWorkspaceIssueRef::new(file.id.clone(), issue_ref.clone()),
+        );
+      }
+    }
+
+    for dep in self.deps.iter_mut() {
+      for issue_ref in [&mut dep.left, &mut dep.right] {
+        if let Some(position) = positions.get(&issue_ref.issue) {
+          *issue_ref = position.clone();
+        }
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    suggest::suggest_workspace,
+    testing::{board, file, issue_ref, list, list_ref, sample, temp_dir},
+  };
+  use chrono::NaiveDate;
+
+  /// Workspace holding [`sample`] as "work" and a "home" file with issues x
+  /// and y.
+  fn workspace() -> HyphaWorkspace {
+    let mut workspace = HyphaWorkspace::default();
+    let home = file(vec![board(
+      "Home",
+      vec![list("Todo", &["x"]), list("Done", &["y"])],
+    )]);
+    for (name, loaded) in [("work", sample()), ("home", home)] {
+      let id = workspace.add_file(PathBuf::from(format!("{name}.toml")));
+      workspace.update_file(&id, loaded);
+    }
+    workspace
+  }
+
+  /// Dep where the issue titled `left` blocks the one titled `right`, looked
+  /// up across the loaded files.
+  fn dep(
+    workspace: &HyphaWorkspace,
+    left: &str,
+    right: &str,
+  ) -> HyphaDep<HyphaWorkspaceIssueRef> {
+    let qualify = |title| {
+      workspace
+        .files
+        .iter()
+        .filter_map(|file| file.loaded.as_ref())
+        .map(|loaded| issue_ref(loaded, title))
+        .find_map(|issue_ref| workspace.qualify(issue_ref))
+        .filter(|issue_ref| {
+          issue_ref.get_item_from_container(workspace).is_some()
+        })
+        .unwrap_or_else(|| HyphaWorkspaceIssueRef {
+          file: HyphaId::default(),
+          issue: HyphaId::from(format!("issue-{title}")),
+          list: HyphaId::default(),
+          stage: 0,
+          board: HyphaId::default(),
+        })
+    };
+    HyphaDep {
+      left: qualify(left),
+      right: qualify(right),
+    }
+  }
+
+  #[test]
+  fn names_files_after_their_path() {
+    let mut workspace = HyphaWorkspace::default();
+    let first = workspace.add_file(PathBuf::from("a/hypha.toml"));
+    let second = workspace.add_file(PathBuf::from("b/hypha.toml"));
+    assert_eq!(workspace.add_file(PathBuf::from("a/hypha.toml")), first);
+    assert_ne!(first, second);
+    let names = workspace
+      .files
+      .iter()
+      .map(|file| file.name.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(names, ["hypha", "hypha-2"]);
+  }
+
+  #[test]
+  fn adds_deps_across_files() {
+    let mut workspace = workspace();
+    assert_eq!(workspace.add_dep(dep(&workspace, "b", "x")), Ok(()));
+    assert!(workspace
+      .label(&dep(&workspace, "b", "x").right)
+      .is_some_and(|label| label.starts_with("home: ")));
+
+    let same = dep(&workspace, "a", "c");
+    assert!(matches!(
+      workspace.add_dep(same),
+      Err(DepError::SameFile(..))
+    ));
+    let missing = dep(&workspace, "a", "z");
+    assert!(matches!(
+      workspace.add_dep(missing),
+      Err(DepError::Missing(..))
+    ));
+    // a blocks b in the work file and b blocks x across files.
+    let cycle = dep(&workspace, "x", "a");
+    assert!(matches!(workspace.add_dep(cycle), Err(DepError::Cycle(..))));
+    assert_eq!(workspace.deps.len(), 1);
+  }
+
+  #[test]
+  fn checks_deps_within_files_for_cycles_across_files() -> Result<(), DepError>
+  {
+    let mut workspace = workspace();
+    workspace.add_dep(dep(&workspace, "a", "x"))?;
+    workspace.add_dep(dep(&workspace, "x", "c"))?;
+    // c -> a stays within the work file but closes a -> x -> c.
+    let within = dep(&workspace, "c", "a");
+    assert!(matches!(
+      workspace.check_dep(&within),
+      Err(DepError::Cycle(..))
+    ));
+    assert_eq!(workspace.check_dep(&dep(&workspace, "a", "c")), Ok(()));
+    Ok(())
+  }
+
+  #[test]
+  fn removes_deps_with_their_file() -> Result<(), DepError> {
+    let mut workspace = workspace();
+    workspace.add_dep(dep(&workspace, "b", "x"))?;
+    let home = workspace.files.get(1).map(|file| file.id.clone());
+    assert!(home.is_some_and(|home| workspace.remove_file(&home)));
+    assert_eq!(workspace.files.len(), 1);
+    assert_eq!(workspace.deps, []);
+    Ok(())
+  }
+
+  #[test]
+  fn combines_loaded_files() -> Result<(), DepError> {
+    let mut workspace = workspace();
+    workspace.add_dep(dep(&workspace, "b", "x"))?;
+    let combined = workspace.combined();
+    let boards = combined
+      .boards
+      .iter()
+      .map(|board| board.title.as_str())
+      .collect::<Vec<_>>();
+    assert_eq!(boards, ["Work", "Home"]);
+    assert_eq!(combined.deps.len(), 1);
+    assert_eq!(
+      combined.boards.first().map(|board| board.deps.len()),
+      Some(1)
+    );
+    assert_eq!(combined.preferences.capacity, 6.0);
+    // Boards carry the preferences of their file.
+    assert!(combined
+      .boards
+      .iter()
+      .all(|board| board.preferences.capacity.is_some()));
+
+    workspace.capacity = Some(2.0);
+    assert_eq!(workspace.combined().preferences.capacity, 2.0);
+    Ok(())
+  }
+
+  #[test]
+  fn suggests_across_files() -> Result<(), DepError> {
+    let mut workspace = workspace();
+    workspace.add_dep(dep(&workspace, "c", "x"))?;
+    let today = NaiveDate::from_ymd_opt(2025, 1, 10).unwrap_or_default();
+    let suggested = suggest_workspace(&workspace, today);
+    let x = dep(&workspace, "c", "x").right;
+    assert!(!suggested.contains(&x));
+    assert!(suggested.contains(&dep(&workspace, "c", "x").left));
+
+    // Once c is done x is no longer blocked.
+    if let Some(file) = workspace.files.first_mut() {
+      file.loaded = file.loaded.take().map(|mut loaded| {
+        let c = issue_ref(&loaded, "c");
+        loaded.move_issue(c, list_ref(&loaded, "Done"), 0);
+        loaded
+      });
+    }
+    workspace.restage();
+    assert!(suggest_workspace(&workspace, today)
+      .iter()
+      .any(|issue_ref| issue_ref.issue == x.issue));
+    Ok(())
+  }
+
+  #[test]
+  fn loads_files_and_restages_deps() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = temp_dir()?;
+    let mut workspace = workspace();
+    workspace.add_dep(dep(&workspace, "b", "x"))?;
+    for file in workspace.files.iter_mut() {
+      file.path = dir.join(&file.path);
+      if let Some(loaded) = file.loaded.as_mut() {
+        loaded.path = file.path.clone();
+        loaded.save()?;
+      }
+    }
+    fs::write(dir.join("broken.toml"), "boards = [")?;
+    workspace.add_file(dir.join("broken.toml"));
+    workspace.path = dir.join("workspace.toml");
+    workspace.save()?;
+
+    let mut loaded = HyphaWorkspace::load(&workspace.path)?;
+    assert!(loaded.files.iter().all(|file| file.loaded.is_none()));
+    // The dep still points at b in Todo while b has moved to Doing.
+    if let Some(work) = loaded.files.first() {
+      let mut moved = HyphaFile::load(&work.path)?;
+      let b = issue_ref(&moved, "b");
+      moved.move_issue(b, list_ref(&moved, "Doing"), 0);
+      moved.save()?;
+    }
+    loaded.load_files();
+
+    let states = loaded
+      .files
+      .iter()
+      .map(|file| file.loaded.is_some())
+      .collect::<Vec<_>>();
+    assert_eq!(states, [true, true, false]);
+    assert_eq!(loaded.deps.first().map(|dep| dep.left.stage), Some(1));
+    assert_eq!(
+      loaded
+        .files
+        .first()
+        .and_then(|file| file.loaded.as_ref())
+        .map(|file| &file.path),
+      workspace.files.first().map(|file| &file.path)
+    );
+    fs::remove_dir_all(dir)?;
+    Ok(())
+  }
+}
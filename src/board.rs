@@ -1,68 +1,50 @@
+use chrono::Local;
 use dioxus::prelude::*;
-use rnglib::{Language, RNG};
-use serde::{Deserialize, Serialize};
-
-use crate::container::HyphaContainer;
-use crate::context::{HyphaBoardContext, HyphaFileContext};
-use crate::dep::HyphaDep;
-use crate::item::HyphaItem;
-use crate::list::HyphaList;
-use crate::r#ref::{
-  HyphaFileBoardRef, HyphaFileIssueRef, HyphaFileListRef, HyphaRef,
-  WithHyphaRef,
+use hypha_core::{
+  graph::HyphaDepGraph,
+  id::HyphaId,
+  query::{HyphaQuery, HyphaQueryScope},
+  r#ref::{HyphaFileBoardRef, HyphaFileListRef, HyphaRef, WithHyphaRef},
+  view::HyphaView,
 };
+use std::collections::HashSet;
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct HyphaBoard {
-  pub title: String,
-  #[serde(default)]
-  pub lists: Vec<HyphaList>,
-  #[serde(default)]
-  pub deps: Vec<HyphaDep<HyphaFileIssueRef>>,
-}
-
-impl HyphaItem for HyphaBoard {
-  fn title(&self) -> &str {
-    &self.title
-  }
-}
-
-impl HyphaContainer for HyphaBoard {
-  type Item = HyphaList;
-  type Ref = HyphaFileIssueRef;
-
-  fn items(&self) -> &Vec<Self::Item> {
-    &self.lists
-  }
-
-  fn items_mut(&mut self) -> &mut Vec<Self::Item> {
-    &mut self.lists
-  }
-}
-
-impl Default for HyphaBoard {
-  fn default() -> Self {
-    let rng = RNG::from(&Language::Roman);
-    let title = rng.generate_name();
-    Self {
-      title,
-      lists: vec![
-        HyphaList::default(),
-        HyphaList::default(),
-        HyphaList::default(),
-      ],
-      deps: vec![],
-    }
-  }
-}
+use crate::context::{HyphaFileContext, HyphaIssueContext};
 
 #[component]
 pub fn Component(board_ref: HyphaFileBoardRef) -> Element {
   let mut file_context = use_context::<HyphaFileContext>();
-  let mut board_context = use_context::<HyphaBoardContext>();
+  let mut issue_context = use_context::<HyphaIssueContext>();
   let mut edit = use_signal(|| false);
+  let mut dragged = use_signal(|| Option::<HyphaFileListRef>::None);
+  let mut filter = use_signal(String::new);
+  let mut view = use_signal(|| Option::<HyphaId>::None);
+  let cycles = use_memo(move || {
+    HyphaDepGraph::new(&file_context.get())
+      .cycles()
+      .into_iter()
+      .flatten()
+      .collect::<HashSet<_>>()
+  });
+
+  let file = file_context.get();
+  let query = HyphaQuery::parse(&filter());
+  let matching = match &query {
+    Ok(query) if !query.is_empty() => {
+      let scope = HyphaQueryScope::new(&file, Local::now().date_naive());
+      Some(
+        query
+          .filter(&scope)
+          .into_iter()
+          .filter(|issue_ref| issue_ref.board == board_ref.board)
+          .map(|issue_ref| issue_ref.issue)
+          .collect::<HashSet<HyphaId>>(),
+      )
+    }
+    _ => None,
+  };
 
-  let board = match board_ref.get_item_from_container(&file_context.get()) {
+  let board = match board_ref.get_item_from_container(&file) {
     Some(board) => board.clone(),
     None => {
       return rsx! {
@@ -88,10 +70,6 @@ pub fn Component(board_ref: HyphaFileBoardRef) -> Element {
                 let board_ref = board_ref.clone();
                 let board = board.clone();
                 move |e: Event<FormData>| {
-                  let mut new_board_ref = board_ref.clone();
-                  new_board_ref.board = e.value();
-                  board_context.set(new_board_ref);
-
                   let mut board = board.clone();
                   board.title = e.value();
                   file_context.update_board(WithHyphaRef {
@@ -117,31 +95,132 @@ pub fn Component(board_ref: HyphaFileBoardRef) -> Element {
           {board.title.clone()}
         }
       }
+      select {
+        onchange: move |e: Event<FormData>| {
+          let value = e.value();
+          *view.write() = file_context
+            .get()
+            .views
+            .into_iter()
+            .find(|saved| saved.id.as_str() == value)
+            .map(|saved| saved.id);
+        },
+        option {
+          value: "",
+          selected: view().is_none(),
+          "Board"
+        }
+        for saved in file.views.iter() {
+          option {
+            value: saved.id.to_string(),
+            selected: view().as_ref() == Some(&saved.id),
+            {saved.title.clone()}
+          }
+        }
+      }
     }
-    div {
-      class: "w-full flex flex-row justify-center items-start",
-      for (idx, list) in board.lists.iter().enumerate() {
-        div {
-          class: "flex flex-col border-1px w-64 p-2 m-2",
-          crate::list::Component {
-            list_ref: HyphaFileListRef {
-              list: list.title.clone(),
-              stage: idx,
-              board: board.title.clone()
-            }
+    if let Some(id) = view() {
+      crate::view::Component {
+        id: id,
+        on_remove: move |_| {
+          *view.write() = None;
+        }
+      }
+    } else {
+      div {
+        class: "w-full flex flex-col items-center",
+        input {
+          placeholder: "Filter like tag:bug blocked:false due<7d",
+          value: filter(),
+          oninput: move |e: Event<FormData>| {
+            *filter.write() = e.value();
           }
         }
-        if idx == board.lists.len() - 1 {
-          button {
-            onclick: {
-              let board_ref = board_ref.clone();
+        button {
+          disabled: !matches!(&query, Ok(query) if !query.is_empty()),
+          onclick: move |_| {
+            let count = file_context.get().views.len().saturating_add(1);
+            let title = format!("View {count}");
+            let id = file_context.add_view(HyphaView::new(title, filter()));
+            *view.write() = Some(id);
+            *filter.write() = String::new();
+          },
+          "Save as view"
+        }
+        if let Err(e) = &query {
+          p {
+            class: "error",
+            {e.to_string()}
+          }
+        }
+      }
+      div {
+        class: "w-full flex flex-row justify-center items-start",
+        for (idx, list) in board.lists.iter().enumerate() {
+          div {
+            class: "flex flex-col border-1px w-64 p-2 m-2",
+            draggable: "true",
+            ondragstart: {
+              let list_ref = HyphaFileListRef {
+                list: list.id.clone(),
+                stage: idx,
+                board: board.id.clone()
+              };
               move |_| {
-                file_context.add_list(board_ref.clone());
+                *dragged.write() = Some(list_ref.clone());
               }
             },
-            "Add"
+            ondragend: move |_| {
+              *dragged.write() = None;
+            },
+            ondragover: move |e: Event<DragData>| {
+              e.prevent_default();
+            },
+            ondrop: move |e: Event<DragData>| {
+              e.prevent_default();
+              let Some(list_ref) = dragged() else {
+                return;
+              };
+              *dragged.write() = None;
+              if file_context.move_list(list_ref, idx).is_none() {
+                return;
+              }
+              if let Some(mut selected) = issue_context.get() {
+                let file = file_context.get();
+                let stage = file
+                  .boards
+                  .iter()
+                  .find(|board| board.id == selected.board)
+                  .and_then(|board| {
+                    board.lists.iter().position(|list| list.id == selected.list)
+                  });
+                if let Some(stage) = stage {
+                  selected.stage = stage;
+                  issue_context.set(Some(selected));
+                }
+              }
+            },
+            crate::list::Component {
+              list_ref: HyphaFileListRef {
+                list: list.id.clone(),
+                stage: idx,
+                board: board.id.clone()
+              },
+              matching: matching.clone(),
+              cycles
+            }
           }
         }
+        button {
+          onclick: {
+            let board_ref = board_ref.clone();
+            let title = format!("List {}", board.lists.len().saturating_add(1));
+            move |_| {
+              file_context.add_list(board_ref.clone(), title.clone());
+            }
+          },
+          "Add"
+        }
       }
     }
   }
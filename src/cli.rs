@@ -0,0 +1,1002 @@
+use chrono::{Local, NaiveDate};
+use clap::{Parser, Subcommand};
+use hypha_core::{
+  board::HyphaBoard,
+  dep::HyphaDep,
+  file::{FileError, HyphaFile},
+  graph::{DepError, HyphaDepGraph},
+  id::HyphaId,
+  issue::{self, HyphaPriority},
+  query::{HyphaQuery, HyphaQueryScope},
+  r#ref::{
+    HyphaFileBoardRef, HyphaFileIssueRef, HyphaFileListRef, HyphaRef,
+    WithHyphaRef,
+  },
+  suggest::{suggest, suggest_workspace},
+  workspace::{HyphaWorkspace, HyphaWorkspaceIssueRef},
+};
+use serde::Serialize;
+use std::{
+  io::{self, Write},
+  path::PathBuf,
+  process::ExitCode,
+};
+use thiserror::Error;
+
+/// Scriptable access to hypha boards.
+///
+/// Boards, lists and issues can be selected by id or by title.
+#[derive(Parser, Debug)]
+#[command(name = "hypha", version)]
+struct Cli {
+  /// Hypha file to use instead of HYPHA_FILE or the default one.
+  #[arg(long, global = true)]
+  file: Option<PathBuf>,
+
+  /// Print JSON instead of tables.
+  #[arg(long, global = true)]
+  json: bool,
+
+  #[command(subcommand)]
+  command: Command,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+  /// List, add or remove boards.
+  Boards {
+    #[command(subcommand)]
+    command: Option<BoardsCommand>,
+  },
+  /// List, add or remove lists.
+  Lists {
+    /// Only list the lists of this board.
+    #[arg(long)]
+    board: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<ListsCommand>,
+  },
+  /// List, add, move, remove or show issues.
+  Issues {
+    /// Only list the issues of this board.
+    #[arg(long)]
+    board: Option<String>,
+
+    /// Only list the issues of this list.
+    #[arg(long)]
+    list: Option<String>,
+
+    /// Only list the issues matching a query like `tag:bug blocked:false
+    /// due<7d`.
+    #[arg(long)]
+    query: Option<HyphaQuery>,
+
+    #[command(subcommand)]
+    command: Option<IssuesCommand>,
+  },
+  /// List, add or remove deps.
+  Deps {
+    #[command(subcommand)]
+    command: Option<DepsCommand>,
+  },
+  /// List the issues suggested to focus on.
+  Suggest {
+    /// Day to suggest issues for instead of today.
+    #[arg(long)]
+    date: Option<NaiveDate>,
+
+    /// Suggest across every file of the workspace instead of just the file.
+    #[arg(long)]
+    workspace: bool,
+  },
+  /// List, add or remove the files of the workspace.
+  Workspace {
+    #[command(subcommand)]
+    command: Option<WorkspaceCommand>,
+  },
+  /// Replace the contents of the file with those of another one.
+  ///
+  /// Use it to move boards between storage formats, for example from a TOML
+  /// file into a SQLite database picked with `--file boards.db`.
+  Import {
+    /// Hypha file or directory to import.
+    from: PathBuf,
+  },
+}
+
+#[derive(Subcommand, Debug)]
+enum BoardsCommand {
+  /// Add a board.
+  Add { title: String },
+  /// Remove a board.
+  Rm { board: String },
+}
+
+#[derive(Subcommand, Debug)]
+enum ListsCommand {
+  /// Add a list at the end of a board.
+  Add { board: String, title: String },
+  /// Remove a list.
+  Rm { board: String, list: String },
+}
+
+#[derive(Subcommand, Debug)]
+enum IssuesCommand {
+  /// Add an issue at the end of a list.
+  Add {
+    board: String,
+    list: String,
+    title: String,
+
+    #[arg(long)]
+    description: Option<String>,
+
+    #[arg(long, value_parser = parse_priority)]
+    priority: Option<HyphaPriority>,
+
+    #[arg(long, value_parser = parse_estimate)]
+    estimate: Option<f64>,
+
+    #[arg(long)]
+    due: Option<NaiveDate>,
+
+    #[arg(long = "tag")]
+    tags: Vec<String>,
+
+    #[arg(long)]
+    assignee: Option<String>,
+  },
+  /// Move an issue to a list.
+  Mv {
+    issue: String,
+    list: String,
+
+    /// Board of the list instead of the board of the issue.
+    #[arg(long)]
+    board: Option<String>,
+
+    /// Position in the list instead of the end.
+    #[arg(long)]
+    index: Option<usize>,
+  },
+  /// Remove an issue.
+  Rm { issue: String },
+  /// Show an issue with its deps.
+  Show { issue: String },
+}
+
+#[derive(Subcommand, Debug)]
+enum WorkspaceCommand {
+  /// Add a hypha file or directory to the workspace.
+  Add { path: PathBuf },
+  /// Remove a file and its deps on other files from the workspace.
+  Rm { file: String },
+}
+
+#[derive(Subcommand, Debug)]
+enum DepsCommand {
+  /// Make an issue block another one.
+  Add { blocker: String, blocked: String },
+  /// Stop an issue from blocking another one.
+  Rm { blocker: String, blocked: String },
+}
+
+#[derive(Error, Debug)]
+enum CliError {
+  #[error("File error: {0}")]
+  File(#[from] FileError),
+
+  #[error("Dependency error: {0}")]
+  Dep(#[from] DepError),
+
+  #[error("No {0} matches '{1}'")]
+  NotFound(&'static str, String),
+
+  #[error("More than one {0} is titled '{1}', use its id instead")]
+  Ambiguous(&'static str, String),
+
+  #[error("Output error: {0}")]
+  Io(#[from] io::Error),
+
+  #[error("JSON error: {0}")]
+  Json(#[from] serde_json::Error),
+}
+
+/// Runs the command line interface and returns the process exit code.
+pub fn run() -> ExitCode {
+  let cli = Cli::parse();
+  match execute(cli) {
+    Ok(()) => ExitCode::SUCCESS,
+    Err(e) => {
+      let _ = writeln!(io::stderr(), "Error: {e}");
+      ExitCode::FAILURE
+    }
+  }
+}
+
+fn execute(cli: Cli) -> Result<(), CliError> {
+  let output = Output { json: cli.json };
+  if let Command::Workspace { command } = cli.command {
+    return workspace(&output, command);
+  }
+
+  let path = match cli.file {
+    Some(path) => path,
+    None => HyphaFile::path()?,
+  };
+  let mut file = HyphaFile::load(&path)?;
+  file.path = path;
+
+  let changed = match cli.command {
+    Command::Boards { command } => boards(&mut file, &output, command)?,
+    Command::Lists { board, command } => {
+      lists(&mut file, &output, board, command)?
+    }
+    Command::Issues {
+      board,
+      list,
+      query,
+      command,
+    } => issues(&mut file, &output, board, list, query, command)?,
+    Command::Deps { command } => deps(&mut file, &output, command)?,
+    Command::Suggest { date, workspace } => {
+      let today = date.unwrap_or_else(|| Local::now().date_naive());
+      if workspace {
+        let mut workspace = HyphaWorkspace::load(&HyphaWorkspace::path()?)?;
+        workspace.load_files();
+        let rows = suggest_workspace(&workspace, today)
+          .iter()
+          .filter_map(|issue_ref| WorkspaceIssueRow::new(&workspace, issue_ref))
+          .collect::<Vec<_>>();
+        output.rows(&rows)?;
+      } else {
+        let rows = suggest(&file, today)
+          .iter()
+          .filter_map(|issue_ref| IssueRow::new(&file, issue_ref))
+          .collect::<Vec<_>>();
+        output.rows(&rows)?;
+      }
+      false
+    }
+    Command::Import { from } => {
+      file = HyphaFile {
+        path: file.path.clone(),
+        ..HyphaFile::load(&from)?
+      };
+      let rows = file.boards.iter().map(BoardRow::new).collect::<Vec<_>>();
+      output.rows(&rows)?;
+      true
+    }
+    Command::Workspace { .. } => false,
+  };
+
+  if changed {
+    file.save()?;
+  }
+  Ok(())
+}
+
+fn boards(
+  file: &mut HyphaFile,
+  output: &Output,
+  command: Option<BoardsCommand>,
+) -> Result<bool, CliError> {
+  match command {
+    None => {
+      let rows = file.boards.iter().map(BoardRow::new).collect::<Vec<_>>();
+      output.rows(&rows)?;
+      Ok(false)
+    }
+    Some(BoardsCommand::Add { title }) => {
+      let board_ref = file.add_board(title.clone());
+      let board = board_ref
+        .get_item_from_container(file)
+        .ok_or_else(|| CliError::NotFound("board", title))?;
+      output.rows(&[BoardRow::new(board)])?;
+      Ok(true)
+    }
+    Some(BoardsCommand::Rm { board }) => {
+      let board_ref = find_board(file, &board)?;
+      Ok(file.remove_board(board_ref))
+    }
+  }
+}
+
+fn lists(
+  file: &mut HyphaFile,
+  output: &Output,
+  board: Option<String>,
+  command: Option<ListsCommand>,
+) -> Result<bool, CliError> {
+  match command {
+    None => {
+      let board_ref =
+        board.map(|board| find_board(file, &board)).transpose()?;
+      let rows = file
+        .boards
+        .iter()
+        .filter(|board| {
+          board_ref
+            .as_ref()
+            .is_none_or(|board_ref| board_ref.board == board.id)
+        })
+        .flat_map(|board| {
+          board
+            .lists
+            .iter()
+            .enumerate()
+            .map(move |(stage, list)| ListRow {
+              id: list.id.clone(),
+              title: list.title.clone(),
+              board: board.title.clone(),
+              stage,
+              issues: list.issues.len(),
+            })
+        })
+        .collect::<Vec<_>>();
+      output.rows(&rows)?;
+      Ok(false)
+    }
+    Some(ListsCommand::Add { board, title }) => {
+      let board_ref = find_board(file, &board)?;
+      let list_ref = file
+        .add_list(board_ref, title.clone())
+        .ok_or_else(|| CliError::NotFound("board", board.clone()))?;
+      let board = HyphaFileBoardRef {
+        board: list_ref.board.clone(),
+      }
+      .get_item_from_container(file)
+      .map(|board| board.title.clone())
+      .unwrap_or(board);
+      output.rows(&[ListRow {
+        id: list_ref.list,
+        title,
+        board,
+        stage: list_ref.stage,
+        issues: 0,
+      }])?;
+      Ok(true)
+    }
+    Some(ListsCommand::Rm { board, list }) => {
+      let board_ref = find_board(file, &board)?;
+      let list_ref = find_list(file, &board_ref, &list)?;
+      Ok(file.remove_list(list_ref))
+    }
+  }
+}
+
+fn issues(
+  file: &mut HyphaFile,
+  output: &Output,
+  board: Option<String>,
+  list: Option<String>,
+  query: Option<HyphaQuery>,
+  command: Option<IssuesCommand>,
+) -> Result<bool, CliError> {
+  match command {
+    None => {
+      let board_ref =
+        board.map(|board| find_board(file, &board)).transpose()?;
+      let list_id = match (&board_ref, list) {
+        (Some(board_ref), Some(list)) => {
+          Some(find_list(file, board_ref, &list)?.list)
+        }
+        (None, Some(list)) => Some(find_any_list(file, &list)?.list),
+        (_, None) => None,
+      };
+      let scope = HyphaQueryScope::new(file, Local::now().date_naive());
+      let rows = scope
+        .graph()
+        .issues()
+        .filter(|issue_ref| {
+          board_ref
+            .as_ref()
+            .is_none_or(|board_ref| board_ref.board == issue_ref.board)
+            && list_id.as_ref().is_none_or(|list| *list == issue_ref.list)
+            && query.as_ref().is_none_or(|query| {
+              issue_ref
+                .get_item_from_container(scope.file())
+                .is_some_and(|issue| query.matches(&scope, issue, issue_ref))
+            })
+        })
+        .filter_map(|issue_ref| IssueRow::new(file, issue_ref))
+        .collect::<Vec<_>>();
+      output.rows(&rows)?;
+      Ok(false)
+    }
+    Some(IssuesCommand::Add {
+      board,
+      list,
+      title,
+      description,
+      priority,
+      estimate,
+      due,
+      tags,
+      assignee,
+    }) => {
+      let board_ref = find_board(file, &board)?;
+      let list_ref = find_list(file, &board_ref, &list)?;
+      let issue_ref = file
+        .add_issue(list_ref)
+        .ok_or_else(|| CliError::NotFound("list", list.clone()))?;
+      let mut issue = issue_ref
+        .get_item_from_container(file)
+        .cloned()
+        .ok_or_else(|| CliError::NotFound("issue", title.clone()))?;
+      issue.title = title;
+      if let Some(description) = description {
+        issue.description = description;
+      }
+      issue.priority = priority;
+      issue.estimate = estimate;
+      issue.due = due;
+      issue.tags = tags;
+      issue.assignee = assignee;
+      file.update_issue(WithHyphaRef {
+        item: issue,
+        r#ref: issue_ref.clone(),
+      });
+      if let Some(row) = IssueRow::new(file, &issue_ref) {
+        output.rows(&[row])?;
+      }
+      Ok(true)
+    }
+    Some(IssuesCommand::Mv {
+      issue,
+      list,
+      board,
+      index,
+    }) => {
+      let issue_ref = find_issue(file, &issue)?;
+      let board_ref = match board {
+        Some(board) => find_board(file, &board)?,
+        None => HyphaFileBoardRef {
+          board: issue_ref.board.clone(),
+        },
+      };
+      let list_ref = find_list(file, &board_ref, &list)?;
+      let moved = file
+        .move_issue(issue_ref, list_ref, index.unwrap_or(usize::MAX))
+        .ok_or_else(|| CliError::NotFound("issue", issue.clone()))?;
+      if let Some(row) = IssueRow::new(file, &moved) {
+        output.rows(&[row])?;
+      }
+      Ok(true)
+    }
+    Some(IssuesCommand::Rm { issue }) => {
+      let issue_ref = find_issue(file, &issue)?;
+      Ok(file.remove_issue(issue_ref))
+    }
+    Some(IssuesCommand::Show { issue }) => {
+      let issue_ref = find_issue(file, &issue)?;
+      let details = IssueDetails::new(file, &issue_ref)
+        .ok_or_else(|| CliError::NotFound("issue", issue.clone()))?;
+      output.details(&details)?;
+      Ok(false)
+    }
+  }
+}
+
+fn deps(
+  file: &mut HyphaFile,
+  output: &Output,
+  command: Option<DepsCommand>,
+) -> Result<bool, CliError> {
+  match command {
+    None => {
+      let rows = file
+        .deps
+        .iter()
+        .chain(file.boards.iter().flat_map(|board| board.deps.iter()))
+        .map(|dep| DepRow::new(file, dep))
+        .collect::<Vec<_>>();
+      output.rows(&rows)?;
+      Ok(false)
+    }
+    Some(DepsCommand::Add { blocker, blocked }) => {
+      let dep = HyphaDep {
+        left: find_issue(file, &blocker)?,
+        right: find_issue(file, &blocked)?,
+      };
+      check_workspace_dep(file, &dep)?;
+      file.add_dep(dep.clone())?;
+      output.rows(&[DepRow::new(file, &dep)])?;
+      Ok(true)
+    }
+    Some(DepsCommand::Rm { blocker, blocked }) => {
+      let dep = HyphaDep {
+        left: find_issue(file, &blocker)?,
+        right: find_issue(file, &blocked)?,
+      };
+      Ok(file.remove_dep(dep))
+    }
+  }
+}
+
+// A dep within a file of the workspace can close a cycle through its other
+// files.
+fn check_workspace_dep(
+  file: &HyphaFile,
+  dep: &HyphaDep<HyphaFileIssueRef>,
+) -> Result<(), CliError> {
+  let mut workspace = HyphaWorkspace::load(&HyphaWorkspace::path()?)?;
+  let Some(id) = workspace
+    .files
+    .iter()
+    .find(|other| other.path == file.path)
+    .map(|other| other.id.clone())
+  else {
+    return Ok(());
+  };
+  workspace.load_files();
+  workspace.update_file(&id, file.clone());
+  workspace.check_dep(&HyphaDep {
+    left: HyphaWorkspaceIssueRef::new(id.clone(), dep.left.clone()),
+    right: HyphaWorkspaceIssueRef::new(id, dep.right.clone()),
+  })?;
+  Ok(())
+}
+
+fn workspace(
+  output: &Output,
+  command: Option<WorkspaceCommand>,
+) -> Result<(), CliError> {
+  let mut workspace = HyphaWorkspace::load(&HyphaWorkspace::path()?)?;
+  match command {
+    None => {}
+    Some(WorkspaceCommand::Add { path }) => {
+      workspace.add_file(path);
+      workspace.save()?;
+    }
+    Some(WorkspaceCommand::Rm { file }) => {
+      let id = select(
+        "file",
+        &file,
+        workspace
+          .files
+          .iter()
+          .map(|file| (file.id.clone(), file.name.clone(), file.id.clone())),
+      )?;
+      workspace.remove_file(&id);
+      workspace.save()?;
+    }
+  }
+
+  let rows = workspace
+    .files
+    .iter()
+    .map(|file| WorkspaceFileRow {
+      id: file.id.clone(),
+      name: file.name.clone(),
+      path: file.path.display().to_string(),
+    })
+    .collect::<Vec<_>>();
+  output.rows(&rows)
+}
+
+fn parse_priority(value: &str) -> Result<HyphaPriority, String> {
+  HyphaPriority::parse(value).ok_or_else(|| {
+    let all = HyphaPriority::ALL.map(HyphaPriority::as_str).join(", ");
+    format!("expected one of {all}")
+  })
+}
+
+fn parse_estimate(value: &str) -> Result<f64, String> {
+  issue::parse_estimate(value)
+    .ok_or_else(|| "expected a number of zero or more".to_string())
+}
+
+// Picks the candidate whose id is the selector or else the only one titled
+// like it.
+fn select<T: Clone>(
+  kind: &'static str,
+  selector: &str,
+  candidates: impl Iterator<Item = (HyphaId, String, T)>,
+) -> Result<T, CliError> {
+  let mut titled = Vec::new();
+  for (id, title, candidate) in candidates {
+    if id.as_str() == selector {
+      return Ok(candidate);
+    }
+    if title == selector {
+      titled.push(candidate);
+    }
+  }
+  match titled.as_slice() {
+    [candidate] => Ok(candidate.clone()),
+    [] => Err(CliError::NotFound(kind, selector.to_string())),
+    _ => Err(CliError::Ambiguous(kind, selector.to_string())),
+  }
+}
+
+fn find_board(
+  file: &HyphaFile,
+  selector: &str,
+) -> Result<HyphaFileBoardRef, CliError> {
+  select(
+    "board",
+    selector,
+    file.boards.iter().map(|board| {
+      let board_ref = HyphaFileBoardRef {
+        board: board.id.clone(),
+      };
+      (board.id.clone(), board.title.clone(), board_ref)
+    }),
+  )
+}
+
+fn find_list(
+  file: &HyphaFile,
+  board_ref: &HyphaFileBoardRef,
+  selector: &str,
+) -> Result<HyphaFileListRef, CliError> {
+  let board = board_ref
+    .get_item_from_container(file)
+    .ok_or_else(|| CliError::NotFound("board", board_ref.board.to_string()))?;
+  select(
+    "list",
+    selector,
+    board.lists.iter().enumerate().map(|(stage, list)| {
+      let list_ref = HyphaFileListRef {
+        list: list.id.clone(),
+        stage,
+        board: board.id.clone(),
+      };
+      (list.id.clone(), list.title.clone(), list_ref)
+    }),
+  )
+}
+
+fn find_any_list(
+  file: &HyphaFile,
+  selector: &str,
+) -> Result<HyphaFileListRef, CliError> {
+  select(
+    "list",
+    selector,
+    file.boards.iter().flat_map(|board| {
+      board.lists.iter().enumerate().map(|(stage, list)| {
+        let list_ref = HyphaFileListRef {
+          list: list.id.clone(),
+          stage,
+          board: board.id.clone(),
+        };
+        (list.id.clone(), list.title.clone(), list_ref)
+      })
+    }),
+  )
+}
+
+fn find_issue(
+  file: &HyphaFile,
+  selector: &str,
+) -> Result<HyphaFileIssueRef, CliError> {
+  let graph = HyphaDepGraph::new(file);
+  select(
+    "issue",
+    selector,
+    graph.issues().filter_map(|issue_ref| {
+      let issue = issue_ref.get_item_from_container(file)?;
+      Some((issue.id.clone(), issue.title.clone(), issue_ref.clone()))
+    }),
+  )
+}
+
+struct Output {
+  json: bool,
+}
+
+impl Output {
+  fn rows<R: Row>(&self, rows: &[R]) -> Result<(), CliError> {
+    let mut stdout = io::stdout().lock();
+    if self.json {
+      serde_json::to_writer_pretty(&mut stdout, rows)?;
+      writeln!(stdout)?;
+      return Ok(());
+    }
+
+    let cells = rows.iter().map(Row::cells).collect::<Vec<_>>();
+    let widths = R::HEADERS
+      .iter()
+      .enumerate()
+      .map(|(column, header)| {
+        cells
+          .iter()
+          .filter_map(|row| row.get(column))
+          .map(|cell| cell.chars().count())
+          .fold(header.len(), usize::max)
+      })
+      .collect::<Vec<_>>();
+    let headers = R::HEADERS
+      .iter()
+      .map(|header| header.to_string())
+      .collect::<Vec<_>>();
+    for row in std::iter::once(&headers).chain(cells.iter()) {
+      let line = row
+        .iter()
+        .zip(widths.iter().copied())
+        .map(|(cell, width)| format!("{cell:width$}"))
+        .collect::<Vec<_>>()
+        .join("  ");
+      writeln!(stdout, "{}", line.trim_end())?;
+    }
+    Ok(())
+  }
+
+  fn details(&self, details: &IssueDetails) -> Result<(), CliError> {
+    let mut stdout = io::stdout().lock();
+    if self.json {
+      serde_json::to_writer_pretty(&mut stdout, details)?;
+      writeln!(stdout)?;
+      return Ok(());
+    }
+
+    let row = &details.issue;
+    for (header, cell) in IssueRow::HEADERS.iter().zip(row.cells()) {
+      writeln!(stdout, "{header:<12}{cell}")?;
+    }
+    if !details.description.is_empty() {
+      writeln!(stdout)?;
+      writeln!(stdout, "{}", details.description)?;
+    }
+    for (header, labels) in [
+      ("Blocked by", &details.blocked_by),
+      ("Blocks", &details.blocks),
+    ] {
+      writeln!(stdout)?;
+      writeln!(stdout, "{header}:")?;
+      for label in labels {
+        writeln!(stdout, "  {label}")?;
+      }
+    }
+    Ok(())
+  }
+}
+
+trait Row: Serialize {
+  const HEADERS: &'static [&'static str];
+
+  fn cells(&self) -> Vec<String>;
+}
+
+#[derive(Serialize)]
+struct BoardRow {
+  id: HyphaId,
+  title: String,
+  lists: usize,
+  issues: usize,
+}
+
+impl BoardRow {
+  fn new(board: &HyphaBoard) -> Self {
+    Self {
+      id: board.id.clone(),
+      title: board.title.clone(),
+      lists: board.lists.len(),
+      issues: board
+        .lists
+        .iter()
+        .map(|list| list.issues.len())
+        .fold(0, usize::saturating_add),
+    }
+  }
+}
+
+impl Row for BoardRow {
+  const HEADERS: &'static [&'static str] = &["Id", "Title", "Lists", "Issues"];
+
+  fn cells(&self) -> Vec<String> {
+    vec![
+      self.id.to_string(),
+      self.title.clone(),
+      self.lists.to_string(),
+      self.issues.to_string(),
+    ]
+  }
+}
+
+#[derive(Serialize)]
+struct ListRow {
+  id: HyphaId,
+  title: String,
+  board: String,
+  stage: usize,
+  issues: usize,
+}
+
+impl Row for ListRow {
+  const HEADERS: &'static [&'static str] =
+    &["Id", "Title", "Board", "Stage", "Issues"];
+
+  fn cells(&self) -> Vec<String> {
+    vec![
+      self.id.to_string(),
+      self.title.clone(),
+      self.board.clone(),
+      self.stage.to_string(),
+      self.issues.to_string(),
+    ]
+  }
+}
+
+#[derive(Serialize)]
+struct IssueRow {
+  id: HyphaId,
+  title: String,
+  board: String,
+  list: String,
+  priority: Option<HyphaPriority>,
+  estimate: Option<f64>,
+  due: Option<NaiveDate>,
+  assignee: Option<String>,
+  tags: Vec<String>,
+}
+
+impl IssueRow {
+  fn new(file: &HyphaFile, issue_ref: &HyphaFileIssueRef) -> Option<Self> {
+    let board_ref = HyphaFileBoardRef {
+      board: issue_ref.board.clone(),
+    };
+    let list_ref = HyphaFileListRef {
+      list: issue_ref.list.clone(),
+      stage: issue_ref.stage,
+      board: issue_ref.board.clone(),
+    };
+    let issue = issue_ref.get_item_from_container(file)?;
+    Some(Self {
+      id: issue.id.clone(),
+      title: issue.title.clone(),
+      board: board_ref.get_item_from_container(file)?.title.clone(),
+      list: list_ref.get_item_from_container(file)?.title.clone(),
+      priority: issue.priority,
+      estimate: issue.estimate,
+      due: issue.due,
+      assignee: issue.assignee.clone(),
+      tags: issue.tags.clone(),
+    })
+  }
+}
+
+impl Row for IssueRow {
+  const HEADERS: &'static [&'static str] = &[
+    "Id", "Title", "Board", "List", "Priority", "Estimate", "Due", "Assignee",
+    "Tags",
+  ];
+
+  fn cells(&self) -> Vec<String> {
+    vec![
+      self.id.to_string(),
+      self.title.clone(),
+      self.board.clone(),
+      self.list.clone(),
+      self
+        .priority
+        .map(|priority| priority.as_str().to_string())
+        .unwrap_or_default(),
+      self
+        .estimate
+        .map(|estimate| estimate.to_string())
+        .unwrap_or_default(),
+      self.due.map(|due| due.to_string()).unwrap_or_default(),
+      self.assignee.clone().unwrap_or_default(),
+      self.tags.join(", "),
+    ]
+  }
+}
+
+#[derive(Serialize)]
+struct WorkspaceIssueRow {
+  file: String,
+  #[serde(flatten)]
+  issue: IssueRow,
+}
+
+impl WorkspaceIssueRow {
+  fn new(
+    workspace: &HyphaWorkspace,
+    issue_ref: &HyphaWorkspaceIssueRef,
+  ) -> Option<Self> {
+    let file = workspace.file(&issue_ref.file)?;
+    Some(Self {
+      file: file.name.clone(),
+      issue: IssueRow::new(file.loaded.as_ref()?, &issue_ref.file_ref())?,
+    })
+  }
+}
+
+impl Row for WorkspaceIssueRow {
+  const HEADERS: &'static [&'static str] = &[
+    "File", "Id", "Title", "Board", "List", "Priority", "Estimate", "Due",
+    "Assignee", "Tags",
+  ];
+
+  fn cells(&self) -> Vec<String> {
+    let mut cells = vec![self.file.clone()];
+    cells.extend(self.issue.cells());
+    cells
+  }
+}
+
+#[derive(Serialize)]
+struct IssueDetails {
+  #[serde(flatten)]
+  issue: IssueRow,
+  description: String,
+  blocked_by: Vec<String>,
+  blocks: Vec<String>,
+}
+
+impl IssueDetails {
+  fn new(file: &HyphaFile, issue_ref: &HyphaFileIssueRef) -> Option<Self> {
+    let graph = HyphaDepGraph::new(file);
+    let labels = |ids: &[HyphaId]| {
+      ids
+        .iter()
+        .filter_map(|id| graph.issue(id))
+        .filter_map(|issue_ref| issue::label(file, issue_ref))
+        .collect::<Vec<_>>()
+    };
+    Some(Self {
+      issue: IssueRow::new(file, issue_ref)?,
+      description: issue_ref.get_item_from_container(file)?.description.clone(),
+      blocked_by: labels(graph.prerequisites(&issue_ref.issue)),
+      blocks: labels(graph.dependents(&issue_ref.issue)),
+    })
+  }
+}
+
+#[derive(Serialize)]
+struct DepRow {
+  blocker: HyphaId,
+  blocker_title: String,
+  blocked: HyphaId,
+  blocked_title: String,
+}
+
+impl DepRow {
+  fn new(file: &HyphaFile, dep: &HyphaDep<HyphaFileIssueRef>) -> Self {
+    let title = |issue_ref: &HyphaFileIssueRef| {
+      issue_ref
+        .get_item_from_container(file)
+        .map(|issue| issue.title.clone())
+        .unwrap_or_default()
+    };
+    Self {
+      blocker: dep.left.issue.clone(),
+      blocker_title: title(&dep.left),
+      blocked: dep.right.issue.clone(),
+      blocked_title: title(&dep.right),
+    }
+  }
+}
+
+impl Row for DepRow {
+  const HEADERS: &'static [&'static str] =
+    &["Blocker", "Title", "Blocked", "Title"];
+
+  fn cells(&self) -> Vec<String> {
+    vec![
+      self.blocker.to_string(),
+      self.blocker_title.clone(),
+      self.blocked.to_string(),
+      self.blocked_title.clone(),
+    ]
+  }
+}
+
+#[derive(Serialize)]
+struct WorkspaceFileRow {
+  id: HyphaId,
+  name: String,
+  path: String,
+}
+
+impl Row for WorkspaceFileRow {
+  const HEADERS: &'static [&'static str] = &["Id", "Name", "Path"];
+
+  fn cells(&self) -> Vec<String> {
+    vec![self.id.to_string(), self.name.clone(), self.path.clone()]
+  }
+}
@@ -1,17 +1,35 @@
+use dioxus::logger::tracing::*;
 use dioxus::prelude::*;
-
-use crate::board::HyphaBoard;
-use crate::file::HyphaFile;
-use crate::issue::HyphaIssue;
-use crate::list::HyphaList;
-use crate::r#ref::{
-  HyphaFileBoardRef, HyphaFileIssueRef, HyphaFileListRef, HyphaRef,
-  WithHyphaRef,
+use hypha_core::board::HyphaBoard;
+use hypha_core::dep::HyphaDep;
+use hypha_core::file::HyphaFile;
+use hypha_core::graph::DepError;
+use hypha_core::history::HyphaHistory;
+use hypha_core::id::HyphaId;
+use hypha_core::issue::HyphaIssue;
+use hypha_core::list::HyphaList;
+use hypha_core::preferences::HyphaPreferences;
+use hypha_core::r#ref::{
+  HyphaFileBoardRef, HyphaFileIssueRef, HyphaFileListRef, WithHyphaRef,
 };
+use hypha_core::search::{HyphaSearchHit, HyphaSearchIndex};
+use hypha_core::view::HyphaView;
+use hypha_core::workspace::{HyphaWorkspace, HyphaWorkspaceIssueRef};
+use std::path::PathBuf;
+
+use crate::file::HyphaSaveStatus;
 
 #[derive(Debug, Clone, Copy)]
 pub struct HyphaFileContext {
   signal: Signal<HyphaFile>,
+  history: Signal<HyphaHistory>,
+  index: Signal<HyphaSearchIndex>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct HyphaWorkspaceContext {
+  signal: Signal<HyphaWorkspace>,
+  active: Signal<Option<HyphaId>>,
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -24,235 +42,271 @@ pub struct HyphaIssueContext {
   signal: Signal<Option<HyphaFileIssueRef>>,
 }
 
+#[derive(Debug, Clone, Copy)]
+pub struct HyphaSaveContext {
+  signal: Signal<HyphaSaveStatus>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct HyphaDragContext {
+  signal: Signal<Option<HyphaFileIssueRef>>,
+}
+
 impl HyphaFileContext {
-  pub fn new(signal: Signal<HyphaFile>) -> Self {
-    Self { signal }
+  pub fn new(
+    signal: Signal<HyphaFile>,
+    history: Signal<HyphaHistory>,
+    index: Signal<HyphaSearchIndex>,
+  ) -> Self {
+    Self {
+      signal,
+      history,
+      index,
+    }
   }
 
   pub fn get(&self) -> HyphaFile {
     (self.signal)()
   }
 
-  pub fn add_board(&mut self) {
-    let board = HyphaBoard::default();
-    let board_ref = HyphaFileBoardRef {
-      board: board.title.clone(),
-    };
-    if board_ref.get_item_from_container(&self.get()).is_some() {
-      return;
-    }
+  pub fn can_undo(&self) -> bool {
+    self.history.read().can_undo()
+  }
 
-    let mut writer = self.signal.write();
-    writer.boards.push(board);
+  pub fn can_redo(&self) -> bool {
+    self.history.read().can_redo()
   }
 
-  pub fn update_board(
-    &mut self,
-    board: WithHyphaRef<HyphaBoard, HyphaFileBoardRef>,
-  ) {
-    let board_ref = HyphaFileBoardRef {
-      board: board.item.title.clone(),
-    };
-    if board_ref.get_item_from_container(&self.get()).is_some() {
-      return;
-    }
+  pub fn search(&self, query: &str) -> Vec<HyphaSearchHit> {
+    self.index.read().search(query)
+  }
 
-    let mut writer = self.signal.write();
-    for dep in writer.deps.iter_mut() {
-      if dep.left.board == board.r#ref.board {
-        dep.left.board = board.item.title.clone();
-      }
-      if dep.right.board == board.r#ref.board {
-        dep.right.board = board.item.title.clone();
-      }
-    }
-    if let Some(container_board) =
-      board.r#ref.get_item_from_container_mut(&mut writer)
-    {
-      *container_board = board.item;
+  pub fn undo(&mut self) {
+    let current = self.get();
+    if let Some(snapshot) = self.history.write().undo(current) {
+      self.set(snapshot);
     }
   }
 
-  pub fn remove_board(&mut self, board_ref: HyphaFileBoardRef) {
-    let mut writer = self.signal.write();
-    let removed = board_ref.remove_item_from_container(&mut *writer);
-    if removed {
-      writer.deps.retain(|dep| {
-        dep.left.board != board_ref.board && dep.right.board != board_ref.board
-      });
+  pub fn redo(&mut self) {
+    let current = self.get();
+    if let Some(snapshot) = self.history.write().redo(current) {
+      self.set(snapshot);
     }
   }
 
-  pub fn add_list(&mut self, board_ref: HyphaFileBoardRef) {
-    let list = HyphaList::default();
-    let list_ref = HyphaFileListRef {
-      list: list.title.clone(),
-      stage: 0,
-      board: board_ref.board.clone(),
-    };
-    if list_ref.get_item_from_container(&self.get()).is_some() {
-      return;
-    }
+  pub fn replace(&mut self, file: HyphaFile) {
+    self.record(None);
+    self.set(file);
+  }
+
+  // Whole files carry marks that do not match the saved rows, so they are
+  // dropped and the next save compares everything.
+  fn set(&mut self, mut file: HyphaFile) {
+    file.changes.reset();
+    *self.signal.write() = file;
+    self.reindex(None);
+  }
 
-    let mut writer = self.signal.write();
-    if let Some(board) = board_ref.get_item_from_container_mut(&mut *writer) {
-      board.lists.push(HyphaList::default());
+  fn record(&mut self, item: Option<HyphaId>) {
+    let snapshot = self.get();
+    self.history.write().record(snapshot, item);
+  }
+
+  fn apply<T>(
+    &mut self,
+    item: Option<HyphaId>,
+    operation: impl FnOnce(&mut HyphaFile) -> T,
+  ) -> T {
+    self.record(item.clone());
+    let result = operation(&mut *self.signal.write());
+    self.reindex(item.as_ref());
+    result
+  }
+
+  // Items edited in place are reindexed on their own while anything that can
+  // add, remove or move items syncs the whole index.
+  fn reindex(&mut self, item: Option<&HyphaId>) {
+    let file = self.signal.peek();
+    match item {
+      Some(id) => self.index.write().update(&file, id),
+      None => self.index.write().sync(&file),
     }
   }
 
-  pub fn update_list(
+  pub fn update_preferences(&mut self, preferences: HyphaPreferences) {
+    self.apply(None, |file| file.update_preferences(preferences));
+  }
+
+  pub fn add_view(&mut self, view: HyphaView) -> HyphaId {
+    self.apply(None, |file| file.add_view(view))
+  }
+
+  pub fn update_view(&mut self, view: HyphaView) {
+    let id = view.id.clone();
+    self.apply(Some(id), |file| file.update_view(view));
+  }
+
+  pub fn remove_view(&mut self, id: &HyphaId) {
+    self.apply(None, |file| file.remove_view(id));
+  }
+
+  pub fn add_board(&mut self, title: String) {
+    self.apply(None, |file| file.add_board(title));
+  }
+
+  pub fn update_board(
     &mut self,
-    list: WithHyphaRef<HyphaList, HyphaFileListRef>,
+    board: WithHyphaRef<HyphaBoard, HyphaFileBoardRef>,
   ) {
-    let list_ref = HyphaFileListRef {
-      list: list.item.title.clone(),
-      stage: 0,
-      board: list.r#ref.board.clone(),
-    };
-    if list_ref.get_item_from_container(&self.get()).is_some() {
-      return;
-    }
+    let id = board.item.id.clone();
+    self.apply(Some(id), |file| file.update_board(board));
+  }
 
-    let mut writer = self.signal.write();
-    for dep in writer.deps.iter_mut() {
-      if dep.left.board == list.r#ref.board && dep.left.list == list.r#ref.list
-      {
-        dep.left.list = list.item.title.clone();
-      }
-      if dep.right.board == list.r#ref.board
-        && dep.right.list == list.r#ref.list
-      {
-        dep.right.list = list.item.title.clone();
-      }
-    }
+  pub fn remove_board(&mut self, board_ref: HyphaFileBoardRef) {
+    self.apply(None, |file| file.remove_board(board_ref));
+  }
 
-    let board_ref = HyphaFileBoardRef {
-      board: list.r#ref.board.clone(),
-    };
-    if let Some(board) = board_ref.get_item_from_container_mut(&mut writer) {
-      for dep in board.deps.iter_mut() {
-        if dep.left.list == list.r#ref.list {
-          dep.left.list = list.item.title.clone();
-        }
-        if dep.right.list == list.r#ref.list {
-          dep.right.list = list.item.title.clone();
-        }
-      }
-    }
+  pub fn add_list(&mut self, board_ref: HyphaFileBoardRef, title: String) {
+    self.apply(None, |file| file.add_list(board_ref, title));
+  }
 
-    if let Some(container_list) =
-      list.r#ref.get_item_from_container_mut(&mut writer)
-    {
-      *container_list = list.item;
-    }
+  pub fn update_list(
+    &mut self,
+    list: WithHyphaRef<HyphaList, HyphaFileListRef>,
+  ) {
+    let id = list.item.id.clone();
+    self.apply(Some(id), |file| file.update_list(list));
   }
 
   pub fn remove_list(&mut self, list_ref: HyphaFileListRef) {
-    let mut writer = self.signal.write();
-    let removed = list_ref.remove_item_from_container(&mut *writer);
-    if removed {
-      let board_ref = HyphaFileBoardRef {
-        board: list_ref.board.clone(),
-      };
-      if let Some(board) = board_ref.get_item_from_container_mut(&mut *writer) {
-        board.deps.retain(|dep| {
-          dep.left.list != list_ref.list && dep.right.list != list_ref.list
-        });
-      }
-      writer.deps.retain(|dep| {
-        dep.left.list != list_ref.list && dep.right.list != list_ref.list
-      });
-    }
+    self.apply(None, |file| file.remove_list(list_ref));
   }
 
-  pub fn add_issue(&mut self, list_ref: HyphaFileListRef) {
-    let issue = HyphaIssue::default();
-    let issue_ref = HyphaFileIssueRef {
-      issue: issue.title.clone(),
-      list: list_ref.list.clone(),
-      stage: 0,
-      board: list_ref.board.clone(),
-    };
-    if issue_ref.get_item_from_container(&self.get()).is_some() {
-      return;
-    }
+  pub fn move_list(
+    &mut self,
+    list_ref: HyphaFileListRef,
+    index: usize,
+  ) -> Option<HyphaFileListRef> {
+    self.apply(None, |file| file.move_list(list_ref, index))
+  }
 
-    let mut writer = self.signal.write();
-    if let Some(list) = list_ref.get_item_from_container_mut(&mut *writer) {
-      list.issues.push(issue);
-    }
+  pub fn add_issue(&mut self, list_ref: HyphaFileListRef) {
+    self.apply(None, |file| file.add_issue(list_ref));
   }
 
   pub fn update_issue(
     &mut self,
     issue: WithHyphaRef<HyphaIssue, HyphaFileIssueRef>,
   ) {
-    let issue_ref = HyphaFileIssueRef {
-      issue: issue.item.title.clone(),
-      list: issue.r#ref.list.clone(),
-      stage: 0,
-      board: issue.r#ref.board.clone(),
-    };
-    if issue_ref.get_item_from_container(&self.get()).is_some() {
-      return;
-    }
-
-    let mut writer = self.signal.write();
-    for dep in writer.deps.iter_mut() {
-      if dep.left.board == issue.r#ref.board
-        && dep.left.list == issue.r#ref.list
-        && dep.left.issue == issue.r#ref.issue
-      {
-        dep.left.issue = issue.item.title.clone();
-      }
-      if dep.right.board == issue.r#ref.board
-        && dep.right.list == issue.r#ref.list
-        && dep.right.list == issue.r#ref.issue
-      {
-        dep.right.issue = issue.item.title.clone();
-      }
-    }
-    let board_ref = HyphaFileBoardRef {
-      board: issue.r#ref.board.clone(),
-    };
-    if let Some(board) = board_ref.get_item_from_container_mut(&mut writer) {
-      for dep in board.deps.iter_mut() {
-        if dep.left.list == issue.r#ref.list
-          && dep.left.issue == issue.r#ref.issue
-        {
-          dep.left.issue = issue.item.title.clone();
-        }
-        if dep.right.list == issue.r#ref.list
-          && dep.right.issue == issue.r#ref.issue
-        {
-          dep.right.issue = issue.item.title.clone();
-        }
-      }
-    }
-    if let Some(container_issue) =
-      issue.r#ref.get_item_from_container_mut(&mut *writer)
-    {
-      *container_issue = issue.item;
-    }
+    let id = issue.item.id.clone();
+    self.apply(Some(id), |file| file.update_issue(issue));
   }
 
   pub fn remove_issue(&mut self, issue_ref: HyphaFileIssueRef) {
-    let mut writer = self.signal.write();
-    let removed = issue_ref.remove_item_from_container(&mut *writer);
-    if removed {
-      let board_ref = HyphaFileBoardRef {
-        board: issue_ref.board.clone(),
-      };
-      if let Some(board) = board_ref.get_item_from_container_mut(&mut *writer) {
-        board.deps.retain(|dep| {
-          dep.left.issue != issue_ref.issue
-            && dep.right.issue != issue_ref.issue
-        });
-      }
-      writer.deps.retain(|dep| {
-        dep.left.issue != issue_ref.issue && dep.right.issue != issue_ref.issue
-      });
+    self.apply(None, |file| file.remove_issue(issue_ref));
+  }
+
+  pub fn move_issue(
+    &mut self,
+    from: HyphaFileIssueRef,
+    to: HyphaFileListRef,
+    index: usize,
+  ) -> Option<HyphaFileIssueRef> {
+    self.apply(None, |file| file.move_issue(from, to, index))
+  }
+
+  pub fn add_dep(
+    &mut self,
+    dep: HyphaDep<HyphaFileIssueRef>,
+  ) -> Result<(), DepError> {
+    let mut file = self.get();
+    file.add_dep(dep)?;
+    self.replace(file);
+    Ok(())
+  }
+
+  pub fn remove_dep(&mut self, dep: HyphaDep<HyphaFileIssueRef>) {
+    self.apply(None, |file| file.remove_dep(dep));
+  }
+}
+
+impl HyphaWorkspaceContext {
+  pub fn new(
+    signal: Signal<HyphaWorkspace>,
+    active: Signal<Option<HyphaId>>,
+  ) -> Self {
+    Self { signal, active }
+  }
+
+  pub fn get(&self) -> HyphaWorkspace {
+    (self.signal)()
+  }
+
+  pub fn active(&self) -> Option<HyphaId> {
+    (self.active)()
+  }
+
+  /// Id and path of the file being edited.
+  pub fn active_path(&self) -> Option<(HyphaId, PathBuf)> {
+    let active = self.active()?;
+    let workspace = self.signal.read();
+    let file = workspace.file(&active)?;
+    Some((file.id.clone(), file.path.clone()))
+  }
+
+  pub fn set_active(&mut self, id: HyphaId) {
+    *self.active.write() = Some(id);
+  }
+
+  /// Keeps the copy of a file used for deps and suggestions across files up
+  /// to date with its edits.
+  pub fn update_file(&mut self, id: &HyphaId, file: HyphaFile) {
+    self.signal.write().update_file(id, file);
+  }
+
+  pub fn add_file(&mut self, path: PathBuf) -> HyphaId {
+    let id = self.signal.write().add_file(path);
+    self.save();
+    id
+  }
+
+  pub fn remove_file(&mut self, id: &HyphaId) {
+    self.signal.write().remove_file(id);
+    self.save();
+  }
+
+  pub fn check_dep(
+    &self,
+    dep: &HyphaDep<HyphaWorkspaceIssueRef>,
+  ) -> Result<(), DepError> {
+    self.signal.read().check_dep(dep)
+  }
+
+  pub fn add_dep(
+    &mut self,
+    dep: HyphaDep<HyphaWorkspaceIssueRef>,
+  ) -> Result<(), DepError> {
+    self.signal.write().add_dep(dep)?;
+    self.save();
+    Ok(())
+  }
+
+  pub fn remove_dep(&mut self, dep: HyphaDep<HyphaWorkspaceIssueRef>) {
+    self.signal.write().remove_dep(dep);
+    self.save();
+  }
+
+  fn save(&self) {
+    let workspace = self.signal.peek().clone();
+    if workspace.path.as_os_str().is_empty() {
+      return;
     }
+    spawn(async move {
+      if let Err(err) = workspace.save_async().await {
+        error!("Failed to save workspace: {err}");
+      }
+    });
   }
 }
 
@@ -270,6 +324,34 @@ impl HyphaBoardContext {
   }
 }
 
+impl HyphaSaveContext {
+  pub fn new(signal: Signal<HyphaSaveStatus>) -> Self {
+    Self { signal }
+  }
+
+  pub fn get(&self) -> HyphaSaveStatus {
+    (self.signal)()
+  }
+
+  pub fn set(&mut self, status: HyphaSaveStatus) {
+    *self.signal.write() = status;
+  }
+}
+
+impl HyphaDragContext {
+  pub fn new(signal: Signal<Option<HyphaFileIssueRef>>) -> Self {
+    Self { signal }
+  }
+
+  pub fn get(&self) -> Option<HyphaFileIssueRef> {
+    (self.signal)()
+  }
+
+  pub fn set(&mut self, issue_ref: Option<HyphaFileIssueRef>) {
+    *self.signal.write() = issue_ref;
+  }
+}
+
 impl HyphaIssueContext {
   pub fn new(signal: Signal<Option<HyphaFileIssueRef>>) -> Self {
     Self { signal }
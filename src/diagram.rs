@@ -0,0 +1,273 @@
+use dioxus::prelude::*;
+use hypha_core::{
+  file::HyphaFile,
+  graph::HyphaDepGraph,
+  id::HyphaId,
+  r#ref::{HyphaFileBoardRef, HyphaFileIssueRef, HyphaRef},
+};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::{
+  context::{HyphaBoardContext, HyphaFileContext, HyphaIssueContext},
+  issue,
+};
+
+const PALETTE: [&str; 6] = [
+  "#6366f1", "#0ea5e9", "#10b981", "#f59e0b", "#ec4899", "#8b5cf6",
+];
+const CRITICAL: &str = "#ef4444";
+const EDGE: &str = "#9ca3af";
+const NODE_WIDTH: f64 = 160.0;
+const NODE_HEIGHT: f64 = 40.0;
+const COLUMN_WIDTH: f64 = 220.0;
+const ROW_HEIGHT: f64 = 70.0;
+const MARGIN: f64 = 20.0;
+const TITLE_LENGTH: usize = 18;
+
+struct Node {
+  issue_ref: HyphaFileIssueRef,
+  title: String,
+  x: f64,
+  y: f64,
+  text_x: f64,
+  text_y: f64,
+  fill: &'static str,
+  stroke: &'static str,
+}
+
+struct Edge {
+  x1: f64,
+  y1: f64,
+  x2: f64,
+  y2: f64,
+  stroke: &'static str,
+  stroke_width: &'static str,
+}
+
+struct Layout {
+  nodes: Vec<Node>,
+  edges: Vec<Edge>,
+  width: f64,
+  height: f64,
+}
+
+// Nodes caught in a cycle keep the deepest layer their acyclic prerequisites
+// pushed them to.
+fn layers(graph: &HyphaDepGraph, ids: &[HyphaId]) -> HashMap<HyphaId, usize> {
+  let mut indegrees = ids
+    .iter()
+    .map(|id| (id.clone(), graph.prerequisites(id).len()))
+    .collect::<HashMap<_, _>>();
+  let mut layers = HashMap::<HyphaId, usize>::new();
+  let mut queue = ids
+    .iter()
+    .filter(|id| graph.prerequisites(id).is_empty())
+    .cloned()
+    .collect::<VecDeque<_>>();
+
+  while let Some(id) = queue.pop_front() {
+    let layer = *layers.entry(id.clone()).or_insert(0);
+    for dependent in graph.dependents(&id) {
+      let next = layer.saturating_add(1);
+      let entry = layers.entry(dependent.clone()).or_insert(next);
+      *entry = (*entry).max(next);
+      if let Some(indegree) = indegrees.get_mut(dependent) {
+        *indegree = indegree.saturating_sub(1);
+        if *indegree == 0 {
+          queue.push_back(dependent.clone());
+        }
+      }
+    }
+  }
+
+  for id in ids {
+    layers.entry(id.clone()).or_insert(0);
+  }
+  layers
+}
+
+fn critical_path(
+  graph: &HyphaDepGraph,
+  ids: &[HyphaId],
+  layers: &HashMap<HyphaId, usize>,
+) -> Vec<HyphaId> {
+  let Some(mut current) =
+    ids.iter().rev().max_by_key(|id| layers.get(*id)).cloned()
+  else {
+    return Vec::new();
+  };
+
+  let mut path = vec![current.clone()];
+  while let Some(previous) = graph.prerequisites(&current).iter().find(|id| {
+    layers.get(*id).and_then(|layer| layer.checked_add(1))
+      == layers.get(&current).copied()
+  }) {
+    current = previous.clone();
+    path.push(current.clone());
+  }
+  path.reverse();
+  path
+}
+
+fn layout(file: &HyphaFile, graph: &HyphaDepGraph) -> Layout {
+  let ids = graph
+    .issues()
+    .map(|issue_ref| issue_ref.issue.clone())
+    .filter(|id| {
+      !graph.prerequisites(id).is_empty() || !graph.dependents(id).is_empty()
+    })
+    .collect::<Vec<_>>();
+  let layers = layers(graph, &ids);
+  let path = critical_path(graph, &ids, &layers);
+  let critical_nodes = path.iter().collect::<HashSet<_>>();
+  let critical_edges = path.windows(2).collect::<HashSet<_>>();
+
+  let mut rows = HashMap::<usize, usize>::new();
+  let mut positions = HashMap::<HyphaId, (f64, f64)>::new();
+  let mut nodes = Vec::new();
+  for id in ids.iter() {
+    let Some(issue_ref) = graph.issue(id) else {
+      continue;
+    };
+    let Some(issue) = issue_ref.get_item_from_container(file) else {
+      continue;
+    };
+
+    let layer = layers.get(id).copied().unwrap_or_default();
+    let row = rows.entry(layer).or_default();
+    let x = MARGIN + COLUMN_WIDTH * layer as f64;
+    let y = MARGIN + ROW_HEIGHT * *row as f64;
+    *row = row.saturating_add(1);
+    positions.insert(id.clone(), (x, y));
+
+    let fill = issue_ref
+      .stage
+      .checked_rem(PALETTE.len())
+      .and_then(|idx| PALETTE.get(idx))
+      .copied()
+      .unwrap_or(EDGE);
+    nodes.push(Node {
+      issue_ref: issue_ref.clone(),
+      title: issue.title.chars().take(TITLE_LENGTH).collect(),
+      x,
+      y,
+      text_x: x + 8.0,
+      text_y: y + NODE_HEIGHT / 2.0 + 5.0,
+      fill,
+      stroke: if critical_nodes.contains(id) {
+        CRITICAL
+      } else {
+        fill
+      },
+    });
+  }
+
+  let mut edges = Vec::new();
+  for id in ids.iter() {
+    for dependent in graph.dependents(id) {
+      let (Some((left_x, left_y)), Some((right_x, right_y))) =
+        (positions.get(id), positions.get(dependent))
+      else {
+        continue;
+      };
+      let critical =
+        critical_edges.contains([id.clone(), dependent.clone()].as_slice());
+      edges.push(Edge {
+        x1: left_x + NODE_WIDTH,
+        y1: left_y + NODE_HEIGHT / 2.0,
+        x2: *right_x,
+        y2: right_y + NODE_HEIGHT / 2.0,
+        stroke: if critical { CRITICAL } else { EDGE },
+        stroke_width: if critical { "3" } else { "1.5" },
+      });
+    }
+  }
+
+  let columns = layers
+    .values()
+    .max()
+    .map_or(0, |layer| layer.saturating_add(1));
+  let rows = rows.values().max().copied().unwrap_or_default();
+  Layout {
+    nodes,
+    edges,
+    width: MARGIN * 2.0 + COLUMN_WIDTH * columns as f64,
+    height: MARGIN * 2.0 + ROW_HEIGHT * rows as f64,
+  }
+}
+
+#[component]
+pub fn Diagram() -> Element {
+  let file_context = use_context::<HyphaFileContext>();
+  let mut board_context = use_context::<HyphaBoardContext>();
+  let mut issue_context = use_context::<HyphaIssueContext>();
+
+  let file = file_context.get();
+  let graph = HyphaDepGraph::new(&file);
+  let Layout {
+    nodes,
+    edges,
+    width,
+    height,
+  } = layout(&file, &graph);
+
+  rsx! {
+    if nodes.is_empty() {
+      p {
+        class: "text-center",
+        "No dependencies yet"
+      }
+    } else {
+      div {
+        class: "w-full overflow-auto",
+        svg {
+          width: "{width}",
+          height: "{height}",
+          for edge in edges {
+            line {
+              x1: "{edge.x1}",
+              y1: "{edge.y1}",
+              x2: "{edge.x2}",
+              y2: "{edge.y2}",
+              stroke: edge.stroke,
+              stroke_width: edge.stroke_width,
+            }
+          }
+          for node in nodes {
+            g {
+              class: "cursor-pointer",
+              onclick: {
+                let issue_ref = node.issue_ref.clone();
+                move |_| {
+                  board_context.set(HyphaFileBoardRef {
+                    board: issue_ref.board.clone()
+                  });
+                  issue_context.set(Some(issue_ref.clone()));
+                }
+              },
+              rect {
+                x: "{node.x}",
+                y: "{node.y}",
+                width: "{NODE_WIDTH}",
+                height: "{NODE_HEIGHT}",
+                rx: "6",
+                fill: node.fill,
+                stroke: node.stroke,
+                stroke_width: "3",
+              }
+              text {
+                x: "{node.text_x}",
+                y: "{node.text_y}",
+                fill: "white",
+                {node.title.clone()}
+              }
+            }
+          }
+        }
+      }
+    }
+    if let Some(issue_ref) = issue_context.get() {
+      issue::Component { issue_ref: issue_ref }
+    }
+  }
+}
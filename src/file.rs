@@ -1,60 +1,46 @@
+use dioxus::logger::tracing::*;
 use dioxus::prelude::*;
-use directories::ProjectDirs;
-use itertools::Itertools;
-use log::{error, info, warn};
-use serde::{Deserialize, Serialize};
+use hypha_core::{
+  file::{expand, FileError, HyphaFile},
+  recent::HyphaRecent,
+  template::HyphaTemplate,
+};
 use std::{
-  env,
   path::{Path, PathBuf},
   str::FromStr,
 };
-use thiserror::Error;
-use tokio::{fs, io};
+use tokio::fs;
 
-use crate::{
-  board::HyphaBoard, container::HyphaContainer, dep::HyphaDep,
-  r#ref::HyphaFileIssueRef,
-};
+use crate::context::HyphaSaveContext;
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct HyphaFile {
-  #[serde(default)]
-  pub boards: Vec<HyphaBoard>,
-  #[serde(default)]
-  pub deps: Vec<HyphaDep<HyphaFileIssueRef>>,
-  #[serde(skip)]
-  pub path: PathBuf,
+#[derive(Debug, Clone, PartialEq)]
+pub enum HyphaSaveStatus {
+  Saved,
+  Pending,
+  Saving,
+  Failed(String),
 }
 
-impl HyphaContainer for HyphaFile {
-  type Item = HyphaBoard;
-  type Ref = HyphaFileIssueRef;
-
-  fn items(&self) -> &Vec<Self::Item> {
-    &self.boards
-  }
-
-  fn items_mut(&mut self) -> &mut Vec<Self::Item> {
-    &mut self.boards
-  }
-}
-
-impl Default for HyphaFile {
-  fn default() -> Self {
-    let boards = vec![HyphaBoard::default()];
-    if let Ok(path) = HyphaFile::path() {
-      HyphaFile {
-        path,
-        boards,
-        deps: vec![],
-      }
-    } else {
-      HyphaFile {
-        boards,
-        path: PathBuf::new(),
-        deps: vec![],
+#[component]
+pub fn SaveStatus() -> Element {
+  let save_context = use_context::<HyphaSaveContext>();
+
+  match save_context.get() {
+    HyphaSaveStatus::Saved => rsx! {
+      span { "Saved" }
+    },
+    HyphaSaveStatus::Pending => rsx! {
+      span { "Unsaved changes" }
+    },
+    HyphaSaveStatus::Saving => rsx! {
+      span { "Saving..." }
+    },
+    HyphaSaveStatus::Failed(err) => rsx! {
+      span {
+        class: "error",
+        "Failed to save: {err}"
       }
-    }
+    },
   }
 }
 
@@ -103,170 +89,171 @@ pub fn Edit(file: HyphaFile, on_change: EventHandler<HyphaFile>) -> Element {
   }
 }
 
-impl HyphaFile {
-  #[allow(dead_code, reason = "I like these")]
-  pub fn load(path: &Path) -> Result<HyphaFile, FileError> {
-    info!("Attempting to load config from: {}", path.display());
-    match std::fs::read_to_string(path) {
-      Ok(content) => {
-        let mut file: HyphaFile = toml::from_str(&content)?;
-        file.path = path.to_path_buf();
-        file.uniq();
-        info!("Hypha file loaded successfully.");
-        Ok(file)
+/// Picks the file to open from the recent ones, a typed path or a new file
+/// created from a template.
+#[component]
+pub fn Start(error: Option<String>, on_open: EventHandler<PathBuf>) -> Element {
+  let mut input = use_signal(String::new);
+  let mut template = use_signal(|| HyphaTemplate::Kanban);
+  let mut create_error = use_signal(|| Option::<String>::None);
+  let mut recent = use_resource(load_recent);
+
+  let path = expand(&input());
+  let recent = match &*recent.read() {
+    Some(Ok(recent)) => recent.clone(),
+    Some(Err(e)) => {
+      warn!("Failed to load recent files: {e}");
+      Vec::new()
+    }
+    None => Vec::new(),
+  };
+
+  rsx! {
+    div {
+      class: "flex flex-col gap-2 p-2",
+      if let Some(error) = error {
+        p {
+          class: "error",
+          "Failed to load the file: {error}"
+        }
       }
-      Err(e) if e.kind() == io::ErrorKind::NotFound => {
-        warn!(
-          "Hypha file not found at {}, creating default.",
-          path.display()
-        );
-        Ok(HyphaFile::default())
+      if !recent.is_empty() {
+        h3 { "Recent" }
       }
-      Err(e) => {
-        error!("Failed to read hypha file: {e}");
-        Err(FileError::Io(e))
+      for (recent_path, file) in recent {
+        div {
+          class: "flex flex-row gap-2 border-1px p-2",
+          div {
+            class: "grow cursor-pointer",
+            onclick: {
+              let recent_path = recent_path.clone();
+              move |_| {
+                on_open(recent_path.clone());
+              }
+            },
+            match file {
+              Some(file) => rsx! {
+                Details { file: file }
+              },
+              None => rsx! {
+                p { {recent_path.display().to_string()} }
+                p { "Missing" }
+              },
+            }
+          }
+          span {
+            class: "cursor-pointer",
+            onclick: {
+              let recent_path = recent_path.clone();
+              move |_| {
+                let recent_path = recent_path.clone();
+                spawn(async move {
+                  if let Err(e) = forget(&recent_path).await {
+                    error!("Failed to remove recent file: {e}");
+                  }
+                  recent.restart();
+                });
+              }
+            },
+            "X"
+          }
+        }
       }
-    }
-  }
-
-  pub async fn load_async(path: &Path) -> Result<HyphaFile, FileError> {
-    info!("Attempting to load config from: {}", path.display());
-    match fs::read_to_string(path).await {
-      Ok(content) => {
-        let mut file: HyphaFile = toml::from_str(&content)?;
-        file.path = path.to_path_buf();
-        file.uniq();
-        info!("Hypha file loaded successfully.");
-        Ok(file)
+      h3 { "Open or create" }
+      p {
+        input {
+          placeholder: "Path to a hypha file or directory",
+          value: input(),
+          oninput: move |e: Event<FormData>| {
+            *input.write() = e.value();
+            *create_error.write() = None;
+          }
+        }
       }
-      Err(e) if e.kind() == io::ErrorKind::NotFound => {
-        warn!(
-          "Hypha file not found at {}, creating default.",
-          path.display()
-        );
-        Ok(HyphaFile::default())
+      if let Err(e) = &path {
+        if !input().is_empty() {
+          p {
+            class: "error",
+            {e.to_string()}
+          }
+        }
+      }
+      div {
+        class: "flex flex-row gap-2",
+        button {
+          disabled: path.is_err(),
+          onclick: {
+            let path = path.as_ref().ok().cloned();
+            move |_| {
+              if let Some(path) = path.clone() {
+                on_open(path);
+              }
+            }
+          },
+          "Open"
+        }
+        select {
+          onchange: move |e: Event<FormData>| {
+            if let Some(value) = HyphaTemplate::parse(&e.value()) {
+              *template.write() = value;
+            }
+          },
+          for value in HyphaTemplate::ALL {
+            option {
+              value: value.as_str(),
+              selected: template() == value,
+              {value.label()}
+            }
+          }
+        }
+        button {
+          disabled: path.is_err(),
+          onclick: {
+            let path = path.as_ref().ok().cloned();
+            move |_| {
+              let Some(path) = path.clone() else {
+                return;
+              };
+              spawn(async move {
+                match template().create_async(&path).await {
+                  Ok(_) => on_open(path),
+                  Err(e) => *create_error.write() = Some(e.to_string()),
+                }
+              });
+            }
+          },
+          "Create"
+        }
       }
-      Err(e) => {
-        error!("Failed to read hypha file: {e}");
-        Err(FileError::Io(e))
+      if let Some(error) = create_error() {
+        p {
+          class: "error",
+          "Failed to create the file: {error}"
+        }
       }
     }
   }
+}
 
-  pub fn path() -> Result<PathBuf, FileError> {
-    if let Ok(path_str) = env::var("HYPHA_FILE") {
-      let expanded_path = shellexpand::full(&path_str)
-        .map_err(|e| FileError::Expansion(path_str.clone(), e.to_string()))?;
-      info!("Using hypha file path from HYPHA_FILE env var: {expanded_path}");
-      return Ok(PathBuf::from(expanded_path.to_string()));
-    }
-
-    if let Some(proj_dirs) = ProjectDirs::from("com", "HyphaApp", "Hypha") {
-      let data_dir = proj_dirs.data_local_dir();
-      let default_path = data_dir.join("hypha.toml");
-      info!("Using default hypha file path: {}", default_path.display());
-      Ok(default_path)
+// Recent files with their contents, or none when they no longer exist.
+async fn load_recent() -> Result<Vec<(PathBuf, Option<HyphaFile>)>, FileError> {
+  let recent = HyphaRecent::load_async(&HyphaRecent::path()?).await?;
+  let mut files = Vec::new();
+  for recent_file in recent.files {
+    let file = if fs::try_exists(&recent_file.path).await.unwrap_or(false) {
+      HyphaFile::load_async(&recent_file.path).await.ok()
     } else {
-      error!(
-        "Could not determine project directory for default hypha file path."
-      );
-      Err(FileError::Directory)
-    }
-  }
-
-  #[allow(dead_code, reason = "I like these")]
-  pub fn reload(&mut self) -> Result<(), FileError> {
-    let loaded = Self::load(&self.path)?;
-    *self = loaded;
-    Ok(())
-  }
-
-  #[allow(dead_code, reason = "I like these")]
-  pub async fn reload_async(&mut self) -> Result<(), FileError> {
-    let loaded = Self::load_async(&self.path).await?;
-    *self = loaded;
-    Ok(())
-  }
-
-  pub fn save(&self) -> Result<(), FileError> {
-    let path = self.path.as_path();
-
-    info!("Attempting to save hypha file to: {}", path.display());
-
-    if let Some(parent_dir) = path.parent() {
-      std::fs::create_dir_all(parent_dir)?;
-      info!("Ensured hypha directory exists: {}", parent_dir.display());
-    }
-
-    let toml_string = toml::to_string_pretty(self)?;
-    std::fs::write(path, toml_string)?;
-    info!("Hypha file saved successfully to: {}", path.display());
-    Ok(())
-  }
-
-  pub async fn save_async(&self) -> Result<(), FileError> {
-    let path = self.path.as_path();
-
-    info!("Attempting to save hypha file to: {}", path.display());
-
-    if let Some(parent_dir) = path.parent() {
-      fs::create_dir_all(parent_dir).await?;
-      info!("Ensured hypha directory exists: {}", parent_dir.display());
-    }
-
-    let toml_string = toml::to_string_pretty(self)?;
-    fs::write(path, toml_string).await?;
-    info!("Hypha file saved successfully to: {}", path.display());
-    Ok(())
-  }
-
-  fn uniq(&mut self) {
-    self.boards = self
-      .boards
-      .iter()
-      .cloned()
-      .unique_by(|board| board.title.clone())
-      .collect();
-
-    for board in self.boards.iter_mut() {
-      board.lists = board
-        .lists
-        .iter()
-        .cloned()
-        .unique_by(|list| list.title.clone())
-        .collect();
-    }
-
-    for board in self.boards.iter_mut() {
-      for list in board.lists.iter_mut() {
-        list.issues = list
-          .issues
-          .iter()
-          .cloned()
-          .unique_by(|issue| issue.title.clone())
-          .collect();
-      }
-    }
+      None
+    };
+    files.push((recent_file.path, file));
   }
+  Ok(files)
 }
 
-#[derive(Error, Debug)]
-pub enum FileError {
-  #[error("IO error: {0}")]
-  Io(#[from] io::Error),
-
-  #[error("TOML serialization error: {0}")]
-  TomlSer(#[from] toml::ser::Error),
-
-  #[error("TOML deserialization error: {0}")]
-  TomlDe(#[from] toml::de::Error),
-
-  #[error("Environment variable error: {0}")]
-  EnvVar(#[from] env::VarError),
-
-  #[error("Could not determine project directory for config path")]
-  Directory,
-
-  #[error("Failed to expand path '{0}': {1}")]
-  Expansion(String, String),
+async fn forget(path: &Path) -> Result<(), FileError> {
+  let mut recent = HyphaRecent::load_async(&HyphaRecent::path()?).await?;
+  if recent.remove(path) {
+    recent.save_async().await?;
+  }
+  Ok(())
 }
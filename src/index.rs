@@ -3,7 +3,7 @@ use dioxus::prelude::*;
 use crate::{
   board,
   context::{HyphaBoardContext, HyphaIssueContext},
-  issue,
+  issue, suggest,
 };
 
 #[component]
@@ -13,6 +13,7 @@ pub fn Index() -> Element {
 
   let board_ref = board_context.get();
   rsx! {
+    suggest::Today {  }
     board::Component { board_ref: board_ref }
     if let Some(issue_ref) = issue_context.get() {
       issue::Component { issue_ref: issue_ref }
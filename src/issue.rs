@@ -1,36 +1,19 @@
+use chrono::NaiveDate;
 use dioxus::prelude::*;
-use rnglib::{Language, RNG};
-use serde::{Deserialize, Serialize};
-
-use crate::{
-  context::{HyphaFileContext, HyphaIssueContext},
-  item::HyphaItem,
-  r#ref::{HyphaFileIssueRef, HyphaRef, WithHyphaRef},
+use hypha_core::{
+  dep::HyphaDep,
+  graph::{DepError, HyphaDepGraph},
+  id::HyphaId,
+  issue::{label, parse_estimate, HyphaPriority},
+  r#ref::{HyphaFileBoardRef, HyphaFileIssueRef, HyphaRef, WithHyphaRef},
+  workspace::HyphaWorkspaceIssueRef,
 };
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct HyphaIssue {
-  pub title: String,
-  #[serde(default)]
-  pub description: String,
-}
-
-impl HyphaItem for HyphaIssue {
-  fn title(&self) -> &str {
-    &self.title
-  }
-}
+use crate::context::{
+  HyphaBoardContext, HyphaFileContext, HyphaIssueContext, HyphaWorkspaceContext,
+};
 
-impl Default for HyphaIssue {
-  fn default() -> Self {
-    let rng = RNG::from(&Language::Roman);
-    let title = rng.generate_name();
-    HyphaIssue {
-      title,
-      description: String::new(),
-    }
-  }
-}
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M";
 
 #[component]
 pub fn Component(issue_ref: HyphaFileIssueRef) -> Element {
@@ -50,6 +33,13 @@ pub fn Component(issue_ref: HyphaFileIssueRef) -> Element {
       }
     }
   };
+  let tags = issue.tags.join(", ");
+  let created = issue
+    .created
+    .map(|created| created.format(TIMESTAMP_FORMAT).to_string());
+  let updated = issue
+    .updated
+    .map(|updated| updated.format(TIMESTAMP_FORMAT).to_string());
 
   rsx! {
     if edit() {
@@ -60,10 +50,6 @@ pub fn Component(issue_ref: HyphaFileIssueRef) -> Element {
             let issue_ref = issue_ref.clone();
             let value = issue.clone();
             move |e: Event<FormData>| {
-              let mut new_issue_ref = issue_ref.clone();
-              new_issue_ref.issue = e.value();
-              issue_context.set(Some(new_issue_ref));
-
               let mut value = value.clone();
               value.title = e.value();
               file_context.update_issue(WithHyphaRef {
@@ -91,6 +77,125 @@ pub fn Component(issue_ref: HyphaFileIssueRef) -> Element {
           }
         }
       }
+      p {
+        select {
+          onchange: {
+            let issue_ref = issue_ref.clone();
+            let value = issue.clone();
+            move |e: Event<FormData>| {
+              let mut value = value.clone();
+              value.priority = HyphaPriority::parse(&e.value());
+              file_context.update_issue(WithHyphaRef {
+                item: value,
+                r#ref: issue_ref.clone()
+              });
+            }
+          },
+          option {
+            value: "",
+            selected: issue.priority.is_none(),
+            "No priority"
+          }
+          for priority in HyphaPriority::ALL {
+            option {
+              value: priority.as_str(),
+              selected: issue.priority == Some(priority),
+              {priority.as_str()}
+            }
+          }
+        }
+      }
+      p {
+        input {
+          r#type: "number",
+          step: "any",
+          placeholder: "Estimate",
+          value: issue.estimate.map(|estimate| estimate.to_string()).unwrap_or_default(),
+          onchange: {
+            let issue_ref = issue_ref.clone();
+            let value = issue.clone();
+            move |e: Event<FormData>| {
+              let estimate = e.value();
+              let mut value = value.clone();
+              value.estimate = if estimate.is_empty() {
+                None
+              } else if let Some(estimate) = parse_estimate(&estimate) {
+                Some(estimate)
+              } else {
+                return;
+              };
+              file_context.update_issue(WithHyphaRef {
+                item: value,
+                r#ref: issue_ref.clone()
+              });
+            }
+          }
+        }
+      }
+      p {
+        input {
+          r#type: "date",
+          value: issue.due.map(|due| due.to_string()).unwrap_or_default(),
+          oninput: {
+            let issue_ref = issue_ref.clone();
+            let value = issue.clone();
+            move |e: Event<FormData>| {
+              let mut value = value.clone();
+              value.due = e.value().parse::<NaiveDate>().ok();
+              file_context.update_issue(WithHyphaRef {
+                item: value,
+                r#ref: issue_ref.clone()
+              });
+            }
+          }
+        }
+      }
+      p {
+        input {
+          placeholder: "Tags, separated by commas",
+          value: tags.clone(),
+          onchange: {
+            let issue_ref = issue_ref.clone();
+            let value = issue.clone();
+            move |e: Event<FormData>| {
+              let mut value = value.clone();
+              value.tags = e
+                .value()
+                .split(',')
+                .map(|tag| tag.trim().to_string())
+                .filter(|tag| !tag.is_empty())
+                .collect();
+              file_context.update_issue(WithHyphaRef {
+                item: value,
+                r#ref: issue_ref.clone()
+              });
+            }
+          }
+        }
+      }
+      p {
+        input {
+          placeholder: "Assignee",
+          value: issue.assignee.clone().unwrap_or_default(),
+          oninput: {
+            let issue_ref = issue_ref.clone();
+            let value = issue.clone();
+            move |e: Event<FormData>| {
+              let assignee = e.value();
+              let mut value = value.clone();
+              value.assignee = if assignee.is_empty() {
+                None
+              } else {
+                Some(assignee)
+              };
+              file_context.update_issue(WithHyphaRef {
+                item: value,
+                r#ref: issue_ref.clone()
+              });
+            }
+          }
+        }
+      }
       button {
         onclick: move |_| {
           *edit.write() = false;
@@ -98,8 +203,29 @@ pub fn Component(issue_ref: HyphaFileIssueRef) -> Element {
         "Preview"
       }
     } else {
-      h5 { {issue.title} }
-      p { {issue.description} }
+      h5 { {issue.title.clone()} }
+      p { {issue.description.clone()} }
+      if let Some(priority) = issue.priority {
+        p { "Priority: {priority.as_str()}" }
+      }
+      if let Some(estimate) = issue.estimate {
+        p { "Estimate: {estimate}" }
+      }
+      if let Some(due) = issue.due {
+        p { "Due: {due}" }
+      }
+      if !tags.is_empty() {
+        p { "Tags: {tags}" }
+      }
+      if let Some(assignee) = issue.assignee.clone() {
+        p { "Assignee: {assignee}" }
+      }
+      if let Some(created) = created {
+        p { "Created: {created}" }
+      }
+      if let Some(updated) = updated {
+        p { "Updated: {updated}" }
+      }
       button {
         onclick: move |_| {
           *edit.write() = true;
@@ -107,6 +233,7 @@ pub fn Component(issue_ref: HyphaFileIssueRef) -> Element {
         "Edit"
       }
     }
+    Deps { issue_ref: issue_ref.clone() }
     button {
       onclick: move |_| {
         issue_context.set(None);
@@ -115,3 +242,256 @@ pub fn Component(issue_ref: HyphaFileIssueRef) -> Element {
     }
   }
 }
+
+#[component]
+fn Deps(issue_ref: HyphaFileIssueRef) -> Element {
+  let mut file_context = use_context::<HyphaFileContext>();
+  let mut workspace_context = use_context::<HyphaWorkspaceContext>();
+  let mut board_context = use_context::<HyphaBoardContext>();
+  let mut issue_context = use_context::<HyphaIssueContext>();
+  let mut query = use_signal(String::new);
+  let mut error = use_signal(|| Option::<String>::None);
+
+  let Some(active) = workspace_context.active() else {
+    return rsx! {};
+  };
+  let file = file_context.get();
+  let mut workspace = workspace_context.get();
+  workspace.update_file(&active, file.clone());
+  let combined = workspace.combined();
+  let graph = HyphaDepGraph::new(&combined);
+  let this = HyphaWorkspaceIssueRef::new(active.clone(), issue_ref.clone());
+
+  let describe = |other: &HyphaFileIssueRef| {
+    let other = workspace.qualify(other.clone())?;
+    let label = if other.file == active {
+      label(&file, &other.file_ref())?
+    } else {
+      workspace.label(&other)?
+    };
+    Some((label, other))
+  };
+  let resolve = |ids: &[HyphaId]| {
+    ids
+      .iter()
+      .filter_map(|id| graph.issue(id))
+      .filter_map(describe)
+      .collect::<Vec<_>>()
+  };
+  let blocked_by = resolve(graph.prerequisites(&issue_ref.issue));
+  let blocks = resolve(graph.dependents(&issue_ref.issue));
+
+  let needle = query().to_lowercase();
+  let candidates = if needle.is_empty() {
+    Vec::new()
+  } else {
+    graph
+      .issues()
+      .filter(|other| other.issue != issue_ref.issue)
+      .filter_map(describe)
+      .filter(|(label, _)| label.to_lowercase().contains(&needle))
+      .collect::<Vec<_>>()
+  };
+
+  rsx! {
+    h6 { "Blocked by" }
+    for (label, other) in blocked_by {
+      div {
+        class: "flex flex-row",
+        p {
+          class: "grow cursor-pointer",
+          onclick: {
+            let other = other.clone();
+            let active = active.clone();
+            move |_| {
+              open(
+                &other,
+                &active,
+                &mut workspace_context,
+                &mut board_context,
+                &mut issue_context,
+              );
+            }
+          },
+          {label}
+        }
+        span {
+          class: "cursor-pointer",
+          onclick: {
+            let dep = HyphaDep {
+              left: other.clone(),
+              right: this.clone()
+            };
+            move |_| {
+              remove_dep(
+                &mut file_context,
+                &mut workspace_context,
+                dep.clone(),
+              );
+            }
+          },
+          "X"
+        }
+      }
+    }
+    h6 { "Blocks" }
+    for (label, other) in blocks {
+      div {
+        class: "flex flex-row",
+        p {
+          class: "grow cursor-pointer",
+          onclick: {
+            let other = other.clone();
+            let active = active.clone();
+            move |_| {
+              open(
+                &other,
+                &active,
+                &mut workspace_context,
+                &mut board_context,
+                &mut issue_context,
+              );
+            }
+          },
+          {label}
+        }
+        span {
+          class: "cursor-pointer",
+          onclick: {
+            let dep = HyphaDep {
+              left: this.clone(),
+              right: other.clone()
+            };
+            move |_| {
+              remove_dep(
+                &mut file_context,
+                &mut workspace_context,
+                dep.clone(),
+              );
+            }
+          },
+          "X"
+        }
+      }
+    }
+    p {
+      input {
+        placeholder: "Search issues to add a dependency",
+        value: query(),
+        oninput: move |e: Event<FormData>| {
+          *query.write() = e.value();
+          *error.write() = None;
+        }
+      }
+    }
+    if let Some(error) = error() {
+      p {
+        class: "error",
+        {error}
+      }
+    }
+    for (label, other) in candidates {
+      div {
+        class: "flex flex-row",
+        p {
+          class: "grow",
+          {label}
+        }
+        button {
+          onclick: {
+            let dep = HyphaDep {
+              left: other.clone(),
+              right: this.clone()
+            };
+            move |_| {
+              let added = add_dep(
+                &mut file_context,
+                &mut workspace_context,
+                dep.clone(),
+              );
+              match added {
+                Ok(()) => *query.write() = String::new(),
+                Err(err) => *error.write() = Some(err.to_string()),
+              }
+            }
+          },
+          "Blocked by"
+        }
+        button {
+          onclick: {
+            let dep = HyphaDep {
+              left: this.clone(),
+              right: other.clone()
+            };
+            move |_| {
+              let added = add_dep(
+                &mut file_context,
+                &mut workspace_context,
+                dep.clone(),
+              );
+              match added {
+                Ok(()) => *query.write() = String::new(),
+                Err(err) => *error.write() = Some(err.to_string()),
+              }
+            }
+          },
+          "Blocks"
+        }
+      }
+    }
+  }
+}
+
+// Issues of other files are opened by switching to their file.
+fn open(
+  other: &HyphaWorkspaceIssueRef,
+  active: &HyphaId,
+  workspace_context: &mut HyphaWorkspaceContext,
+  board_context: &mut HyphaBoardContext,
+  issue_context: &mut HyphaIssueContext,
+) {
+  if &other.file != active {
+    workspace_context.set_active(other.file.clone());
+    return;
+  }
+  board_context.set(HyphaFileBoardRef {
+    board: other.board.clone(),
+  });
+  issue_context.set(Some(other.file_ref()));
+}
+
+// Deps within the file are kept in the file and deps across files in the
+// workspace. Both are checked against the whole workspace with the file as it
+// is now since a dep within the file can close a cycle through other files.
+fn add_dep(
+  file_context: &mut HyphaFileContext,
+  workspace_context: &mut HyphaWorkspaceContext,
+  dep: HyphaDep<HyphaWorkspaceIssueRef>,
+) -> Result<(), DepError> {
+  if let Some(active) = workspace_context.active() {
+    workspace_context.update_file(&active, file_context.get());
+  }
+  workspace_context.check_dep(&dep)?;
+  if dep.left.file == dep.right.file {
+    return file_context.add_dep(HyphaDep {
+      left: dep.left.file_ref(),
+      right: dep.right.file_ref(),
+    });
+  }
+  workspace_context.add_dep(dep)
+}
+
+fn remove_dep(
+  file_context: &mut HyphaFileContext,
+  workspace_context: &mut HyphaWorkspaceContext,
+  dep: HyphaDep<HyphaWorkspaceIssueRef>,
+) {
+  if dep.left.file == dep.right.file {
+    file_context.remove_dep(HyphaDep {
+      left: dep.left.file_ref(),
+      right: dep.right.file_ref(),
+    });
+    return;
+  }
+  workspace_context.remove_dep(dep);
+}
@@ -1,42 +1,21 @@
 use dioxus::prelude::*;
-use rnglib::{Language, RNG};
-use serde::{Deserialize, Serialize};
-
-use crate::context::{HyphaFileContext, HyphaIssueContext};
-use crate::issue::HyphaIssue;
-use crate::item::HyphaItem;
-use crate::r#ref::{
+use hypha_core::id::HyphaId;
+use hypha_core::r#ref::{
   HyphaFileIssueRef, HyphaFileListRef, HyphaRef, WithHyphaRef,
 };
+use std::collections::HashSet;
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
-pub struct HyphaList {
-  pub title: String,
-  #[serde(default)]
-  pub issues: Vec<HyphaIssue>,
-}
-
-impl HyphaItem for HyphaList {
-  fn title(&self) -> &str {
-    &self.title
-  }
-}
-
-impl Default for HyphaList {
-  fn default() -> Self {
-    let rng = RNG::from(&Language::Roman);
-    let title = rng.generate_name();
-    HyphaList {
-      title,
-      issues: vec![HyphaIssue::default()],
-    }
-  }
-}
+use crate::context::{HyphaDragContext, HyphaFileContext, HyphaIssueContext};
 
 #[component]
-pub fn Component(list_ref: HyphaFileListRef) -> Element {
+pub fn Component(
+  list_ref: HyphaFileListRef,
+  matching: Option<HashSet<HyphaId>>,
+  cycles: Memo<HashSet<HyphaId>>,
+) -> Element {
   let mut file_context = use_context::<HyphaFileContext>();
   let mut issue_context = use_context::<HyphaIssueContext>();
+  let mut drag_context = use_context::<HyphaDragContext>();
   let mut edit = use_signal(|| false);
 
   let file = file_context.get();
@@ -51,8 +30,24 @@ pub fn Component(list_ref: HyphaFileListRef) -> Element {
       }
     }
   };
-  let board_title = list_ref.board.clone();
+  let board_id = list_ref.board.clone();
   let stage = list_ref.stage;
+  let on_drop = {
+    let list_ref = list_ref.clone();
+    move |e: Event<DragData>, index: usize| {
+      e.prevent_default();
+      if let Some(dragged) = drag_context.get() {
+        e.stop_propagation();
+        let moved = file_context.move_issue(dragged, list_ref.clone(), index);
+        if let (Some(moved), Some(selected)) = (moved, issue_context.get()) {
+          if moved.issue == selected.issue {
+            issue_context.set(Some(moved));
+          }
+        }
+      }
+      drag_context.set(None);
+    }
+  };
 
   rsx! {
     if edit() {
@@ -110,23 +105,57 @@ pub fn Component(list_ref: HyphaFileListRef) -> Element {
     div {
       class: "w-full h-px bg-indigo-500"
     }
-    for issue in list.issues.clone().iter() {
+    for (idx, issue) in list
+      .issues
+      .clone()
+      .iter()
+      .enumerate()
+      .filter(|(_, issue)| {
+        matching.as_ref().is_none_or(|matching| matching.contains(&issue.id))
+      })
+    {
       {
+        let class = if cycles.read().contains(&issue.id) {
+          "grow cursor-pointer text-red-500"
+        } else {
+          "grow cursor-pointer"
+        };
+        let issue_ref = HyphaFileIssueRef {
+          issue: issue.id.clone(),
+          list: list.id.clone(),
+          stage,
+          board: board_id.clone()
+        };
+        let mut on_drop = on_drop.clone();
         rsx! {
           div {
             class: "flex flex-row",
+            draggable: "true",
+            ondragstart: move |e: Event<DragData>| {
+              e.stop_propagation();
+              drag_context.set(Some(issue_ref.clone()));
+            },
+            ondragend: move |_| {
+              drag_context.set(None);
+            },
+            ondragover: move |e: Event<DragData>| {
+              e.prevent_default();
+            },
+            ondrop: move |e: Event<DragData>| {
+              on_drop(e, idx);
+            },
             p {
-              class: "grow cursor-pointer",
+              class: "{class}",
               onclick: {
-                let issue_title = issue.title.clone();
-                let board_title = board_title.clone();
-                let list_title = list.title.clone();
+                let issue_id = issue.id.clone();
+                let board_id = board_id.clone();
+                let list_id = list.id.clone();
                 move |_| {
                   issue_context.set(Some(HyphaFileIssueRef {
-                    issue: issue_title.clone(),
-                    list: list_title.clone(),
+                    issue: issue_id.clone(),
+                    list: list_id.clone(),
                     stage,
-                    board: board_title.clone()
+                    board: board_id.clone()
                   }));
                 }
               },
@@ -135,15 +164,15 @@ pub fn Component(list_ref: HyphaFileListRef) -> Element {
             span {
               class: "cursor-pointer",
               onclick: {
-                let issue_title = issue.title.clone();
-                let board_title = board_title.clone();
-                let list_title = list.title.clone();
+                let issue_id = issue.id.clone();
+                let board_id = board_id.clone();
+                let list_id = list.id.clone();
                 move |_| {
                   let r#ref = HyphaFileIssueRef {
-                    issue: issue_title.clone(),
-                    list: list_title.clone(),
+                    issue: issue_id.clone(),
+                    list: list_id.clone(),
                     stage,
-                    board: board_title.clone()
+                    board: board_id.clone()
                   };
                   file_context.remove_issue(r#ref);
                 }
@@ -154,6 +183,19 @@ pub fn Component(list_ref: HyphaFileListRef) -> Element {
         }
       }
     }
+    div {
+      class: "min-h-4",
+      ondragover: move |e: Event<DragData>| {
+        e.prevent_default();
+      },
+      ondrop: {
+        let mut on_drop = on_drop.clone();
+        let len = list.issues.len();
+        move |e: Event<DragData>| {
+          on_drop(e, len);
+        }
+      }
+    }
     button {
       onclick: {
         let list_ref = list_ref.clone();
@@ -7,25 +7,51 @@
 #![deny(clippy::allow_attributes_without_reason)]
 
 mod board;
-mod container;
+mod cli;
 mod context;
-mod dep;
+mod diagram;
 mod file;
 mod index;
 mod issue;
-mod item;
 mod list;
+mod merge;
+mod preferences;
 mod provider;
-mod r#ref;
+mod search;
+mod suggest;
+mod validate;
+mod view;
+mod workspace;
 
 use dioxus::prelude::*;
+use std::{env, process::ExitCode};
+
+use crate::{
+  context::HyphaFileContext, diagram::Diagram, index::Index,
+  preferences::Settings,
+};
 
 const FAVICON: Asset = asset!("assets/favicon.ico");
 const ROOT_CSS: Asset = asset!("assets/root.css");
 const TAILWIND_CSS: Asset = asset!("assets/tailwind.css");
 
-fn main() {
+#[derive(Routable, Debug, Clone, PartialEq)]
+enum Route {
+  #[layout(Nav)]
+  #[route("/")]
+  Index {},
+  #[route("/diagram")]
+  Diagram {},
+  #[route("/settings")]
+  Settings {},
+}
+
+fn main() -> ExitCode {
+  if env::args_os().nth(1).is_some() {
+    return cli::run();
+  }
   launch(App);
+  ExitCode::SUCCESS
 }
 
 #[component]
@@ -39,13 +65,67 @@ fn App() -> Element {
 
     div {
       class: "mx-auto mt-2 container flex flex-col",
-      provider::FileProvider {
-        provider::BoardProvider {
-          provider::IssueProvider {
-            index::Index {  }
+      provider::WorkspaceProvider {
+        provider::FileProvider {
+          provider::BoardProvider {
+            provider::IssueProvider {
+              provider::DragProvider {
+                Router::<Route> {  }
+              }
+            }
           }
         }
       }
     }
   }
 }
+
+#[component]
+fn Nav() -> Element {
+  let mut file_context = use_context::<HyphaFileContext>();
+
+  rsx! {
+    div {
+      class: "flex flex-col",
+      tabindex: "0",
+      onkeydown: move |e: Event<KeyboardData>| {
+        let modifiers = e.modifiers();
+        if e.code() != Code::KeyZ
+          || !(modifiers.ctrl() || modifiers.meta())
+        {
+          return;
+        }
+        e.prevent_default();
+        if modifiers.shift() {
+          file_context.redo();
+        } else {
+          file_context.undo();
+        }
+      },
+      nav {
+        class: "flex flex-row justify-center gap-4",
+        Link { to: Route::Index {}, "Board" }
+        Link { to: Route::Diagram {}, "Dependencies" }
+        Link { to: Route::Settings {}, "Settings" }
+        button {
+          disabled: !file_context.can_undo(),
+          onclick: move |_| {
+            file_context.undo();
+          },
+          "Undo"
+        }
+        button {
+          disabled: !file_context.can_redo(),
+          onclick: move |_| {
+            file_context.redo();
+          },
+          "Redo"
+        }
+        file::SaveStatus {}
+      }
+      workspace::Switcher {}
+      search::Search {}
+      Outlet::<Route> {  }
+    }
+  }
+}
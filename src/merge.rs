@@ -0,0 +1,90 @@
+use dioxus::prelude::*;
+use hypha_core::{
+  file::HyphaFile,
+  merge::{merge, HyphaConflictKey, HyphaSide},
+};
+use std::collections::HashMap;
+
+#[component]
+pub fn Prompt(
+  base: HyphaFile,
+  ours: HyphaFile,
+  theirs: HyphaFile,
+  on_resolve: EventHandler<HyphaFile>,
+) -> Element {
+  let mut choices = use_signal(HashMap::<HyphaConflictKey, HyphaSide>::new);
+
+  let resolved = merge(&base, &ours, &theirs, |key| {
+    choices.read().get(key).copied().unwrap_or(HyphaSide::Ours)
+  });
+  let conflicts = resolved
+    .conflicts
+    .iter()
+    .map(|conflict| {
+      let side = match choices.read().get(&conflict.key) {
+        Some(HyphaSide::Theirs) => "theirs",
+        _ => "ours",
+      };
+      (conflict.label.clone(), conflict.key.clone(), side)
+    })
+    .collect::<Vec<_>>();
+
+  rsx! {
+    div {
+      class: "flex flex-col border-1px p-2 m-2",
+      h3 { "The file was changed outside of hypha" }
+      if conflicts.is_empty() {
+        p { "The changes do not conflict with yours." }
+      }
+      for (label, key, side) in conflicts {
+        div {
+          class: "flex flex-row",
+          p {
+            class: "grow",
+            {label}
+          }
+          select {
+            value: side,
+            onchange: move |e: Event<FormData>| {
+              let side = if e.value() == "theirs" {
+                HyphaSide::Theirs
+              } else {
+                HyphaSide::Ours
+              };
+              choices.write().insert(key.clone(), side);
+            },
+            option { value: "ours", "Keep mine" }
+            option { value: "theirs", "Take theirs" }
+          }
+        }
+      }
+      div {
+        class: "flex flex-row gap-2",
+        button {
+          onclick: {
+            let file = resolved.file.clone();
+            move |_| on_resolve(file.clone())
+          },
+          "Merge"
+        }
+        button {
+          onclick: {
+            let file = ours.clone();
+            move |_| on_resolve(file.clone())
+          },
+          "Keep mine"
+        }
+        button {
+          onclick: {
+            let file = HyphaFile {
+              path: ours.path.clone(),
+              ..theirs.clone()
+            };
+            move |_| on_resolve(file.clone())
+          },
+          "Take theirs"
+        }
+      }
+    }
+  }
+}
@@ -0,0 +1,164 @@
+use dioxus::prelude::*;
+use hypha_core::{
+  preferences::{HyphaPreferences, HyphaPreferencesOverride},
+  r#ref::{HyphaRef, WithHyphaRef},
+};
+
+use crate::context::{HyphaBoardContext, HyphaFileContext};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum HyphaPreference {
+  Priority,
+  Age,
+  Due,
+  Unblocking,
+  Capacity,
+}
+
+impl HyphaPreference {
+  const ALL: [HyphaPreference; 5] = [
+    HyphaPreference::Priority,
+    HyphaPreference::Age,
+    HyphaPreference::Due,
+    HyphaPreference::Unblocking,
+    HyphaPreference::Capacity,
+  ];
+
+  fn label(self) -> &'static str {
+    match self {
+      HyphaPreference::Priority => "Priority weight",
+      HyphaPreference::Age => "Age weight",
+      HyphaPreference::Due => "Due date weight",
+      HyphaPreference::Unblocking => "Unblocking weight",
+      HyphaPreference::Capacity => "Daily capacity",
+    }
+  }
+
+  fn get(self, preferences: &HyphaPreferences) -> f64 {
+    match self {
+      HyphaPreference::Priority => preferences.priority,
+      HyphaPreference::Age => preferences.age,
+      HyphaPreference::Due => preferences.due,
+      HyphaPreference::Unblocking => preferences.unblocking,
+      HyphaPreference::Capacity => preferences.capacity,
+    }
+  }
+
+  fn set(self, preferences: &mut HyphaPreferences, value: f64) {
+    match self {
+      HyphaPreference::Priority => preferences.priority = value,
+      HyphaPreference::Age => preferences.age = value,
+      HyphaPreference::Due => preferences.due = value,
+      HyphaPreference::Unblocking => preferences.unblocking = value,
+      HyphaPreference::Capacity => preferences.capacity = value,
+    }
+  }
+
+  fn get_override(self, overrides: &HyphaPreferencesOverride) -> Option<f64> {
+    match self {
+      HyphaPreference::Priority => overrides.priority,
+      HyphaPreference::Age => overrides.age,
+      HyphaPreference::Due => overrides.due,
+      HyphaPreference::Unblocking => overrides.unblocking,
+      HyphaPreference::Capacity => overrides.capacity,
+    }
+  }
+
+  fn set_override(
+    self,
+    overrides: &mut HyphaPreferencesOverride,
+    value: Option<f64>,
+  ) {
+    match self {
+      HyphaPreference::Priority => overrides.priority = value,
+      HyphaPreference::Age => overrides.age = value,
+      HyphaPreference::Due => overrides.due = value,
+      HyphaPreference::Unblocking => overrides.unblocking = value,
+      HyphaPreference::Capacity => overrides.capacity = value,
+    }
+  }
+}
+
+#[component]
+pub fn Settings() -> Element {
+  let mut file_context = use_context::<HyphaFileContext>();
+  let board_context = use_context::<HyphaBoardContext>();
+
+  let file = file_context.get();
+  let board_ref = board_context.get();
+  let board = board_ref.get_item_from_container(&file).cloned();
+  let rows = HyphaPreference::ALL.map(|preference| {
+    let overridden = board
+      .as_ref()
+      .and_then(|board| preference.get_override(&board.preferences))
+      .map(|value| value.to_string())
+      .unwrap_or_default();
+    (preference, preference.get(&file.preferences), overridden)
+  });
+
+  rsx! {
+    h3 { "Preferences" }
+    for (preference, value, _) in rows.clone() {
+      div {
+        class: "flex flex-row",
+        label {
+          class: "grow",
+          {preference.label()}
+        }
+        input {
+          r#type: "number",
+          step: "any",
+          value: "{value}",
+          onchange: {
+            let preferences = file.preferences.clone();
+            move |e: Event<FormData>| {
+              if let Ok(value) = e.value().parse::<f64>() {
+                let mut preferences = preferences.clone();
+                preference.set(&mut preferences, value);
+                file_context.update_preferences(preferences);
+              }
+            }
+          }
+        }
+      }
+    }
+    if let Some(board) = board {
+      h3 { "Overrides for {board.title}" }
+      for (preference, value, overridden) in rows {
+        div {
+          class: "flex flex-row",
+          label {
+            class: "grow",
+            {preference.label()}
+          }
+          input {
+            r#type: "number",
+            step: "any",
+            placeholder: "{value}",
+            value: overridden,
+            onchange: {
+              let board = board.clone();
+              let board_ref = board_ref.clone();
+              move |e: Event<FormData>| {
+                let value = e.value();
+                let value = if value.is_empty() {
+                  None
+                } else if let Ok(value) = value.parse::<f64>() {
+                  Some(value)
+                } else {
+                  return;
+                };
+                let mut board = board.clone();
+                preference.set_override(&mut board.preferences, value);
+                file_context.update_board(WithHyphaRef {
+                  item: board,
+                  r#ref: board_ref.clone()
+                });
+              }
+            }
+          }
+        }
+      }
+    }
+  }
+}
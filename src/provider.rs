@@ -1,62 +1,118 @@
 use std::path::{Path, PathBuf};
-use std::str::FromStr;
+use std::time::{Duration, SystemTime};
 
+use chrono::Local;
 use dioxus::logger::tracing::*;
 use dioxus::prelude::*;
+use hypha_core::file::{FileError, HyphaFile};
+use hypha_core::history::HyphaHistory;
+use hypha_core::id::HyphaId;
+use hypha_core::markdown;
+use hypha_core::r#ref::{HyphaFileBoardRef, HyphaFileIssueRef};
+use hypha_core::recent::HyphaRecent;
+use hypha_core::search::HyphaSearchIndex;
+use hypha_core::validate::HyphaReport;
+use hypha_core::workspace::HyphaWorkspace;
+use tokio::fs;
 
-use crate::context::{HyphaBoardContext, HyphaFileContext, HyphaIssueContext};
-use crate::file::HyphaFile;
-use crate::r#ref::{HyphaFileBoardRef, HyphaFileIssueRef};
+use crate::context::{
+  HyphaBoardContext, HyphaDragContext, HyphaFileContext, HyphaIssueContext,
+  HyphaSaveContext, HyphaWorkspaceContext,
+};
+use crate::file::{self, HyphaSaveStatus};
+use crate::merge;
+use crate::validate;
+
+const BACKUP_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+#[component]
+pub fn WorkspaceProvider(children: Element) -> Element {
+  let workspace_resource = use_resource(load_workspace);
+
+  match &*workspace_resource.read() {
+    Some(workspace) => rsx! {
+      WorkspaceProviderInner {
+        workspace: workspace.clone(),
+        {children}
+      }
+    },
+    None => rsx! {
+      p { "Loading..." }
+    },
+  }
+}
+
+#[component]
+fn WorkspaceProviderInner(
+  workspace: HyphaWorkspace,
+  children: Element,
+) -> Element {
+  let active =
+    use_signal(|| workspace.files.first().map(|file| file.id.clone()));
+  let signal = use_signal(|| workspace);
+  use_context_provider(|| HyphaWorkspaceContext::new(signal, active));
+
+  rsx! {
+    { children }
+  }
+}
 
 #[component]
 pub fn FileProvider(children: Element) -> Element {
-  let mut path = use_signal(|| Option::<PathBuf>::None);
+  let mut workspace_context = use_context::<HyphaWorkspaceContext>();
+  let active = use_memo(move || workspace_context.active_path());
+  // The start screen shows on launch until a file is picked from it.
+  let mut picked = use_signal(|| false);
 
   let mut file_resource = use_resource(move || async move {
-    if let Some(path) = path() {
-      return load(path.as_path()).await;
-    }
-
-    match HyphaFile::path() {
-      Ok(path) => load(path.as_path()).await,
-      Err(e) => {
-        error!("Failed to determine hypha file path: {e}. Letting user pick.");
-        None
-      }
+    if !picked() {
+      return None;
     }
+    let (id, path) = active()?;
+    Some(
+      load(path.as_path())
+        .await
+        .map(|loaded| (id, loaded))
+        .map_err(|e| e.to_string()),
+    )
   });
 
   match *file_resource.state().read() {
     UseResourceState::Ready => match &*file_resource.read() {
-      Some(file) => match file {
-        Some(file) => rsx! {
-          FileProviderInner {
-            file: file.clone(),
-            {children}
-          }
-        },
-        None => {
-          rsx! {
-            p {
-              input {
-                value: path()
-                  .and_then(|path| path.to_str().map(|path| path.to_string()))
-                  .unwrap_or(String::new()),
-                oninput: move |e| {
-                  let Ok(new_path) = PathBuf::from_str(e.value().as_str());
-                  *path.write() = Some(new_path);
-                }
-              }
+      Some(Some(Ok((id, (file, report))))) => rsx! {
+        FileProviderInner {
+          key: "{id}",
+          id: id.clone(),
+          file: file.clone(),
+          report: report.clone(),
+          {children}
+        }
+      },
+      Some(loaded) => {
+        let error = loaded
+          .as_ref()
+          .and_then(|loaded| loaded.as_ref().err().cloned());
+        rsx! {
+          file::Start {
+            error: error,
+            on_open: move |path: PathBuf| {
+              let id = workspace_context.add_file(path);
+              workspace_context.set_active(id);
+              picked.set(true);
+              file_resource.restart();
             }
-            button {
-              onclick: move |_| {
+          }
+          if let Some((_, target)) = active().filter(|_| picked()) {
+            Backups {
+              path: target,
+              on_restore: move |_| {
                 file_resource.restart();
-              },
-              "Add"
+              }
             }
           }
         }
-      },
+      }
       None => rsx! {
         p { "Loading..." }
       },
@@ -70,17 +126,130 @@ pub fn FileProvider(children: Element) -> Element {
 }
 
 #[component]
-fn FileProviderInner(file: HyphaFile, children: Element) -> Element {
+fn FileProviderInner(
+  id: HyphaId,
+  file: HyphaFile,
+  report: HyphaReport,
+  children: Element,
+) -> Element {
+  let mut saved = use_signal(|| file.clone());
   let signal = use_signal(|| file);
-  use_context_provider(|| HyphaFileContext::new(signal));
+  let history = use_signal(HyphaHistory::default);
+  let index = use_signal(|| HyphaSearchIndex::new(&signal.peek()));
+  let status = use_signal(|| HyphaSaveStatus::Saved);
+  let mut file_context =
+    use_context_provider(|| HyphaFileContext::new(signal, history, index));
+  let mut save_context = use_context_provider(|| HyphaSaveContext::new(status));
+  let mut workspace_context = use_context::<HyphaWorkspaceContext>();
+
+  let interval = use_hook(HyphaFile::autosave_interval);
+  let mut pending = use_signal(|| Option::<Task>::None);
+  let mut incoming = use_signal(|| Option::<(HyphaFile, HyphaFile)>::None);
+  let mut report =
+    use_signal(|| Some(report).filter(|report| !report.is_empty()));
+  use_effect(move || {
+    let file = signal();
+    if file == *saved.peek() || incoming.peek().is_some() {
+      return;
+    }
+
+    if let Some(task) = pending.write().take() {
+      task.cancel();
+    }
+    save_context.set(HyphaSaveStatus::Pending);
+    *pending.write() = Some(spawn(async move {
+      tokio::time::sleep(interval).await;
+      save_context.set(HyphaSaveStatus::Saving);
+      match file.save_async().await {
+        Ok(()) => {
+          *saved.write() = file;
+          save_context.set(HyphaSaveStatus::Saved);
+        }
+        Err(err) => {
+          error!("Failed to autosave hypha file: {}", err);
+          save_context.set(HyphaSaveStatus::Failed(err.to_string()));
+        }
+      }
+      *pending.write() = None;
+    }));
+  });
+
+  // Our own saves show up as changes too but then match either the saved or
+  // the in-app file.
+  use_future(move || async move {
+    let mut last = modified(signal.peek().path.as_path()).await;
+    loop {
+      tokio::time::sleep(WATCH_INTERVAL).await;
+      let path = signal.peek().path.clone();
+      let current = modified(path.as_path()).await;
+      if current.is_none() || current == last {
+        continue;
+      }
+      last = current;
+
+      let theirs = match HyphaFile::load_async(path.as_path()).await {
+        Ok(theirs) => theirs,
+        Err(err) => {
+          warn!("Failed to reload externally changed hypha file: {err}");
+          continue;
+        }
+      };
+      let base = match &*incoming.peek() {
+        Some((base, _)) => base.clone(),
+        None => saved.peek().clone(),
+      };
+      let ours = signal.peek().clone();
+      if theirs == base || theirs == ours {
+        *saved.write() = theirs;
+        *incoming.write() = None;
+      } else if ours == base {
+        info!("Reloading externally changed hypha file");
+        *saved.write() = theirs.clone();
+        file_context.replace(theirs);
+      } else {
+        info!("Hypha file changed externally while there are unsaved edits");
+        *incoming.write() = Some((base, theirs));
+      }
+    }
+  });
+
+  use_effect(move || {
+    let file = signal();
+    workspace_context.update_file(&id, file);
+  });
 
   use_drop(move || {
-    if let Err(err) = signal().save() {
+    let file = signal.peek().clone();
+    if file == *saved.peek() {
+      return;
+    }
+    if let Err(err) = file.save() {
       error!("Failed to save hypha file: {}", err);
     }
   });
 
   rsx! {
+    if let Some(problems) = report() {
+      validate::Report {
+        report: problems,
+        on_close: move |_| {
+          *report.write() = None;
+        }
+      }
+    }
+    if let Some((base, theirs)) = incoming() {
+      merge::Prompt {
+        base: base,
+        ours: signal(),
+        theirs: theirs,
+        on_resolve: move |file: HyphaFile| {
+          if let Some((_, theirs)) = incoming.write().take() {
+            *saved.write() = theirs;
+          }
+          file_context.replace(file);
+        }
+      }
+    }
     { children }
   }
 }
@@ -90,7 +259,7 @@ pub fn BoardProvider(children: Element) -> Element {
   let mut context = use_context::<HyphaFileContext>();
   let mut board_signal = use_signal(|| {
     context.get().boards.first().map(|board| HyphaFileBoardRef {
-      board: board.title.clone(),
+      board: board.id.clone(),
     })
   });
 
@@ -117,9 +286,9 @@ pub fn BoardProvider(children: Element) -> Element {
             p {
               class: "cursor-pointer grow",
               onclick: {
-                let board_title = board.title.clone();
+                let board_id = board.id.clone();
                 move |_| {
-                  *board_signal.write() = Some(HyphaFileBoardRef { board: board_title.clone() });
+                  *board_signal.write() = Some(HyphaFileBoardRef { board: board_id.clone() });
                 }
               },
               "{board.title.clone()}"
@@ -127,9 +296,9 @@ pub fn BoardProvider(children: Element) -> Element {
             span {
               class: "cursor-pointer",
               onclick: {
-                let board_title = board.title.clone();
+                let board_id = board.id.clone();
                 move |_| {
-                  context.remove_board(HyphaFileBoardRef { board: board_title.clone() });
+                  context.remove_board(HyphaFileBoardRef { board: board_id.clone() });
                 }
               },
               "X"
@@ -139,7 +308,8 @@ pub fn BoardProvider(children: Element) -> Element {
         p {
           class: "cursor-pointer",
           onclick: move |_| {
-            context.add_board();
+            let count = context.get().boards.len().saturating_add(1);
+            context.add_board(format!("Board {count}"));
           },
           "Add"
         }
@@ -168,23 +338,145 @@ pub fn IssueProvider(children: Element) -> Element {
   }
 }
 
-async fn load(path: &Path) -> Option<HyphaFile> {
-  match HyphaFile::load_async(path).await {
-    Ok(file) => Some(file),
+#[component]
+pub fn DragProvider(children: Element) -> Element {
+  let signal = use_signal(|| Option::<HyphaFileIssueRef>::None);
+  use_context_provider(|| HyphaDragContext::new(signal));
+
+  rsx! {
+    { children }
+  }
+}
+
+#[component]
+fn Backups(path: PathBuf, on_restore: EventHandler<()>) -> Element {
+  let mut error = use_signal(|| Option::<String>::None);
+  let backups = use_resource(use_reactive!(|(path,)| async move {
+    HyphaFile::backups_async(path.as_path()).await
+  }));
+
+  let backups = match &*backups.read() {
+    Some(Ok(backups)) => backups
+      .iter()
+      .map(|backup| {
+        let label = backup
+          .timestamp
+          .with_timezone(&Local)
+          .format(BACKUP_FORMAT)
+          .to_string();
+        (label, backup.clone())
+      })
+      .collect::<Vec<_>>(),
+    Some(Err(e)) => {
+      return rsx! {
+        p {
+          class: "error",
+          "Failed to list backups: {e}"
+        }
+      };
+    }
+    None => Vec::new(),
+  };
+  if backups.is_empty() {
+    return rsx! {};
+  }
+
+  rsx! {
+    h3 { "Restore backup" }
+    for (label, backup) in backups {
+      div {
+        class: "flex flex-row w-64",
+        p {
+          class: "grow",
+          {label}
+        }
+        button {
+          onclick: {
+            let path = path.clone();
+            move |_| {
+              let path = path.clone();
+              let backup = backup.clone();
+              spawn(async move {
+                match HyphaFile::restore_async(path.as_path(), &backup).await {
+                  Ok(_) => on_restore(()),
+                  Err(e) => {
+                    error!("Failed to restore backup: {e}");
+                    *error.write() = Some(e.to_string());
+                  }
+                }
+              });
+            }
+          },
+          "Restore"
+        }
+      }
+    }
+    if let Some(error) = error() {
+      p {
+        class: "error",
+        "Failed to restore backup: {error}"
+      }
+    }
+  }
+}
+
+async fn modified(path: &Path) -> Option<SystemTime> {
+  if markdown::is_directory(path) {
+    return markdown::modified_async(path).await;
+  }
+  fs::metadata(path)
+    .await
+    .and_then(|metadata| metadata.modified())
+    .ok()
+}
+
+async fn load(path: &Path) -> Result<(HyphaFile, HyphaReport), FileError> {
+  match HyphaFile::load_validated_async(path).await {
+    Ok(loaded) => {
+      if let Err(e) = remember(path).await {
+        warn!("Failed to add {} to recent files: {e}", path.display());
+      }
+      Ok(loaded)
+    }
     Err(e) => {
       error!(
-        "Failed to load config file at {}: {}. Using default.",
+        "Failed to load config file at {}: {}. Letting user pick.",
         path.display(),
         e
       );
-      let default_file = HyphaFile {
-        path: path.to_path_buf(),
-        ..HyphaFile::default()
-      };
-      if let Err(save_err) = default_file.save_async().await {
-        error!("Failed to save default config file: {save_err}");
-      }
-      Some(default_file)
+      Err(e)
+    }
+  }
+}
+
+async fn remember(path: &Path) -> Result<(), FileError> {
+  let mut recent = HyphaRecent::load_async(&HyphaRecent::path()?).await?;
+  recent.open(path);
+  recent.save_async().await
+}
+
+// Falls back to a workspace with just the default file when the workspace can
+// not be loaded. It has no path so it is never saved and a broken one is not
+// overwritten.
+async fn load_workspace() -> HyphaWorkspace {
+  let loaded = match HyphaWorkspace::path() {
+    Ok(path) => HyphaWorkspace::load_async(path.as_path()).await,
+    Err(e) => Err(e),
+  };
+  let workspace = loaded.unwrap_or_else(|e| {
+    error!("Failed to load workspace: {e}. Using the default file.");
+    let mut workspace = HyphaWorkspace::default();
+    if let Ok(path) = HyphaFile::path() {
+      workspace.add_file(path);
+    }
+    workspace
+  });
+
+  match workspace.clone().load_files_async().await {
+    Ok(workspace) => workspace,
+    Err(e) => {
+      error!("Failed to load workspace files: {e}");
+      workspace
     }
   }
 }
@@ -0,0 +1,68 @@
+use dioxus::prelude::*;
+use hypha_core::{issue::label, search::HyphaSearchTarget};
+
+use crate::{
+  context::{HyphaBoardContext, HyphaFileContext, HyphaIssueContext},
+  Route,
+};
+
+const RESULT_COUNT: usize = 10;
+
+#[component]
+pub fn Search() -> Element {
+  let file_context = use_context::<HyphaFileContext>();
+  let mut board_context = use_context::<HyphaBoardContext>();
+  let mut issue_context = use_context::<HyphaIssueContext>();
+  let navigator = use_navigator();
+  let mut query = use_signal(String::new);
+
+  let file = file_context.get();
+  let hits = file_context
+    .search(&query())
+    .into_iter()
+    .take(RESULT_COUNT)
+    .map(|hit| {
+      let label = match &hit.target {
+        HyphaSearchTarget::Board(_) => format!("Board: {}", hit.title),
+        HyphaSearchTarget::List(_) => format!("List: {}", hit.title),
+        HyphaSearchTarget::Issue(issue_ref) => {
+          label(&file, issue_ref).unwrap_or_else(|| hit.title.clone())
+        }
+      };
+      (label, hit.target)
+    })
+    .collect::<Vec<_>>();
+
+  rsx! {
+    div {
+      class: "flex flex-col items-center",
+      input {
+        placeholder: "Search",
+        value: query(),
+        oninput: move |e: Event<FormData>| {
+          *query.write() = e.value();
+        },
+        onkeydown: move |e: Event<KeyboardData>| {
+          if e.key() == Key::Escape {
+            *query.write() = String::new();
+          }
+        }
+      }
+      if !query().trim().is_empty() && hits.is_empty() {
+        p { "No results" }
+      }
+      for (label, target) in hits {
+        p {
+          class: "cursor-pointer",
+          onclick: move |_| {
+            board_context.set(target.board());
+            issue_context.set(target.issue());
+            *query.write() = String::new();
+            navigator.push(Route::Index {});
+          },
+          {label}
+        }
+      }
+    }
+  }
+}
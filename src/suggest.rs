@@ -0,0 +1,68 @@
+use chrono::Local;
+use dioxus::prelude::*;
+use hypha_core::{
+  issue::label, r#ref::HyphaFileBoardRef, suggest::suggest_workspace,
+};
+
+use crate::context::{
+  HyphaBoardContext, HyphaFileContext, HyphaIssueContext, HyphaWorkspaceContext,
+};
+
+#[component]
+pub fn Today() -> Element {
+  let file_context = use_context::<HyphaFileContext>();
+  let mut workspace_context = use_context::<HyphaWorkspaceContext>();
+  let mut board_context = use_context::<HyphaBoardContext>();
+  let mut issue_context = use_context::<HyphaIssueContext>();
+
+  let file = file_context.get();
+  let active = workspace_context.active();
+  let mut workspace = workspace_context.get();
+  if let Some(active) = active.as_ref() {
+    workspace.update_file(active, file.clone());
+  }
+  let suggestions = suggest_workspace(&workspace, Local::now().date_naive())
+    .into_iter()
+    .filter_map(|issue_ref| {
+      let local = active.as_ref() == Some(&issue_ref.file);
+      let label = if local {
+        label(&file, &issue_ref.file_ref())?
+      } else {
+        workspace.label(&issue_ref)?
+      };
+      Some((label, local, issue_ref))
+    })
+    .collect::<Vec<_>>();
+
+  rsx! {
+    div {
+      class: "flex flex-col border-1px w-64 p-2 m-2",
+      h3 { "Today" }
+      div {
+        class: "w-full h-px bg-indigo-500"
+      }
+      if suggestions.is_empty() {
+        p { "Nothing to focus on" }
+      }
+      for (label, local, suggestion) in suggestions {
+        p {
+          class: "cursor-pointer",
+          onclick: {
+            let suggestion = suggestion.clone();
+            move |_| {
+              if !local {
+                workspace_context.set_active(suggestion.file.clone());
+                return;
+              }
+              board_context.set(HyphaFileBoardRef {
+                board: suggestion.board.clone()
+              });
+              issue_context.set(Some(suggestion.file_ref()));
+            }
+          },
+          {label}
+        }
+      }
+    }
+  }
+}
@@ -0,0 +1,43 @@
+use dioxus::prelude::*;
+use hypha_core::validate::{fix, HyphaReport};
+
+use crate::context::HyphaFileContext;
+
+#[component]
+pub fn Report(report: HyphaReport, on_close: EventHandler<()>) -> Element {
+  let mut file_context = use_context::<HyphaFileContext>();
+
+  let problems = report
+    .problems
+    .iter()
+    .map(|problem| problem.to_string())
+    .collect::<Vec<_>>();
+
+  rsx! {
+    div {
+      class: "flex flex-col border-1px p-2 m-2",
+      h3 { "Problems found while loading" }
+      for problem in problems {
+        p { {problem} }
+      }
+      div {
+        class: "flex flex-row gap-2",
+        button {
+          onclick: move |_| {
+            let mut file = file_context.get();
+            fix(&mut file);
+            file_context.replace(file);
+            on_close(());
+          },
+          "Fix"
+        }
+        button {
+          onclick: move |_| {
+            on_close(());
+          },
+          "Keep"
+        }
+      }
+    }
+  }
+}
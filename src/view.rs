@@ -0,0 +1,161 @@
+use chrono::Local;
+use dioxus::prelude::*;
+use hypha_core::{
+  id::HyphaId,
+  issue::label,
+  query::HyphaQueryScope,
+  view::{HyphaViewGroup, HyphaViewSort},
+};
+
+use crate::context::{HyphaFileContext, HyphaIssueContext};
+
+#[component]
+pub fn Component(id: HyphaId, on_remove: EventHandler<()>) -> Element {
+  let mut file_context = use_context::<HyphaFileContext>();
+  let mut issue_context = use_context::<HyphaIssueContext>();
+
+  let file = file_context.get();
+  let view = match file.views.iter().find(|view| view.id == id) {
+    Some(view) => view.clone(),
+    None => {
+      return rsx! {
+        p {
+          class: "error",
+          "View {id} not found"
+        }
+      };
+    }
+  };
+  let scope = HyphaQueryScope::new(&file, Local::now().date_naive());
+  let lists = view.lists(&scope).map(|lists| {
+    lists
+      .into_iter()
+      .map(|list| {
+        let issues = list
+          .issues
+          .into_iter()
+          .filter_map(|issue_ref| Some((label(&file, &issue_ref)?, issue_ref)))
+          .collect::<Vec<_>>();
+        (list.title, issues)
+      })
+      .collect::<Vec<_>>()
+  });
+
+  rsx! {
+    div {
+      class: "w-full flex flex-row justify-center gap-2",
+      input {
+        placeholder: "Title",
+        value: view.title.clone(),
+        oninput: {
+          let view = view.clone();
+          move |e: Event<FormData>| {
+            let mut view = view.clone();
+            view.title = e.value();
+            file_context.update_view(view);
+          }
+        }
+      }
+      input {
+        placeholder: "Query like tag:bug blocked:false due<7d",
+        value: view.query.clone(),
+        oninput: {
+          let view = view.clone();
+          move |e: Event<FormData>| {
+            let mut view = view.clone();
+            view.query = e.value();
+            file_context.update_view(view);
+          }
+        }
+      }
+      label {
+        "Sort "
+        select {
+          onchange: {
+            let view = view.clone();
+            move |e: Event<FormData>| {
+              if let Some(sort) = HyphaViewSort::parse(&e.value()) {
+                let mut view = view.clone();
+                view.sort = sort;
+                file_context.update_view(view);
+              }
+            }
+          },
+          for sort in HyphaViewSort::ALL {
+            option {
+              value: sort.as_str(),
+              selected: view.sort == sort,
+              {sort.as_str()}
+            }
+          }
+        }
+      }
+      label {
+        "Group "
+        select {
+          onchange: {
+            let view = view.clone();
+            move |e: Event<FormData>| {
+              if let Some(group) = HyphaViewGroup::parse(&e.value()) {
+                let mut view = view.clone();
+                view.group = group;
+                file_context.update_view(view);
+              }
+            }
+          },
+          for group in HyphaViewGroup::ALL {
+            option {
+              value: group.as_str(),
+              selected: view.group == group,
+              {group.as_str()}
+            }
+          }
+        }
+      }
+      button {
+        onclick: {
+          let id = view.id.clone();
+          move |_| {
+            file_context.remove_view(&id);
+            on_remove(());
+          }
+        },
+        "Delete"
+      }
+    }
+    match lists {
+      Ok(lists) => rsx! {
+        div {
+          class: "w-full flex flex-row justify-center items-start",
+          if lists.is_empty() {
+            p { "No issues match" }
+          }
+          for (title, issues) in lists {
+            div {
+              class: "flex flex-col border-1px w-64 p-2 m-2",
+              h3 { {title} }
+              div {
+                class: "w-full h-px bg-indigo-500"
+              }
+              for (text, issue_ref) in issues {
+                p {
+                  class: "cursor-pointer",
+                  onclick: move |_| {
+                    issue_context.set(Some(issue_ref.clone()));
+                  },
+                  {text}
+                }
+              }
+            }
+          }
+        }
+      },
+      Err(e) => rsx! {
+        p {
+          class: "error",
+          {e.to_string()}
+        }
+      },
+    }
+  }
+}
@@ -0,0 +1,72 @@
+use dioxus::prelude::*;
+use hypha_core::file::expand;
+
+use crate::context::HyphaWorkspaceContext;
+
+#[component]
+pub fn Switcher() -> Element {
+  let mut workspace_context = use_context::<HyphaWorkspaceContext>();
+  let mut path = use_signal(String::new);
+
+  let expanded = expand(&path());
+  let active = workspace_context.active();
+  let files = workspace_context
+    .get()
+    .files
+    .into_iter()
+    .map(|file| (active.as_ref() == Some(&file.id), file))
+    .collect::<Vec<_>>();
+
+  rsx! {
+    div {
+      class: "flex flex-row justify-center gap-2",
+      for (is_active, file) in files {
+        div {
+          class: "flex flex-row gap-1",
+          button {
+            disabled: is_active,
+            title: file.path.display().to_string(),
+            onclick: {
+              let id = file.id.clone();
+              move |_| {
+                workspace_context.set_active(id.clone());
+              }
+            },
+            "{file.name}"
+          }
+          if !is_active {
+            span {
+              class: "cursor-pointer",
+              onclick: {
+                let id = file.id.clone();
+                move |_| {
+                  workspace_context.remove_file(&id);
+                }
+              },
+              "X"
+            }
+          }
+        }
+      }
+      input {
+        placeholder: "Path to another hypha file or directory",
+        value: path(),
+        oninput: move |e: Event<FormData>| {
+          *path.write() = e.value();
+        }
+      }
+      button {
+        disabled: expanded.is_err(),
+        title: expanded.as_ref().err().map(|e| e.to_string()),
+        onclick: move |_| {
+          if let Ok(new_path) = expand(&path()) {
+            let id = workspace_context.add_file(new_path);
+            workspace_context.set_active(id);
+            *path.write() = String::new();
+          }
+        },
+        "Open"
+      }
+    }
+  }
+}